@@ -0,0 +1,190 @@
+use crate::ytdlp::{AppSettings, YtDlpOptions};
+use serde::{Deserialize, Serialize};
+
+/// A time-of-day window with its own speed limit.
+/// `start`/`end` are local "HH:MM"; windows where `end <= start` wrap past midnight.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthWindow {
+    pub start: String,
+    pub end: String,
+    /// yt-dlp `--limit-rate` value ("2M", "500K", bare numbers are KiB/s).
+    /// Empty means unlimited, "0" pauses downloads for the whole window.
+    pub rate: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// The limit a download should run with right now.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BandwidthLimit {
+    Unlimited,
+    Rate(String),
+    Paused,
+}
+
+impl BandwidthLimit {
+    /// Compact form stored on the task (`"0"` = paused, `""` = unlimited).
+    pub fn as_applied(&self) -> String {
+        match self {
+            BandwidthLimit::Unlimited => String::new(),
+            BandwidthLimit::Rate(r) => r.clone(),
+            BandwidthLimit::Paused => "0".to_string(),
+        }
+    }
+}
+
+/// Parses "HH:MM" into minutes since midnight.
+pub fn parse_hhmm(value: &str) -> Option<u32> {
+    let (h, m) = value.trim().split_once(':')?;
+    let h: u32 = h.trim().parse().ok()?;
+    let m: u32 = m.trim().parse().ok()?;
+    if h > 24 || m > 59 || (h == 24 && m > 0) {
+        return None;
+    }
+    Some(h * 60 + m)
+}
+
+fn window_contains(window: &BandwidthWindow, minute: u32) -> bool {
+    let (Some(start), Some(end)) = (parse_hhmm(&window.start), parse_hhmm(&window.end)) else {
+        return false;
+    };
+    if start < end {
+        minute >= start && minute < end
+    } else {
+        // Overnight window (e.g. 22:00-06:00) or a full day when start == end
+        minute >= start || minute < end
+    }
+}
+
+/// Normalizes a user rate string; numbers without a unit are treated as KiB/s
+/// (same convention the static `speed_limit` setting always used).
+fn parse_rate(raw: &str) -> BandwidthLimit {
+    let rate = raw.trim();
+    if rate.is_empty() || rate.starts_with('-') {
+        return BandwidthLimit::Unlimited;
+    }
+    if rate.chars().all(|c| c == '0' || c == '.') {
+        return BandwidthLimit::Paused;
+    }
+    let mut limit = rate.to_string();
+    if limit.chars().all(|c| c.is_numeric()) {
+        limit.push('K');
+    }
+    BandwidthLimit::Rate(limit)
+}
+
+/// First enabled window covering `minute` (minutes since local midnight).
+pub fn active_window(schedule: &[BandwidthWindow], minute: u32) -> Option<&BandwidthWindow> {
+    schedule
+        .iter()
+        .find(|w| w.enabled && window_contains(w, minute))
+}
+
+/// Resolves the limit for a task: per-task override, then the schedule, then the static setting.
+pub fn effective_limit_at(
+    settings: &AppSettings,
+    options: &YtDlpOptions,
+    minute: u32,
+) -> BandwidthLimit {
+    if let Some(task_limit) = &options.speed_limit {
+        // A per-task override never pauses; "0" simply means "no limit" here
+        return match parse_rate(task_limit) {
+            BandwidthLimit::Paused => BandwidthLimit::Unlimited,
+            other => other,
+        };
+    }
+    if let Some(window) = active_window(&settings.bandwidth_schedule, minute) {
+        return parse_rate(&window.rate);
+    }
+    match parse_rate(&settings.speed_limit) {
+        BandwidthLimit::Paused => BandwidthLimit::Unlimited,
+        other => other,
+    }
+}
+
+pub fn current_minute() -> u32 {
+    use chrono::Timelike;
+    let now = chrono::Local::now();
+    now.hour() * 60 + now.minute()
+}
+
+pub fn effective_limit(settings: &AppSettings, options: &YtDlpOptions) -> BandwidthLimit {
+    effective_limit_at(settings, options, current_minute())
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start: &str, end: &str, rate: &str) -> BandwidthWindow {
+        BandwidthWindow {
+            start: start.to_string(),
+            end: end.to_string(),
+            rate: rate.to_string(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn parse_hhmm_bounds() {
+        assert_eq!(parse_hhmm("00:00"), Some(0));
+        assert_eq!(parse_hhmm("09:30"), Some(570));
+        assert_eq!(parse_hhmm("24:00"), Some(1440));
+        assert_eq!(parse_hhmm("24:01"), None);
+        assert_eq!(parse_hhmm("9"), None);
+    }
+
+    #[test]
+    fn schedule_picks_window_and_wraps_midnight() {
+        let settings = AppSettings {
+            speed_limit: "500".to_string(),
+            bandwidth_schedule: vec![window("09:00", "18:00", "2M"), window("23:00", "06:00", "")],
+            ..Default::default()
+        };
+        let opts = YtDlpOptions::default();
+
+        assert_eq!(
+            effective_limit_at(&settings, &opts, 10 * 60),
+            BandwidthLimit::Rate("2M".to_string())
+        );
+        assert_eq!(
+            effective_limit_at(&settings, &opts, 2 * 60),
+            BandwidthLimit::Unlimited
+        );
+        // Outside every window: static speed_limit, bare number means KiB/s
+        assert_eq!(
+            effective_limit_at(&settings, &opts, 20 * 60),
+            BandwidthLimit::Rate("500K".to_string())
+        );
+    }
+
+    #[test]
+    fn pause_window_and_task_override() {
+        let settings = AppSettings {
+            bandwidth_schedule: vec![window("08:00", "12:00", "0")],
+            ..Default::default()
+        };
+        assert_eq!(
+            effective_limit_at(&settings, &YtDlpOptions::default(), 9 * 60),
+            BandwidthLimit::Paused
+        );
+
+        let opts = YtDlpOptions {
+            speed_limit: Some("1M".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            effective_limit_at(&settings, &opts, 9 * 60),
+            BandwidthLimit::Rate("1M".to_string())
+        );
+    }
+}
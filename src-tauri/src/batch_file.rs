@@ -0,0 +1,483 @@
+use crate::ytdlp::YtDlpOptions;
+use serde::{Deserialize, Serialize};
+
+/// Batch file layouts. `Text` also covers yt-dlp's native `--batch-file` format,
+/// which is the same thing without per-line options.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchFormat {
+    Text,
+    Csv,
+    Json,
+}
+
+/// Where an entry came from, 1-based: a line of a text/CSV file, or the
+/// index of an entry in a JSON list. Serialized as `{"line": n}` / `{"entry": n}`.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchPosition {
+    Line(usize),
+    Entry(usize),
+}
+
+/// One download from a batch file, with the per-line overrides already applied.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchEntry {
+    pub position: BatchPosition,
+    pub url: String,
+    pub options: YtDlpOptions,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchError {
+    pub position: BatchPosition,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchParseResult {
+    pub format: BatchFormat,
+    pub entries: Vec<BatchEntry>,
+    pub errors: Vec<BatchError>,
+}
+
+/// Per-entry fields that can be set in any of the formats. Unknown JSON keys
+/// are rejected like unknown text/CSV options, so a typo isn't silently dropped.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Overrides {
+    format: Option<String>,
+    #[serde(alias = "start")]
+    range_start: Option<String>,
+    #[serde(alias = "end")]
+    range_end: Option<String>,
+    #[serde(alias = "output", alias = "folder")]
+    path: Option<String>,
+    #[serde(alias = "filename", alias = "name")]
+    custom_filename: Option<String>,
+}
+
+impl Overrides {
+    fn slot(&mut self, key: &str) -> Option<&mut Option<String>> {
+        match key.to_ascii_lowercase().replace(['_', '-'], "").as_str() {
+            "format" => Some(&mut self.format),
+            "start" | "rangestart" => Some(&mut self.range_start),
+            "end" | "rangeend" => Some(&mut self.range_end),
+            "path" | "output" | "folder" => Some(&mut self.path),
+            "filename" | "name" | "customfilename" => Some(&mut self.custom_filename),
+            _ => None,
+        }
+    }
+
+    fn set(&mut self, key: &str, value: String) -> Result<(), String> {
+        let slot = self
+            .slot(key)
+            .ok_or_else(|| format!("Unknown option \"{}\"", key))?;
+        *slot = Some(value).filter(|v| !v.is_empty());
+        Ok(())
+    }
+
+    fn apply(self, base: &YtDlpOptions) -> Result<YtDlpOptions, String> {
+        let start = self
+            .range_start
+            .as_deref()
+            .map(parse_timestamp)
+            .transpose()?;
+        let end = self.range_end.as_deref().map(parse_timestamp).transpose()?;
+        if let (Some(start), Some(end)) = (start, end) {
+            if end <= start {
+                return Err("Range end must be after range start".to_string());
+            }
+        }
+
+        let mut options = base.clone();
+        options.format = self.format.or(options.format);
+        options.range_start = self.range_start.or(options.range_start);
+        options.range_end = self.range_end.or(options.range_end);
+        options.path = self.path.or(options.path);
+        options.custom_filename = self.custom_filename.or(options.custom_filename);
+        Ok(options)
+    }
+}
+
+/// Strict HH:MM:SS(.ss) / MM:SS / seconds parser; unlike the lenient download
+/// helpers this rejects garbage so it can be reported against the line.
+fn parse_timestamp(value: &str) -> Result<f64, String> {
+    let invalid = || format!("Invalid time \"{}\"", value);
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+    let mut secs = 0.0;
+    for part in parts {
+        let n: f64 = part.parse().map_err(|_| invalid())?;
+        if n < 0.0 || !n.is_finite() {
+            return Err(invalid());
+        }
+        secs = secs * 60.0 + n;
+    }
+    Ok(secs)
+}
+
+fn check_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    match url::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url.to_string()),
+        _ => Err(format!("Not an http(s) URL: \"{}\"", url)),
+    }
+}
+
+/// Picks the format from the extension, falling back to sniffing the content.
+pub fn detect_format(path: &str, content: &str) -> BatchFormat {
+    let ext = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("json") => return BatchFormat::Json,
+        Some("csv") => return BatchFormat::Csv,
+        _ => {}
+    }
+    let first = content
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !is_comment(l))
+        .unwrap_or("");
+    if first.starts_with('[') || first.starts_with('{') {
+        BatchFormat::Json
+    } else if split_csv_line(first)
+        .is_ok_and(|cols| cols.len() > 1 && cols.iter().any(|c| c.eq_ignore_ascii_case("url")))
+    {
+        BatchFormat::Csv
+    } else {
+        BatchFormat::Text
+    }
+}
+
+/// yt-dlp ignores batch lines starting with `#`, `;` or `]`.
+fn is_comment(line: &str) -> bool {
+    line.starts_with(['#', ';', ']'])
+}
+
+pub fn parse(content: &str, format: BatchFormat, base: &YtDlpOptions) -> BatchParseResult {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    let mut push = |position: BatchPosition, result: Result<BatchEntry, String>| match result {
+        Ok(entry) => entries.push(entry),
+        Err(message) => errors.push(BatchError { position, message }),
+    };
+
+    match format {
+        BatchFormat::Text => {
+            for (i, line) in content.lines().enumerate() {
+                let line = line.trim().trim_start_matches('\u{feff}');
+                if line.is_empty() || is_comment(line) {
+                    continue;
+                }
+                push(
+                    BatchPosition::Line(i + 1),
+                    parse_text_line(i + 1, line, base),
+                );
+            }
+        }
+        BatchFormat::Csv => parse_csv(content, base, &mut push),
+        BatchFormat::Json => parse_json(content, base, &mut push),
+    }
+
+    BatchParseResult {
+        format,
+        entries,
+        errors,
+    }
+}
+
+/// `URL [key=value ...] [# comment]`, values may be double-quoted to include spaces.
+fn parse_text_line(line: usize, text: &str, base: &YtDlpOptions) -> Result<BatchEntry, String> {
+    let tokens = split_words(text)?;
+    let (url, rest) = tokens.split_first().ok_or("Empty line")?;
+    let url = check_url(url)?;
+
+    let mut overrides = Overrides::default();
+    for token in rest {
+        let (key, value) = token
+            .split_once('=')
+            .ok_or_else(|| format!("Expected key=value, got \"{}\"", token))?;
+        overrides.set(key, value.to_string())?;
+    }
+    Ok(BatchEntry {
+        position: BatchPosition::Line(line),
+        url,
+        options: overrides.apply(base)?,
+    })
+}
+
+fn split_words(text: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in text.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            // A `#` starting a word begins a trailing comment; URL fragments are mid-word
+            '#' if !in_quotes && current.is_empty() => break,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quote".to_string());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current).trim().to_string()),
+            c => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quote".to_string());
+    }
+    fields.push(current.trim().to_string());
+    Ok(fields)
+}
+
+/// Header row required; `url` is the only mandatory column and unknown columns
+/// are ignored, so a history CSV export can be fed back in.
+fn parse_csv(
+    content: &str,
+    base: &YtDlpOptions,
+    push: &mut impl FnMut(BatchPosition, Result<BatchEntry, String>),
+) {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, l.trim().trim_start_matches('\u{feff}')))
+        .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'));
+
+    let Some((header_line, header)) = lines.next() else {
+        return;
+    };
+    let header = match split_csv_line(header) {
+        Ok(h) => h,
+        Err(e) => return push(BatchPosition::Line(header_line), Err(e)),
+    };
+    let Some(url_col) = header.iter().position(|h| h.eq_ignore_ascii_case("url")) else {
+        return push(
+            BatchPosition::Line(header_line),
+            Err("CSV header has no \"url\" column".to_string()),
+        );
+    };
+
+    for (line, text) in lines {
+        let result = split_csv_line(text).and_then(|fields| {
+            let url = check_url(fields.get(url_col).map_or("", |u| u.as_str()))?;
+            let mut overrides = Overrides::default();
+            for (col, value) in header.iter().zip(fields.iter()) {
+                if let Some(slot) = overrides.slot(col) {
+                    *slot = Some(value.clone()).filter(|v| !v.is_empty());
+                }
+            }
+            Ok(BatchEntry {
+                position: BatchPosition::Line(line),
+                url,
+                options: overrides.apply(base)?,
+            })
+        });
+        push(BatchPosition::Line(line), result);
+    }
+}
+
+/// A list of URL strings or objects (`{"url", "format", "rangeStart", ...}`),
+/// optionally wrapped as `{"entries": [...]}`.
+fn parse_json(
+    content: &str,
+    base: &YtDlpOptions,
+    push: &mut impl FnMut(BatchPosition, Result<BatchEntry, String>),
+) {
+    let value: serde_json::Value = match serde_json::from_str(content) {
+        Ok(v) => v,
+        Err(e) => {
+            return push(
+                BatchPosition::Line(e.line()),
+                Err(format!("Invalid JSON: {}", e)),
+            )
+        }
+    };
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(mut obj) => match obj.remove("entries") {
+            Some(serde_json::Value::Array(items)) => items,
+            _ => {
+                return push(
+                    BatchPosition::Line(1),
+                    Err("Expected a list or an \"entries\" list".to_string()),
+                )
+            }
+        },
+        _ => {
+            return push(
+                BatchPosition::Line(1),
+                Err("Expected a list of entries".to_string()),
+            )
+        }
+    };
+
+    for (i, item) in items.into_iter().enumerate() {
+        let position = BatchPosition::Entry(i + 1);
+        let result = match item {
+            serde_json::Value::String(url) => check_url(&url).map(|url| BatchEntry {
+                position,
+                url,
+                options: base.clone(),
+            }),
+            serde_json::Value::Object(mut obj) => {
+                let url = obj
+                    .remove("url")
+                    .and_then(|u| u.as_str().map(String::from))
+                    .ok_or_else(|| "Missing \"url\"".to_string());
+                url.and_then(|url| check_url(&url)).and_then(|url| {
+                    let overrides: Overrides =
+                        serde_json::from_value(serde_json::Value::Object(obj))
+                            .map_err(|e| e.to_string())?;
+                    Ok(BatchEntry {
+                        position,
+                        url,
+                        options: overrides.apply(base)?,
+                    })
+                })
+            }
+            _ => Err("Expected a URL or an object".to_string()),
+        };
+        push(position, result);
+    }
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_lines_with_overrides() {
+        let content = "# comment\n\
+            ; also a comment\n\
+            https://a.example/1\n\
+            https://a.example/2 format=audio start=1:30 end=2:00 filename=\"My Clip\"\n\
+            not-a-url\n\
+            https://a.example/3 start=2:00 end=1:00\n\
+            https://a.example/4 color=red\n";
+        let base = YtDlpOptions {
+            format: Some("best".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(detect_format("list.txt", content), BatchFormat::Text);
+
+        let result = parse(content, BatchFormat::Text, &base);
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.entries[0].options.format.as_deref(), Some("best"));
+        let clip = &result.entries[1];
+        assert_eq!(clip.position, BatchPosition::Line(4));
+        assert_eq!(clip.options.format.as_deref(), Some("audio"));
+        assert_eq!(clip.options.range_start.as_deref(), Some("1:30"));
+        assert_eq!(clip.options.custom_filename.as_deref(), Some("My Clip"));
+
+        let lines: Vec<BatchPosition> = result.errors.iter().map(|e| e.position).collect();
+        assert_eq!(
+            lines,
+            vec![
+                BatchPosition::Line(5),
+                BatchPosition::Line(6),
+                BatchPosition::Line(7)
+            ]
+        );
+    }
+
+    #[test]
+    fn text_lines_with_trailing_comments() {
+        let content = "https://a.example/1 # watch later
+            https://a.example/2#t=30 format=audio	# live set
+            https://a.example/3 filename=\"Part #1\" #note
+";
+
+        let result = parse(content, BatchFormat::Text, &YtDlpOptions::default());
+        assert!(result.errors.is_empty());
+        assert_eq!(result.entries.len(), 3);
+        assert_eq!(result.entries[1].url, "https://a.example/2#t=30");
+        assert_eq!(result.entries[1].options.format.as_deref(), Some("audio"));
+        assert_eq!(
+            result.entries[2].options.custom_filename.as_deref(),
+            Some("Part #1")
+        );
+    }
+
+    #[test]
+    fn csv_with_header() {
+        let content = "URL,Format,Output,Filename,Title\n\
+            https://a.example/1,audio,/music,\"Song, live\",ignored\n\
+            ,video,,,\n";
+        assert_eq!(detect_format("list", content), BatchFormat::Csv);
+
+        let result = parse(content, BatchFormat::Csv, &YtDlpOptions::default());
+        assert_eq!(result.entries.len(), 1);
+        let entry = &result.entries[0];
+        assert_eq!(entry.options.path.as_deref(), Some("/music"));
+        assert_eq!(entry.options.custom_filename.as_deref(), Some("Song, live"));
+        assert_eq!(result.errors[0].position, BatchPosition::Line(3));
+    }
+
+    #[test]
+    fn json_strings_and_objects() {
+        let content = r#"[
+            "https://a.example/1",
+            {"url": "https://a.example/2", "rangeStart": "10", "rangeEnd": "20", "folder": "/clips"},
+            {"format": "audio"},
+            42,
+            {"url": "https://a.example/3", "formt": "audio"}
+        ]"#;
+        assert_eq!(detect_format("batch.txt", content), BatchFormat::Json);
+
+        let result = parse(content, BatchFormat::Json, &YtDlpOptions::default());
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.entries[1].options.path.as_deref(), Some("/clips"));
+        assert_eq!(result.entries[1].options.range_end.as_deref(), Some("20"));
+        let positions: Vec<BatchPosition> = result.errors.iter().map(|e| e.position).collect();
+        assert_eq!(
+            positions,
+            vec![
+                BatchPosition::Entry(3),
+                BatchPosition::Entry(4),
+                BatchPosition::Entry(5)
+            ]
+        );
+        assert!(result.errors[2].message.contains("formt"));
+        assert_eq!(
+            serde_json::to_value(&result.errors[0]).unwrap()["position"],
+            serde_json::json!({ "entry": 3 })
+        );
+    }
+}
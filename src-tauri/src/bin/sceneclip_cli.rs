@@ -0,0 +1,6 @@
+// Headless front end: no webview, console subsystem on every platform.
+// All logic lives in `sceneclip_lib::cli` so it can reuse the queue and downloader.
+
+fn main() {
+    std::process::exit(sceneclip_lib::cli::run());
+}
@@ -1,5 +1,39 @@
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// The directories binary resolution depends on. Implemented by `AppHandle`
+/// for the GUI and by `HeadlessPaths` for the CLI, which has no Tauri runtime.
+pub trait PathContext {
+    fn app_local_data_dir(&self) -> Option<PathBuf>;
+    fn resource_dir(&self) -> Option<PathBuf>;
+}
+
+impl<R: Runtime> PathContext for AppHandle<R> {
+    fn app_local_data_dir(&self) -> Option<PathBuf> {
+        self.path().app_local_data_dir().ok()
+    }
+
+    fn resource_dir(&self) -> Option<PathBuf> {
+        self.path().resource_dir().ok()
+    }
+}
+
+/// Explicit directories for running without an `AppHandle` (see `cli.rs`).
+#[derive(Debug, Clone)]
+pub struct HeadlessPaths {
+    pub local_data_dir: Option<PathBuf>,
+    pub resource_dir: Option<PathBuf>,
+}
+
+impl PathContext for HeadlessPaths {
+    fn app_local_data_dir(&self) -> Option<PathBuf> {
+        self.local_data_dir.clone()
+    }
+
+    fn resource_dir(&self) -> Option<PathBuf> {
+        self.resource_dir.clone()
+    }
+}
 
 /// Returns the platform-appropriate executable name.
 /// On Windows, appends `.exe` if not already present. On other platforms, returns as-is.
@@ -20,8 +54,8 @@ pub fn platform_exe_name(binary_name: &str) -> String {
 
 /// Returns the writable update storage path for a binary.
 /// This is in `app_local_data_dir`, where updated binaries are placed.
-pub fn writable_path(app: &AppHandle, binary_name: &str) -> PathBuf {
-    let app_local_data = app.path().app_local_data_dir().unwrap();
+pub fn writable_path(app: &impl PathContext, binary_name: &str) -> PathBuf {
+    let app_local_data = app.app_local_data_dir().unwrap();
     if !app_local_data.exists() {
         let _ = std::fs::create_dir_all(&app_local_data);
     }
@@ -34,11 +68,11 @@ pub fn writable_path(app: &AppHandle, binary_name: &str) -> PathBuf {
 /// 3. `bin/` subdirectory next to executable
 /// 4. Sidecar scan (e.g. `binary-x86_64-pc-windows-msvc.exe`)
 /// 5. Fallback to system PATH (just the binary name)
-pub fn resolve(app: &AppHandle, binary_name: &str) -> PathBuf {
+pub fn resolve(app: &impl PathContext, binary_name: &str) -> PathBuf {
     let exe_name = platform_exe_name(binary_name);
 
     // 1. Check writable update storage (priority — contains user-updated binaries)
-    if let Ok(local_dir) = app.path().app_local_data_dir() {
+    if let Some(local_dir) = app.app_local_data_dir() {
         let local_path = local_dir.join(&exe_name);
         if local_path.exists() {
             return local_path;
@@ -78,12 +112,16 @@ pub fn resolve(app: &AppHandle, binary_name: &str) -> PathBuf {
 }
 
 /// Convenience: Resolves to a `String` (for compatibility with existing code that uses String paths).
-pub fn resolve_to_string(app: &AppHandle, binary_name: &str) -> String {
+pub fn resolve_to_string(app: &impl PathContext, binary_name: &str) -> String {
     resolve(app, binary_name).to_string_lossy().to_string()
 }
 
 /// Resolves a binary path, preferring a user-configured path if non-empty.
-pub fn resolve_configured(app: &AppHandle, binary_name: &str, configured_path: &str) -> String {
+pub fn resolve_configured(
+    app: &impl PathContext,
+    binary_name: &str,
+    configured_path: &str,
+) -> String {
     if !configured_path.is_empty() {
         return configured_path.to_string();
     }
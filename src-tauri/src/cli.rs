@@ -0,0 +1,638 @@
+//! Headless command-line front end (`sceneclip-cli`).
+//!
+//! Shares `queue.json` and `settings.json` with the GUI and runs downloads through
+//! `download_media_internal`, using `HeadlessPaths` instead of a Tauri `AppHandle`.
+
+use crate::binary_resolver::HeadlessPaths;
+use crate::commands::download::DownloadEvent;
+use crate::download_queue::{DownloadTask, QueueState, TaskStatus};
+use crate::ytdlp::{AppSettings, PreciseCut, YtDlpOptions};
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Must match `identifier` in tauri.conf.json so both front ends share data.
+const APP_IDENTIFIER: &str = "com.myunikon.sceneclip";
+const API_BASE: &str = "http://127.0.0.1:19575";
+
+const USAGE: &str = "\
+SceneClip headless CLI
+
+USAGE:
+    sceneclip-cli [--data-dir DIR] <COMMAND> [OPTIONS]
+
+COMMANDS:
+    add <URL>...      Add URLs to the queue
+    list [--json]     Print the queue
+    watch [--interval SECS]
+                      Reprint the queue every few seconds (Ctrl+C to stop)
+    run [URL...]      Download all pending tasks (plus any URLs given) in the foreground
+    remove <ID>       Remove a task (ID prefix is enough)
+
+DOWNLOAD OPTIONS (add, run):
+    --format <F>      Format/resolution, e.g. 1080p, best, audio, gif
+    --audio <FMT>     Audio-only download in FMT (mp3, m4a, flac, ...)
+    --path <DIR>      Output directory (defaults to the app's download path)
+    --start <T>       Clip start (seconds or HH:MM:SS)
+    --end <T>         Clip end (seconds or HH:MM:SS)
+    --precise <MODE>  Cut the clip locally: copy (fast) or reencode (frame-exact)
+    --name <NAME>     Custom output filename
+
+The data directory can also be set with SCENECLIP_DATA_DIR.
+While the SceneClip app is running, `add` goes through its local API and
+`run` refuses to start (pass --force to override).";
+
+struct CliContext {
+    data_dir: PathBuf,
+    paths: HeadlessPaths,
+}
+
+impl CliContext {
+    fn resolve(data_dir_override: Option<PathBuf>) -> Result<Self, String> {
+        let override_dir =
+            data_dir_override.or_else(|| std::env::var_os("SCENECLIP_DATA_DIR").map(PathBuf::from));
+
+        let data_dir = match &override_dir {
+            Some(dir) => dir.clone(),
+            None => dirs::data_dir()
+                .ok_or("Could not determine the user data directory")?
+                .join(APP_IDENTIFIER),
+        };
+        let local_data_dir = match &override_dir {
+            Some(dir) => Some(dir.clone()),
+            None => dirs::data_local_dir().map(|d| d.join(APP_IDENTIFIER)),
+        };
+        let resource_dir = std::env::current_exe()
+            .ok()
+            .and_then(|p| p.parent().map(|p| p.to_path_buf()));
+
+        Ok(Self {
+            data_dir,
+            paths: HeadlessPaths {
+                local_data_dir,
+                resource_dir,
+            },
+        })
+    }
+
+    fn open_queue(&self) -> QueueState {
+        QueueState::with_persistence_path(Some(self.data_dir.join("queue.json")))
+    }
+
+    fn state_value(&self, key: &str) -> Option<serde_json::Value> {
+        crate::store_helpers::read_state_from_file(&self.data_dir.join("settings.json"))
+            .and_then(|state| state.get(key).cloned())
+    }
+
+    fn load_settings(&self) -> AppSettings {
+        self.state_value("settings")
+            .and_then(|v| serde_json::from_value::<AppSettings>(v).ok())
+            .unwrap_or_default()
+    }
+
+    fn load_gpu_type(&self) -> String {
+        self.state_value("gpuType")
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| "auto".to_string())
+    }
+}
+
+/// Entry point used by `src/bin/sceneclip_cli.rs`. Returns the process exit code.
+pub fn run() -> i32 {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(rt) => rt,
+        Err(e) => {
+            eprintln!("[ERROR] Failed to start async runtime: {}", e);
+            return 1;
+        }
+    };
+
+    match runtime.block_on(dispatch(args)) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("[ERROR] {}", e);
+            1
+        }
+    }
+}
+
+async fn dispatch(mut args: Vec<String>) -> Result<i32, String> {
+    let data_dir = take_value(&mut args, "--data-dir")?.map(PathBuf::from);
+
+    if args.is_empty() || args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return Ok(0);
+    }
+
+    let ctx = CliContext::resolve(data_dir)?;
+    let command = args.remove(0);
+
+    match command.as_str() {
+        "add" => cmd_add(&ctx, args).await,
+        "list" | "ls" => cmd_list(&ctx, args),
+        "watch" => cmd_watch(&ctx, args).await,
+        "run" => cmd_run(&ctx, args).await,
+        "remove" | "rm" => cmd_remove(&ctx, args),
+        other => Err(format!("Unknown command '{}'. See --help.", other)),
+    }
+}
+
+// --- Argument helpers ---
+
+/// Removes `--flag VALUE` from `args` and returns the value.
+fn take_value(args: &mut Vec<String>, flag: &str) -> Result<Option<String>, String> {
+    match args.iter().position(|a| a == flag) {
+        Some(i) if i + 1 < args.len() => {
+            let value = args.remove(i + 1);
+            args.remove(i);
+            Ok(Some(value))
+        }
+        Some(_) => Err(format!("{} requires a value", flag)),
+        None => Ok(None),
+    }
+}
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    match args.iter().position(|a| a == flag) {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    }
+}
+
+fn take_download_options(args: &mut Vec<String>) -> Result<YtDlpOptions, String> {
+    let mut options = YtDlpOptions {
+        format: take_value(args, "--format")?,
+        path: take_value(args, "--path")?,
+        range_start: take_value(args, "--start")?,
+        range_end: take_value(args, "--end")?,
+        custom_filename: take_value(args, "--name")?,
+        ..Default::default()
+    };
+    if let Some(mode) = take_value(args, "--precise")? {
+        options.precise_cut = Some(match mode.as_str() {
+            "copy" => PreciseCut::Copy,
+            "reencode" => PreciseCut::Reencode,
+            _ => return Err(format!("Unknown --precise mode '{}'", mode)),
+        });
+    }
+    if let Some(audio) = take_value(args, "--audio")? {
+        options.format = Some("audio".to_string());
+        options.audio_format = Some(audio);
+    }
+    Ok(options)
+}
+
+fn take_urls(args: Vec<String>) -> Result<Vec<String>, String> {
+    let mut urls = Vec::new();
+    for arg in args {
+        if arg.starts_with("--") {
+            return Err(format!("Unknown option '{}'", arg));
+        }
+        if !(arg.starts_with("http://") || arg.starts_with("https://")) {
+            return Err(format!("Not a valid URL: {}", arg));
+        }
+        urls.push(crate::ytdlp::sanitize_url(&arg));
+    }
+    Ok(urls)
+}
+
+fn new_cli_task(url: String, options: &YtDlpOptions) -> DownloadTask {
+    let mut task = DownloadTask::new_pending(url, options.clone());
+    task.status_detail = Some("Queued via CLI".to_string());
+    if options.range_start.is_some() || options.range_end.is_some() {
+        task.range = Some(format!(
+            "{}-{}",
+            options.range_start.clone().unwrap_or_default(),
+            options.range_end.clone().unwrap_or_default()
+        ));
+    }
+    task
+}
+
+// --- GUI detection ---
+
+async fn gui_is_running() -> bool {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_millis(500))
+        .build();
+    match client {
+        Ok(c) => c
+            .get(format!("{}/health", API_BASE))
+            .send()
+            .await
+            .map_or(false, |r| r.status().is_success()),
+        Err(_) => false,
+    }
+}
+
+/// Queues through the running app so its in-memory queue isn't overwritten.
+async fn add_via_api(url: &str, options: &YtDlpOptions) -> Result<String, String> {
+    let token = tokio::task::spawn_blocking(crate::commands::keyring::get_or_create_api_token)
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/tasks", API_BASE))
+        .bearer_auth(token)
+        .json(&serde_json::json!({ "url": url, "options": options }))
+        .send()
+        .await
+        .map_err(|e| format!("API request failed: {}", e))?;
+
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(body
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("API request failed")
+            .to_string());
+    }
+    Ok(body
+        .get("id")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string())
+}
+
+// --- Commands ---
+
+async fn cmd_add(ctx: &CliContext, mut args: Vec<String>) -> Result<i32, String> {
+    let options = take_download_options(&mut args)?;
+    let urls = take_urls(args)?;
+    if urls.is_empty() {
+        return Err("add: at least one URL is required".to_string());
+    }
+
+    if gui_is_running().await {
+        let mut failed = 0;
+        for url in &urls {
+            match add_via_api(url, &options).await {
+                Ok(id) => println!("Queued {} ({}) via running app", url, short_id(&id)),
+                Err(e) => {
+                    eprintln!("[ERROR] {}: {}", url, e);
+                    failed += 1;
+                }
+            }
+        }
+        return Ok(if failed > 0 { 1 } else { 0 });
+    }
+
+    let queue = ctx.open_queue();
+    for url in urls {
+        let task = new_cli_task(url, &options);
+        println!("Queued {} ({})", task.url, short_id(&task.id));
+        queue.enqueue(task);
+    }
+    Ok(0)
+}
+
+fn cmd_list(ctx: &CliContext, mut args: Vec<String>) -> Result<i32, String> {
+    let as_json = take_flag(&mut args, "--json");
+    let tasks = ordered_tasks(&ctx.open_queue());
+
+    if as_json {
+        let json = serde_json::to_string_pretty(&tasks).map_err(|e| e.to_string())?;
+        println!("{}", json);
+    } else {
+        print_table(&tasks);
+    }
+    Ok(0)
+}
+
+async fn cmd_watch(ctx: &CliContext, mut args: Vec<String>) -> Result<i32, String> {
+    let interval = take_value(&mut args, "--interval")?
+        .map(|v| v.parse::<u64>().map_err(|_| "--interval expects seconds"))
+        .transpose()?
+        .unwrap_or(2)
+        .max(1);
+    let interactive = std::io::stdout().is_terminal();
+
+    loop {
+        // Re-open every tick so changes written by the app are picked up
+        let tasks = ordered_tasks(&ctx.open_queue());
+        if interactive {
+            print!("\x1b[2J\x1b[H");
+        }
+        print_table(&tasks);
+        let _ = std::io::stdout().flush();
+
+        tokio::select! {
+            _ = tokio::time::sleep(std::time::Duration::from_secs(interval)) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(0),
+        }
+    }
+}
+
+fn cmd_remove(ctx: &CliContext, args: Vec<String>) -> Result<i32, String> {
+    let prefix = args
+        .first()
+        .ok_or("remove: task ID is required")?
+        .to_string();
+    let queue = ctx.open_queue();
+
+    let matches: Vec<String> = ordered_tasks(&queue)
+        .into_iter()
+        .filter(|t| t.id.starts_with(&prefix))
+        .map(|t| t.id)
+        .collect();
+
+    let id = match matches.as_slice() {
+        [id] => id.clone(),
+        [] => return Err(format!("No task matches '{}'", prefix)),
+        _ => {
+            return Err(format!(
+                "'{}' matches several tasks, use a longer ID",
+                prefix
+            ))
+        }
+    };
+
+    {
+        let mut tasks = queue.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let mut order = queue.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+        tasks.remove(&id);
+        order.retain(|x| *x != id);
+    }
+    queue.save_now();
+    println!("Removed {}", short_id(&id));
+    Ok(0)
+}
+
+async fn cmd_run(ctx: &CliContext, mut args: Vec<String>) -> Result<i32, String> {
+    let force = take_flag(&mut args, "--force");
+    let options = take_download_options(&mut args)?;
+    let urls = take_urls(args)?;
+
+    if !force && gui_is_running().await {
+        return Err(
+            "SceneClip is running and owns the queue. Close it or pass --force.".to_string(),
+        );
+    }
+
+    let queue = Arc::new(ctx.open_queue());
+    for url in urls {
+        queue.enqueue(new_cli_task(url, &options));
+    }
+
+    let mut completed = 0;
+    let mut failed = 0;
+
+    while let Some(id) = next_pending(&queue) {
+        match run_task(ctx, &queue, &id).await {
+            RunOutcome::Completed => completed += 1,
+            RunOutcome::Failed => failed += 1,
+            RunOutcome::Interrupted => {
+                println!("\nInterrupted. Progress is kept in the queue.");
+                return Ok(130);
+            }
+        }
+    }
+
+    println!("Done: {} completed, {} failed.", completed, failed);
+    Ok(if failed > 0 { 1 } else { 0 })
+}
+
+// --- Foreground runner ---
+
+enum RunOutcome {
+    Completed,
+    Failed,
+    Interrupted,
+}
+
+fn next_pending(queue: &QueueState) -> Option<String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let tasks = queue.tasks.lock().unwrap_or_else(|e| e.into_inner());
+    let order = queue.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+    // Follow-up jobs need the app's ffmpeg setup, so the CLI leaves them queued
+    order
+        .iter()
+        .filter_map(|id| tasks.get(id))
+        .filter(|t| {
+            t.status == TaskStatus::Pending
+                && t.job.is_none()
+                && t.scheduled_time.map_or(true, |s| s <= now)
+        })
+        .min_by_key(|t| t.priority)
+        .map(|t| t.id.clone())
+}
+
+async fn run_task(ctx: &CliContext, queue: &Arc<QueueState>, id: &str) -> RunOutcome {
+    let Some(task) = queue
+        .tasks
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(id)
+        .cloned()
+    else {
+        return RunOutcome::Failed;
+    };
+
+    let mut settings = ctx.load_settings();
+    if !task.path.is_empty() {
+        settings.download_path = task.path.clone();
+    }
+    if settings.download_path.is_empty() {
+        if let Some(dir) = dirs::download_dir() {
+            settings.download_path = dir.to_string_lossy().to_string();
+        }
+    }
+
+    queue.update_task(id, |t| {
+        t.status = TaskStatus::Downloading;
+        t.status_detail = Some("Downloading (CLI)".to_string());
+        t.clip_files.clear();
+    });
+    queue.save_now();
+    println!("==> {}", task.url);
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let download = tokio::spawn(crate::commands::download::download_media_internal(
+        ctx.paths.clone(),
+        task.url.clone(),
+        task.id.clone(),
+        task.options.clone(),
+        settings,
+        ctx.load_gpu_type(),
+        tx,
+    ));
+
+    let interactive = std::io::stdout().is_terminal();
+    let mut last_reported = -1.0;
+    let mut outcome = RunOutcome::Failed;
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => {
+                let Some(event) = event else { break };
+                match event {
+                    DownloadEvent::Started { title, ytdlp_command, file_path, .. } => {
+                        queue.update_task(id, |t| {
+                            if let Some(ti) = title {
+                                t.title = ti;
+                            }
+                            if let Some(cmd) = ytdlp_command {
+                                t.ytdlp_command = Some(cmd);
+                            }
+                            if let Some(fp) = file_path {
+                                t.file_path = Some(fp);
+                            }
+                        });
+                    }
+                    DownloadEvent::ProcessStarted { pid, .. } => {
+                        queue.update_task(id, |t| t.pid = Some(pid));
+                    }
+                    DownloadEvent::Progress { percent, speed, eta, total_size, .. } => {
+                        queue.update_task(id, |t| {
+                            t.progress = percent;
+                            t.speed = Some(speed.clone());
+                            t.eta = Some(eta.clone());
+                            t.total_size = Some(total_size.clone());
+                        });
+                        if interactive {
+                            print!("\r    {:>5.1}%  {:>12}  ETA {:>8}  {}   ", percent, speed, eta, total_size);
+                            let _ = std::io::stdout().flush();
+                        } else if percent - last_reported >= 10.0 || percent >= 100.0 {
+                            // Keep cron logs readable: one line per 10%
+                            println!("    {:.0}%  {}  ETA {}", percent, speed, eta);
+                            last_reported = percent;
+                        }
+                    }
+                    DownloadEvent::Log { message, level, .. } => {
+                        if level == "warning" || level == "error" {
+                            if interactive {
+                                println!();
+                            }
+                            eprintln!("    [{}] {}", level.to_uppercase(), message);
+                        }
+                    }
+                    DownloadEvent::ClipProgress { index, count, file_path: Some(path), .. } => {
+                        queue.update_task(id, |t| t.clip_files.push(path.clone()));
+                        if interactive {
+                            println!();
+                        }
+                        println!("    Clip {}/{} saved to {}", index, count, path);
+                    }
+                    DownloadEvent::ClipProgress { .. } => {}
+                    DownloadEvent::Completed { file_path, .. } => {
+                        let size = std::fs::metadata(&file_path)
+                            .map(|m| format!("{:.2} MiB", m.len() as f64 / 1024.0 / 1024.0))
+                            .ok();
+                        queue.update_task(id, |t| {
+                            t.status = TaskStatus::Completed;
+                            t.progress = 100.0;
+                            t.status_detail = Some("Done".to_string());
+                            t.file_path = Some(file_path.clone());
+                            t.file_size = size.or_else(|| t.total_size.clone());
+                            t.completed_at = Some(now_millis());
+                        });
+                        if interactive {
+                            println!();
+                        }
+                        println!("    Saved to {}", file_path);
+                        let follow_ups = queue.queue_follow_ups(id, &file_path);
+                        if follow_ups > 0 {
+                            println!("    {} follow-up(s) queued for the app", follow_ups);
+                        }
+                        outcome = RunOutcome::Completed;
+                    }
+                    DownloadEvent::Error { message, .. } => {
+                        queue.update_task(id, |t| {
+                            t.status = TaskStatus::Error;
+                            t.status_detail = Some(message.clone());
+                            t.error_message = Some(message.clone());
+                            t.completed_at = Some(now_millis());
+                        });
+                        if interactive {
+                            println!();
+                        }
+                        eprintln!("    [ERROR] {}", message);
+                        outcome = RunOutcome::Failed;
+                    }
+                    DownloadEvent::Cancelled { .. } => {}
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                // Aborting drops the yt-dlp Command, which kills it (kill_on_drop)
+                download.abort();
+                queue.update_task(id, |t| {
+                    t.status = TaskStatus::Stopped;
+                    t.status_detail = Some("Interrupted (CLI)".to_string());
+                    t.pid = None;
+                });
+                queue.save_now();
+                return RunOutcome::Interrupted;
+            }
+        }
+    }
+
+    // The event stream can end without a terminal event if the attempt errored early
+    if let Ok(Err(e)) = download.await {
+        if matches!(outcome, RunOutcome::Failed) {
+            queue.update_task(id, |t| {
+                if t.status != TaskStatus::Error {
+                    t.status = TaskStatus::Error;
+                    t.error_message = Some(e.clone());
+                    t.status_detail = Some(e.clone());
+                }
+            });
+        }
+    }
+
+    queue.update_task(id, |t| t.pid = None);
+    queue.save_now();
+    outcome
+}
+
+// --- Output ---
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(8)]
+}
+
+fn ordered_tasks(queue: &QueueState) -> Vec<DownloadTask> {
+    let tasks = queue.tasks.lock().unwrap_or_else(|e| e.into_inner());
+    let order = queue.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+    order
+        .iter()
+        .filter_map(|id| tasks.get(id).cloned())
+        .collect()
+}
+
+fn print_table(tasks: &[DownloadTask]) {
+    if tasks.is_empty() {
+        println!("Queue is empty.");
+        return;
+    }
+    println!("{:<8}  {:<13}  {:>6}  TITLE", "ID", "STATUS", "PROG");
+    for t in tasks {
+        let status = serde_json::to_value(&t.status)
+            .ok()
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_default();
+        let title: String = t.title.chars().take(60).collect();
+        println!(
+            "{:<8}  {:<13}  {:>5.1}%  {}",
+            short_id(&t.id),
+            status,
+            t.progress,
+            title
+        );
+    }
+}
@@ -0,0 +1,489 @@
+use crate::subtitles::{SubtitleFormat, SubtitleTrack};
+use crate::ytdlp::{ActiveClip, PreciseCut, YtDlpOptions};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Stdio;
+
+/// The clip ranges of a multi-clip task, or `None` for an ordinary download.
+pub fn clip_count(options: &YtDlpOptions) -> Option<usize> {
+    options.clips.as_ref().map(|c| c.len()).filter(|&n| n > 0)
+}
+
+/// Options for the `index`-th (0-based) clip: its range replaces
+/// `range_start`/`range_end` and `active_clip` drives the filename.
+pub fn clip_options(base: &YtDlpOptions, index: usize) -> Option<YtDlpOptions> {
+    let clips = base.clips.as_ref()?;
+    let clip = clips.get(index)?;
+    let mut options = base.clone();
+    options.range_start = clip.start.clone().filter(|s| !s.is_empty());
+    options.range_end = clip.end.clone().filter(|s| !s.is_empty());
+    options.clips = None;
+    options.active_clip = Some(ActiveClip {
+        index: index as u32 + 1,
+        count: clips.len() as u32,
+        name: clip
+            .name
+            .clone()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| format!("Clip {}", index + 1)),
+        joined: base.concat_clips.unwrap_or(false),
+    });
+    Some(options)
+}
+
+/// Task-level progress while clip `index` (0-based) of `count` is at `clip_percent`.
+pub fn overall_percent(index: usize, count: usize, clip_percent: f64) -> f64 {
+    if count == 0 {
+        return clip_percent;
+    }
+    (index as f64 + clip_percent.clamp(0.0, 100.0) / 100.0) / count as f64 * 100.0
+}
+
+/// `<stem>.<infix>.<ext>`, keeping the extension so ffmpeg still picks the muxer.
+pub fn with_infix(name: &str, infix: &str) -> String {
+    match name.rsplit_once('.') {
+        Some((stem, ext)) => format!("{}.{}.{}", stem, infix, ext),
+        None => format!("{}.{}", name, infix),
+    }
+}
+
+/// Temporary name of a part that will be joined: `<stem>.clip01.<ext>`.
+pub fn part_file_name(final_name: &str, index: u32) -> String {
+    with_infix(final_name, &format!("clip{:02}", index))
+}
+
+/// Inverse of `part_file_name` for the first part: the path of the joined file.
+pub fn joined_path(first_part: &str) -> String {
+    match first_part.rsplit_once(".clip01") {
+        Some((stem, ext)) => format!("{}{}", stem, ext),
+        None => first_part.to_string(),
+    }
+}
+
+/// ffconcat list for the concat demuxer; single quotes need `'\''` escaping.
+pub fn concat_list(parts: &[String]) -> String {
+    let mut list = String::from("ffconcat version 1.0\n");
+    for part in parts {
+        list.push_str(&format!("file '{}'\n", part.replace('\'', "'\\''")));
+    }
+    list
+}
+
+/// Runs ffmpeg quietly, returning the last lines of its log on failure.
+async fn run_ffmpeg(ffmpeg_path: &str, args: &[String]) -> Result<(), String> {
+    #[allow(unused_mut)]
+    let mut std_command = std::process::Command::new(ffmpeg_path);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        std_command.creation_flags(CREATE_NO_WINDOW);
+    }
+    std_command
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let mut command = tokio::process::Command::from(std_command);
+    command.kill_on_drop(true);
+    let output = command
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+    Err(tail.into_iter().rev().collect::<Vec<_>>().join("\n"))
+}
+
+/// Joins `parts` into `output` without re-encoding and removes the parts.
+/// The parts come from the same source and format, so stream copy is safe.
+pub async fn concat_parts(ffmpeg_path: &str, parts: &[String], output: &str) -> Result<(), String> {
+    let list_path = format!("{}.concat.txt", output);
+    std::fs::write(&list_path, concat_list(parts)).map_err(|e| e.to_string())?;
+
+    let args: Vec<String> = ["-y", "-f", "concat", "-safe", "0", "-i", &list_path]
+        .into_iter()
+        .chain(["-c", "copy", "-map", "0", output])
+        .map(String::from)
+        .collect();
+    let result = run_ffmpeg(ffmpeg_path, &args).await;
+    let _ = std::fs::remove_file(&list_path);
+    result.map_err(|e| format!("Joining clips failed: {}", e))?;
+
+    for part in parts {
+        if Path::new(part) != Path::new(output) {
+            let _ = std::fs::remove_file(part);
+        }
+    }
+    Ok(())
+}
+
+/// Subtitle files yt-dlp wrote next to `media` (`<stem>.<lang>.<srt|vtt|ass>`)
+/// as (path, lang) pairs. Sidecars of clip parts (`<stem>.clip01.<lang>.srt`)
+/// are not matched by the joined file.
+pub fn subtitle_sidecars(media: &str) -> Vec<(String, String)> {
+    let path = Path::new(media);
+    let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+        return Vec::new();
+    };
+    let dir = path
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let prefix = format!("{}.", stem);
+    let mut found: Vec<(String, String)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            SubtitleFormat::from_path(&name)?;
+            let (lang, _) = name.strip_prefix(&prefix)?.rsplit_once('.')?;
+            if lang.is_empty() || lang.contains('.') {
+                return None;
+            }
+            Some((entry.path().to_string_lossy().to_string(), lang.to_string()))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+/// Cuts a full-length subtitle file down to the clip window, in place.
+/// Returns the number of cues left.
+pub fn clip_subtitle_file(path: &str, start: f64, end: Option<f64>) -> Result<usize, String> {
+    let mut track = SubtitleTrack::load(path)?;
+    track.clip(start, end);
+    track.save(path)?;
+    Ok(track.cues.len())
+}
+
+/// Text subtitle codec for the container of `output`, `None` for formats
+/// without subtitle streams (audio, GIF).
+pub fn subtitle_codec(output: &str) -> Option<&'static str> {
+    let ext = Path::new(output)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "mp4" | "m4v" | "mov" => Some("mov_text"),
+        "webm" => Some("webvtt"),
+        "mkv" => Some("copy"),
+        _ => None,
+    }
+}
+
+/// ffmpeg arguments muxing `subtitles` (path, lang) into a copy of `media`.
+pub fn embed_subtitle_args(
+    media: &str,
+    subtitles: &[(String, String)],
+    output: &str,
+    codec: &str,
+) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "-y".into(),
+        "-hide_banner".into(),
+        "-i".into(),
+        media.into(),
+    ];
+    for (path, _) in subtitles {
+        args.push("-i".into());
+        args.push(path.clone());
+    }
+    for map in ["0:v?".to_string(), "0:a?".to_string()]
+        .into_iter()
+        .chain((1..=subtitles.len()).map(|i| i.to_string()))
+    {
+        args.push("-map".into());
+        args.push(map);
+    }
+    args.extend(["-c", "copy", "-c:s", codec].map(String::from));
+    for (i, (_, lang)) in subtitles.iter().enumerate() {
+        args.push(format!("-metadata:s:s:{}", i));
+        args.push(format!("language={}", lang));
+    }
+    args.push(output.into());
+    args
+}
+
+/// Muxes the sidecars into `media` in place and removes them.
+pub async fn embed_subtitles(
+    ffmpeg_path: &str,
+    media: &str,
+    subtitles: &[(String, String)],
+) -> Result<(), String> {
+    let Some(codec) = subtitle_codec(media) else {
+        return Ok(());
+    };
+    let temp = with_infix(media, "subs");
+    let args = embed_subtitle_args(media, subtitles, &temp, codec);
+    if let Err(e) = run_ffmpeg(ffmpeg_path, &args).await {
+        let _ = std::fs::remove_file(&temp);
+        return Err(format!("Embedding subtitles failed: {}", e));
+    }
+    std::fs::rename(&temp, media).map_err(|e| format!("Failed to replace clip: {}", e))?;
+    for (path, _) in subtitles {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// Merges the sidecars of joined parts into sidecars of `joined`, moving each
+/// part's cues by the length of the parts before it. A part without a known
+/// length counts up to its last cue.
+pub fn join_part_subtitles(
+    parts: &[String],
+    durations: &[Option<f64>],
+    joined: &str,
+) -> Result<(), String> {
+    let mut merged: BTreeMap<String, SubtitleTrack> = BTreeMap::new();
+    let mut consumed = Vec::new();
+    let mut offset = 0.0;
+    for (i, part) in parts.iter().enumerate() {
+        let mut last_cue_end: f64 = 0.0;
+        for (path, lang) in subtitle_sidecars(part) {
+            let mut track = SubtitleTrack::load(&path)?;
+            last_cue_end = track
+                .cues
+                .iter()
+                .map(|c| c.end)
+                .fold(last_cue_end, f64::max);
+            track.shift(offset);
+            let ext = path.rsplit_once('.').map(|(_, e)| e).unwrap_or("srt");
+            let key = format!("{}.{}", lang, ext);
+            match merged.get_mut(&key) {
+                Some(existing) => existing.cues.extend(track.cues),
+                None => {
+                    merged.insert(key, track);
+                }
+            }
+            consumed.push(path);
+        }
+        offset += durations.get(i).copied().flatten().unwrap_or(last_cue_end);
+    }
+
+    let stem = Path::new(joined).with_extension("");
+    for (key, track) in merged {
+        track.save(&format!("{}.{}", stem.to_string_lossy(), key))?;
+    }
+    for path in consumed {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// Seconds added on each side of a precise cut so the downloaded segment
+/// covers the range even when the downloader snaps to keyframes.
+pub const PRECISE_CUT_PADDING_SECS: f64 = 3.0;
+
+/// The padded range to download for a precise cut, and where the requested
+/// range sits inside the downloaded file.
+#[derive(Debug, PartialEq)]
+pub struct CoveringRange {
+    pub start: f64,
+    pub end: Option<f64>,
+    pub offset: f64,
+    pub duration: Option<f64>,
+}
+
+pub fn covering_range(start: f64, end: Option<f64>) -> CoveringRange {
+    let start = start.max(0.0);
+    let end = end.filter(|&e| e > start);
+    let padded_start = (start - PRECISE_CUT_PADDING_SECS).max(0.0);
+    CoveringRange {
+        start: padded_start,
+        end: end.map(|e| e + PRECISE_CUT_PADDING_SECS),
+        offset: start - padded_start,
+        duration: end.map(|e| e - start),
+    }
+}
+
+/// Re-encode codecs matching the container, so the cut keeps its extension.
+fn reencode_args(output: &str) -> Vec<&'static str> {
+    let ext = Path::new(output)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "mp3" => vec!["-c:a", "libmp3lame", "-q:a", "2"],
+        "m4a" | "aac" => vec!["-c:a", "aac", "-b:a", "192k"],
+        "opus" | "ogg" => vec!["-c:a", "libopus", "-b:a", "160k"],
+        "flac" => vec!["-c:a", "flac"],
+        "wav" => vec!["-c:a", "pcm_s16le"],
+        "gif" => vec![],
+        "webm" => vec![
+            "-c:v",
+            "libvpx-vp9",
+            "-crf",
+            "30",
+            "-b:v",
+            "0",
+            "-c:a",
+            "libopus",
+        ],
+        _ => vec![
+            "-c:v", "libx264", "-crf", "18", "-preset", "veryfast", "-c:a", "aac", "-b:a", "192k",
+        ],
+    }
+}
+
+/// ffmpeg arguments cutting `duration` seconds (or to the end) starting at
+/// `offset` from `input` into `output`.
+pub fn trim_args(
+    input: &str,
+    output: &str,
+    offset: f64,
+    duration: Option<f64>,
+    mode: PreciseCut,
+) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "-y".into(),
+        "-hide_banner".into(),
+        "-ss".into(),
+        format!("{:.3}", offset),
+        "-i".into(),
+        input.into(),
+    ];
+    if let Some(duration) = duration {
+        args.push("-t".into());
+        args.push(format!("{:.3}", duration));
+    }
+    for map in ["0:v?", "0:a?"] {
+        args.push("-map".into());
+        args.push(map.into());
+    }
+    match mode {
+        PreciseCut::Copy => {
+            args.extend(["-c", "copy", "-avoid_negative_ts", "make_zero"].map(String::from));
+        }
+        PreciseCut::Reencode => {
+            args.extend(reencode_args(output).into_iter().map(String::from));
+        }
+    }
+    args.push(output.into());
+    args
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ytdlp::ClipRange;
+
+    fn options(concat: bool) -> YtDlpOptions {
+        YtDlpOptions {
+            clips: Some(vec![
+                ClipRange {
+                    name: Some("Intro".to_string()),
+                    start: Some("0:00".to_string()),
+                    end: Some("0:30".to_string()),
+                },
+                ClipRange {
+                    name: None,
+                    start: Some("5:00".to_string()),
+                    end: None,
+                },
+            ]),
+            concat_clips: Some(concat),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn clip_options_set_range_and_name() {
+        let base = options(false);
+        assert_eq!(clip_count(&base), Some(2));
+
+        let second = clip_options(&base, 1).unwrap();
+        assert_eq!(second.range_start.as_deref(), Some("5:00"));
+        assert_eq!(second.range_end, None);
+        assert!(second.clips.is_none());
+        let clip = second.active_clip.unwrap();
+        assert_eq!((clip.index, clip.count), (2, 2));
+        assert_eq!(clip.name, "Clip 2");
+        assert!(clip_options(&base, 2).is_none());
+
+        assert_eq!(overall_percent(1, 2, 50.0), 75.0);
+        let first = clip_options(&options(true), 0).unwrap();
+        assert!(first.active_clip.unwrap().joined);
+    }
+
+    #[test]
+    fn precise_cut_range_and_args() {
+        let range = covering_range(10.0, Some(15.5));
+        assert_eq!(range.start, 7.0);
+        assert_eq!(range.end, Some(18.5));
+        assert_eq!(range.offset, 3.0);
+        assert_eq!(range.duration, Some(5.5));
+        // Padding never goes below zero, and a bad end means "to the end"
+        let range = covering_range(1.0, Some(0.5));
+        assert_eq!(
+            (range.start, range.offset, range.duration),
+            (0.0, 1.0, None)
+        );
+
+        let args = trim_args("in.mp4", "out.mp4", 3.0, Some(5.5), PreciseCut::Copy).join(" ");
+        assert!(args.contains("-ss 3.000 -i in.mp4 -t 5.500"));
+        assert!(args.ends_with("-c copy -avoid_negative_ts make_zero out.mp4"));
+        let args = trim_args("in.mp3", "out.mp3", 0.0, None, PreciseCut::Reencode).join(" ");
+        assert!(args.contains("-c:a libmp3lame"));
+        assert!(!args.contains(" -t "));
+    }
+
+    #[test]
+    fn part_names_round_trip() {
+        let part = part_file_name("Talk.mp4", 1);
+        assert_eq!(part, "Talk.clip01.mp4");
+        assert_eq!(joined_path(&format!("/dl/{}", part)), "/dl/Talk.mp4");
+        assert_eq!(
+            concat_list(&["/dl/it's.clip01.mp4".to_string()]),
+            "ffconcat version 1.0\nfile '/dl/it'\\''s.clip01.mp4'\n"
+        );
+    }
+
+    #[test]
+    fn part_subtitles_are_found_and_joined() {
+        let dir = std::env::temp_dir().join(format!("sceneclip-subs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+        let cue = |start: &str, end: &str, text: &str| {
+            format!("1\n00:00:{} --> 00:00:{}\n{}\n", start, end, text)
+        };
+        std::fs::write(path("Talk.clip01.en.srt"), cue("01,000", "02,000", "one")).unwrap();
+        std::fs::write(path("Talk.clip02.en.srt"), cue("00,500", "01,000", "two")).unwrap();
+        std::fs::write(path("Talk.clip02.info.json"), "{}").unwrap();
+
+        let parts = [path("Talk.clip01.mp4"), path("Talk.clip02.mp4")];
+        assert_eq!(
+            subtitle_sidecars(&parts[1]),
+            vec![(path("Talk.clip02.en.srt"), "en".to_string())]
+        );
+        assert!(subtitle_sidecars(&path("Talk.mp4")).is_empty());
+
+        join_part_subtitles(&parts, &[Some(30.0), None], &path("Talk.mp4")).unwrap();
+        let joined = SubtitleTrack::load(&path("Talk.en.srt")).unwrap();
+        let times: Vec<(f64, f64)> = joined.cues.iter().map(|c| (c.start, c.end)).collect();
+        assert_eq!(times, vec![(1.0, 2.0), (30.5, 31.0)]);
+        assert!(!dir.join("Talk.clip01.en.srt").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let subs = [("Talk.en.srt".to_string(), "en".to_string())];
+        let args = embed_subtitle_args("Talk.mp4", &subs, "out.mp4", "mov_text").join(" ");
+        assert_eq!(
+            args,
+            "-y -hide_banner -i Talk.mp4 -i Talk.en.srt -map 0:v? -map 0:a? -map 1 \
+             -c copy -c:s mov_text -metadata:s:s:0 language=en out.mp4"
+        );
+        assert_eq!(subtitle_codec("clip.m4a"), None);
+    }
+}
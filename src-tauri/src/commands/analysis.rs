@@ -80,6 +80,8 @@ pub struct EstimationParams {
     pub preset: String,
     pub crf: i32,
     pub audio_bitrate: String,
+    /// Target-size compression lands at (just under) this many MB.
+    pub target_size_mb: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -384,6 +386,10 @@ pub async fn estimate_export_size(
 ) -> Result<u64, String> {
     log::info!("Estimating export size for: {:?}", params);
 
+    if let Some(mb) = params.target_size_mb.filter(|mb| *mb > 0.0) {
+        return Ok(clamp_to_u64(mb * 1_000_000.0));
+    }
+
     let (duration_secs, actual_bitrate, probe_height) = probe_file_info(&app_handle, params.file_path.as_deref()).await;
 
     let duration = duration_secs.unwrap_or(1.0).max(1.0);
@@ -470,7 +476,7 @@ pub async fn estimate_download_size(
 // ─── Internal Helpers ────────────────────────────────────────────────
 
 /// Runs ffprobe on a local file and returns (duration_secs, bitrate_bps).
-async fn probe_file_info(app_handle: &AppHandle, file_path: Option<&str>) -> (Option<f64>, Option<u64>, Option<u32>) {
+pub(crate) async fn probe_file_info(app_handle: &AppHandle, file_path: Option<&str>) -> (Option<f64>, Option<u64>, Option<u32>) {
     let path = match file_path {
         Some(p) if Path::new(p).exists() => p,
         _ => return (None, None, None),
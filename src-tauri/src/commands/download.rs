@@ -1,6 +1,8 @@
 // Tauri Channels for typed event streaming
 // Enables frontend to receive progress events from Rust backend
 
+use crate::binary_resolver::PathContext;
+use crate::clips;
 use crate::ytdlp::{self, AppSettings, YtDlpOptions};
 use regex::Regex;
 use serde::Serialize;
@@ -73,6 +75,9 @@ pub enum DownloadEvent {
         // NEW: Send generated command to frontend
         ytdlp_command: Option<String>,
         file_path: Option<String>,
+        uploader: Option<String>,
+        video_id: Option<String>,
+        extractor: Option<String>,
     },
     /// Process spawned with PID
     #[serde(rename_all = "camelCase")]
@@ -99,6 +104,16 @@ pub enum DownloadEvent {
         #[serde(default)]
         is_replace: bool,
     },
+    /// Progress of one clip of a multi-clip task; `file_path` is set once the clip is saved
+    #[serde(rename_all = "camelCase")]
+    ClipProgress {
+        id: String,
+        index: u32,
+        count: u32,
+        name: String,
+        percent: f64,
+        file_path: Option<String>,
+    },
     /// Download completed successfully
     #[serde(rename_all = "camelCase")]
     Completed { id: String, file_path: String },
@@ -113,8 +128,8 @@ pub enum DownloadEvent {
 
 // Internal function callable by Queue
 // Now wrapped by retry logic
-async fn perform_download_attempt(
-    app: AppHandle,
+async fn perform_download_attempt<C: PathContext>(
+    app: C,
     url: String,
     id: String,
     options: YtDlpOptions,
@@ -134,6 +149,9 @@ async fn perform_download_attempt(
         title: None,
         ytdlp_command: None,
         file_path: None,
+        uploader: None,
+        video_id: None,
+        extractor: None,
     });
 
     let ytdlp_path = ytdlp::resolve_ytdlp_path(&app, &settings.binary_path_yt_dlp);
@@ -252,6 +270,18 @@ async fn perform_download_attempt(
         title: display_title,
         ytdlp_command: Some(full_command_string),
         file_path: Some(full_path_str.clone()),
+        uploader: meta
+            .get("uploader")
+            .and_then(|u| u.as_str())
+            .map(|u| u.to_string()),
+        video_id: meta
+            .get("id")
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string()),
+        extractor: meta
+            .get("extractor_key")
+            .and_then(|e| e.as_str())
+            .map(|e| e.to_string()),
     });
 
     let _ = sender.send(DownloadEvent::Log {
@@ -647,8 +677,498 @@ async fn perform_download_attempt(
     }
 }
 
-pub async fn download_media_internal(
-    app: AppHandle,
+/// Downloads a single URL, retrying with fallbacks. Generic over `PathContext`
+/// so the headless CLI can run it without a Tauri `AppHandle`.
+pub async fn download_media_internal<C: PathContext + Clone>(
+    app: C,
+    url: String,
+    id: String,
+    options: YtDlpOptions,
+    settings: AppSettings,
+    gpu_type: String,
+    sender: mpsc::UnboundedSender<DownloadEvent>,
+) -> Result<(), String> {
+    if clips::clip_count(&options).is_some() {
+        return download_clips(app, url, id, options, settings, gpu_type, sender).await;
+    }
+    download_range(app, url, id, options, settings, gpu_type, sender).await
+}
+
+/// One download, precise-cut when requested for a clipped range.
+async fn download_range<C: PathContext + Clone>(
+    app: C,
+    url: String,
+    id: String,
+    options: YtDlpOptions,
+    settings: AppSettings,
+    gpu_type: String,
+    sender: mpsc::UnboundedSender<DownloadEvent>,
+) -> Result<(), String> {
+    let is_clipping = options.range_start.is_some() || options.range_end.is_some();
+    if is_clipping && options.subtitles.unwrap_or(false) {
+        return download_with_clip_subtitles(app, url, id, options, settings, gpu_type, sender)
+            .await;
+    }
+    download_cut(app, url, id, options, settings, gpu_type, sender).await
+}
+
+async fn download_cut<C: PathContext + Clone>(
+    app: C,
+    url: String,
+    id: String,
+    options: YtDlpOptions,
+    settings: AppSettings,
+    gpu_type: String,
+    sender: mpsc::UnboundedSender<DownloadEvent>,
+) -> Result<(), String> {
+    let is_clipping = options.range_start.is_some() || options.range_end.is_some();
+    match options.precise_cut {
+        Some(mode) if is_clipping => {
+            download_precise(app, url, id, options, mode, settings, gpu_type, sender).await
+        }
+        _ => download_single(app, url, id, options, settings, gpu_type, sender).await,
+    }
+}
+
+/// yt-dlp writes the whole subtitle track next to a clipped download, with
+/// the timestamps of the full video. Once the clip is done each sidecar is
+/// cut to the clip window and rebased to zero, then muxed into the clip when
+/// `embed_subtitles` is set.
+async fn download_with_clip_subtitles<C: PathContext + Clone>(
+    app: C,
+    url: String,
+    id: String,
+    options: YtDlpOptions,
+    settings: AppSettings,
+    gpu_type: String,
+    sender: mpsc::UnboundedSender<DownloadEvent>,
+) -> Result<(), String> {
+    let start = parse_time_to_seconds(options.range_start.as_deref());
+    let end = options
+        .range_end
+        .as_deref()
+        .map(|e| parse_time_to_seconds(Some(e)))
+        .filter(|&e| e > start);
+    let embed = options.embed_subtitles.unwrap_or(false);
+
+    let forward_sender = sender.clone();
+    let file_path = hold_completed(
+        |tx| {
+            download_cut(
+                app.clone(),
+                url.clone(),
+                id.clone(),
+                options,
+                settings.clone(),
+                gpu_type.clone(),
+                tx,
+            )
+        },
+        move |event| {
+            let _ = forward_sender.send(event);
+        },
+    )
+    .await?;
+
+    let log = |message: String, level: &str| {
+        let _ = sender.send(DownloadEvent::Log {
+            id: id.clone(),
+            message,
+            level: level.to_string(),
+            is_replace: false,
+        });
+    };
+
+    let mut sidecars = clips::subtitle_sidecars(&file_path);
+    // A sidecar that can't be parsed is left as downloaded rather than failing the clip
+    sidecars.retain(
+        |(path, lang)| match clips::clip_subtitle_file(path, start, end) {
+            Ok(cues) => {
+                log(
+                    format!("Subtitles ({}): {} cues in the clip window", lang, cues),
+                    "info",
+                );
+                true
+            }
+            Err(e) => {
+                log(format!("Subtitles ({}) left uncut: {}", lang, e), "warning");
+                false
+            }
+        },
+    );
+
+    if embed && !sidecars.is_empty() && clips::subtitle_codec(&file_path).is_some() {
+        let ffmpeg_path = ytdlp::resolve_ffmpeg_path(&app, &settings.binary_path_ffmpeg);
+        match clips::embed_subtitles(&ffmpeg_path, &file_path, &sidecars).await {
+            Ok(()) => log(
+                format!("Embedded {} subtitle track(s)", sidecars.len()),
+                "info",
+            ),
+            // The clip itself is fine; the captions stay as sidecar files
+            Err(e) => log(e, "warning"),
+        }
+    }
+
+    let _ = sender.send(DownloadEvent::Completed {
+        id: id.clone(),
+        file_path,
+    });
+    Ok(())
+}
+
+/// Runs `run` on its own channel, passing every event except `Completed` to
+/// `forward` and returning the completed file path instead.
+async fn hold_completed<Fut>(
+    run: impl FnOnce(mpsc::UnboundedSender<DownloadEvent>) -> Fut,
+    mut forward: impl FnMut(DownloadEvent) + Send + 'static,
+) -> Result<String, String>
+where
+    Fut: std::future::Future<Output = Result<(), String>>,
+{
+    let (tx, mut rx) = mpsc::unbounded_channel::<DownloadEvent>();
+    let forwarder = tokio::spawn(async move {
+        let mut saved = None;
+        while let Some(event) = rx.recv().await {
+            match event {
+                DownloadEvent::Completed { file_path, .. } => saved = Some(file_path),
+                other => forward(other),
+            }
+        }
+        saved
+    });
+
+    let result = run(tx).await;
+    let saved = forwarder.await.ok().flatten();
+    result?;
+    saved.ok_or_else(|| "Download finished without an output file".to_string())
+}
+
+/// `Progress` with its percentage mapped through `scale`; other events unchanged.
+fn rescale_progress(event: DownloadEvent, scale: impl Fn(f64) -> f64) -> DownloadEvent {
+    match event {
+        DownloadEvent::Progress {
+            id,
+            percent,
+            speed,
+            eta,
+            total_size,
+            status,
+            speed_raw,
+            eta_raw,
+        } => DownloadEvent::Progress {
+            id,
+            percent: scale(percent),
+            speed,
+            eta,
+            total_size,
+            status,
+            speed_raw,
+            eta_raw,
+        },
+        other => other,
+    }
+}
+
+/// Downloads each clip range in turn, then joins them when `concat_clips` is set.
+/// Progress is rescaled to the whole task; with separate files `Completed`
+/// carries the first clip and every clip is announced via `ClipProgress`.
+async fn download_clips<C: PathContext + Clone>(
+    app: C,
+    url: String,
+    id: String,
+    options: YtDlpOptions,
+    settings: AppSettings,
+    gpu_type: String,
+    sender: mpsc::UnboundedSender<DownloadEvent>,
+) -> Result<(), String> {
+    let count = clips::clip_count(&options).unwrap_or(0);
+    let mut files: Vec<String> = Vec::new();
+
+    for index in 0..count {
+        let Some(clip_options) = clips::clip_options(&options, index) else {
+            break;
+        };
+        let name = clip_options
+            .active_clip
+            .as_ref()
+            .map(|c| c.name.clone())
+            .unwrap_or_default();
+        let _ = sender.send(DownloadEvent::Log {
+            id: id.clone(),
+            message: format!("Clip {}/{}: {}", index + 1, count, name),
+            level: "info".to_string(),
+            is_replace: false,
+        });
+
+        let forward_sender = sender.clone();
+        let clip_name = name.clone();
+        let forward = move |event: DownloadEvent| {
+            if let DownloadEvent::Progress { id, percent, .. } = &event {
+                let _ = forward_sender.send(DownloadEvent::ClipProgress {
+                    id: id.clone(),
+                    index: index as u32 + 1,
+                    count: count as u32,
+                    name: clip_name.clone(),
+                    percent: *percent,
+                    file_path: None,
+                });
+            }
+            let _ = forward_sender.send(rescale_progress(event, |p| {
+                clips::overall_percent(index, count, p)
+            }));
+        };
+        let file_path = hold_completed(
+            |tx| {
+                download_range(
+                    app.clone(),
+                    url.clone(),
+                    id.clone(),
+                    clip_options,
+                    settings.clone(),
+                    gpu_type.clone(),
+                    tx,
+                )
+            },
+            forward,
+        )
+        .await
+        .map_err(|e| format!("Clip \"{}\": {}", name, e))?;
+
+        let _ = sender.send(DownloadEvent::ClipProgress {
+            id: id.clone(),
+            index: index as u32 + 1,
+            count: count as u32,
+            name,
+            percent: 100.0,
+            file_path: Some(file_path.clone()),
+        });
+        files.push(file_path);
+    }
+
+    let Some(first) = files.first().cloned() else {
+        return Err("No clips to download".to_string());
+    };
+    let final_path = if options.concat_clips.unwrap_or(false) {
+        let output = clips::joined_path(&first);
+        let joined = if files.len() == 1 {
+            std::fs::rename(&first, &output).map_err(|e| e.to_string())
+        } else {
+            let _ = sender.send(DownloadEvent::Log {
+                id: id.clone(),
+                message: format!("Joining {} clips...", files.len()),
+                level: "info".to_string(),
+                is_replace: false,
+            });
+            let ffmpeg_path = ytdlp::resolve_ffmpeg_path(&app, &settings.binary_path_ffmpeg);
+            clips::concat_parts(&ffmpeg_path, &files, &output).await
+        };
+        if let Err(e) = joined {
+            let _ = sender.send(DownloadEvent::Error {
+                id: id.clone(),
+                message: e.clone(),
+            });
+            return Err(e);
+        }
+        if options.subtitles.unwrap_or(false) {
+            let durations: Vec<Option<f64>> = options
+                .clips
+                .iter()
+                .flatten()
+                .map(|clip| {
+                    let start = parse_time_to_seconds(clip.start.as_deref());
+                    let end = parse_time_to_seconds(clip.end.as_deref());
+                    (end > start).then_some(end - start)
+                })
+                .collect();
+            if let Err(e) = clips::join_part_subtitles(&files, &durations, &output) {
+                log::warn!("[Download] Joining clip subtitles failed for {}: {}", id, e);
+            }
+        }
+        output
+    } else {
+        first
+    };
+
+    log::info!("All {} clips finished for task {}", files.len(), id);
+    let _ = sender.send(DownloadEvent::Completed {
+        id,
+        file_path: final_path,
+    });
+    Ok(())
+}
+
+/// Downloads the range padded on both sides, then cuts the exact range out
+/// locally, so the result doesn't depend on where the downloader put the cut.
+/// The download takes the first 80% of the progress bar, the cut the rest.
+async fn download_precise<C: PathContext + Clone>(
+    app: C,
+    url: String,
+    id: String,
+    options: YtDlpOptions,
+    mode: ytdlp::PreciseCut,
+    settings: AppSettings,
+    gpu_type: String,
+    sender: mpsc::UnboundedSender<DownloadEvent>,
+) -> Result<(), String> {
+    let start = parse_time_to_seconds(options.range_start.as_deref());
+    let end = options
+        .range_end
+        .as_deref()
+        .map(|e| parse_time_to_seconds(Some(e)))
+        .filter(|&e| e > 0.0);
+    let range = clips::covering_range(start, end);
+
+    let mut covering = options.clone();
+    covering.range_start = Some(format!("{:.3}", range.start));
+    covering.range_end = range.end.map(|e| format!("{:.3}", e));
+
+    let forward_sender = sender.clone();
+    let forward = move |event: DownloadEvent| {
+        let _ = forward_sender.send(rescale_progress(event, |p| p * 0.8));
+    };
+    let input = hold_completed(
+        |tx| {
+            download_single(
+                app.clone(),
+                url.clone(),
+                id.clone(),
+                covering,
+                settings.clone(),
+                gpu_type.clone(),
+                tx,
+            )
+        },
+        forward,
+    )
+    .await?;
+
+    let _ = sender.send(DownloadEvent::Log {
+        id: id.clone(),
+        message: format!(
+            "Cutting {:.3}s from {:.3}s ({})",
+            range.duration.unwrap_or(0.0),
+            range.offset,
+            if mode == ytdlp::PreciseCut::Copy {
+                "stream copy"
+            } else {
+                "re-encode"
+            }
+        ),
+        level: "info".to_string(),
+        is_replace: false,
+    });
+
+    let ffmpeg_path = ytdlp::resolve_ffmpeg_path(&app, &settings.binary_path_ffmpeg);
+    if let Err(e) = trim_locally(&ffmpeg_path, &id, &input, &range, mode, &sender).await {
+        let _ = sender.send(DownloadEvent::Error {
+            id: id.clone(),
+            message: e.clone(),
+        });
+        return Err(e);
+    }
+
+    let _ = sender.send(DownloadEvent::Completed {
+        id,
+        file_path: input,
+    });
+    Ok(())
+}
+
+/// Cuts `range` out of `input` in place (via a temporary sibling file),
+/// reporting progress from ffmpeg's `time=` output.
+async fn trim_locally(
+    ffmpeg_path: &str,
+    id: &str,
+    input: &str,
+    range: &clips::CoveringRange,
+    mode: ytdlp::PreciseCut,
+    sender: &mpsc::UnboundedSender<DownloadEvent>,
+) -> Result<(), String> {
+    let temp = clips::with_infix(input, "cut");
+    let args = clips::trim_args(input, &temp, range.offset, range.duration, mode);
+
+    #[allow(unused_mut)]
+    let mut std_command = std::process::Command::new(ffmpeg_path);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        std_command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut command = Command::from(std_command);
+    command.args(&args);
+    command.stdout(Stdio::null());
+    command.stderr(Stdio::piped());
+    command.kill_on_drop(true);
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to spawn ffmpeg: {}", e))?;
+    let _ = sender.send(DownloadEvent::ProcessStarted {
+        id: id.to_string(),
+        pid: child.id().unwrap_or(0),
+    });
+
+    let mut tail: VecDeque<String> = VecDeque::new();
+    if let Some(stderr) = child.stderr.take() {
+        use tokio::io::AsyncBufReadExt;
+        // ffmpeg ends progress lines with \r, so split on both
+        let mut reader = tokio::io::BufReader::new(stderr);
+        let mut buf = Vec::new();
+        while let Ok(n) = reader.read_until(b'\r', &mut buf).await {
+            if n == 0 {
+                break;
+            }
+            for line in String::from_utf8_lossy(&buf).split(['\r', '\n']) {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                if let Some(time) = FFMPEG_TIME_RE.captures(line).and_then(|c| c.get(1)) {
+                    let done = parse_time_to_seconds(Some(time.as_str()));
+                    let fraction = range
+                        .duration
+                        .map(|d| (done / d.max(0.001)).clamp(0.0, 1.0))
+                        .unwrap_or(0.0);
+                    let speed = FFMPEG_SPEED_RE
+                        .captures(line)
+                        .and_then(|c| c.get(1))
+                        .map(|m| format!("{}x", m.as_str()))
+                        .unwrap_or_else(|| "N/A".to_string());
+                    let _ = sender.send(DownloadEvent::Progress {
+                        id: id.to_string(),
+                        percent: 80.0 + fraction * 19.0,
+                        speed,
+                        eta: "Cutting...".to_string(),
+                        total_size: "N/A".to_string(),
+                        status: "Cutting precisely...".to_string(),
+                        speed_raw: None,
+                        eta_raw: None,
+                    });
+                } else {
+                    if tail.len() >= 10 {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line.to_string());
+                }
+            }
+            buf.clear();
+        }
+    }
+
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    if !status.success() {
+        let _ = std::fs::remove_file(&temp);
+        return Err(format!(
+            "Precise cut failed: {}",
+            tail.into_iter().collect::<Vec<_>>().join("\n")
+        ));
+    }
+    std::fs::rename(&temp, input).map_err(|e| format!("Failed to replace clip: {}", e))
+}
+
+async fn download_single<C: PathContext + Clone>(
+    app: C,
     url: String,
     id: String,
     options: YtDlpOptions,
@@ -1,10 +1,14 @@
+use crate::binary_resolver::PathContext;
+use crate::subtitles::SubtitleStyle;
 use crate::ytdlp::AppSettings;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::process::Stdio;
 use tauri::{ipc::Channel, AppHandle};
 use tokio::io::{AsyncBufReadExt, BufReader as AsyncBufReader};
 use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedSender;
 
 lazy_static::lazy_static! {
     static ref DURATION_RE: Regex = Regex::new(r"Duration:\s+(\d+:\d+:\d+\.\d+)").unwrap();
@@ -23,6 +27,80 @@ pub struct CompressionOptions {
     pub preset: String, // 'quality' | 'balanced' | 'speed' | 'archive' | 'wa'
     pub audio_bitrate: Option<String>,
     pub speed_preset: String, // 'ultrafast' | 'superfast' | 'veryfast' | 'faster' | 'fast' | 'medium' | 'slow' | 'slower' | 'veryslow'
+    /// Hard output size limit in MB (10^6 bytes). Switches video to a two-pass
+    /// bitrate encode; `crf` and `encoder` are ignored.
+    #[serde(default)]
+    pub target_size_mb: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnimatedFormat {
+    Gif,
+    Webp,
+    Apng,
+}
+
+impl AnimatedFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            AnimatedFormat::Gif => "gif",
+            AnimatedFormat::Webp => "webp",
+            AnimatedFormat::Apng => "png",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimatedExportOptions {
+    pub format: AnimatedFormat,
+    pub fps: Option<u32>,        // Defaults to 15, like gif_fps
+    pub height: Option<u32>,     // Defaults to 480; 0 keeps the source size
+    pub quality: Option<String>, // 'high' | 'fast', like gif_quality
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+    /// Times the animation plays; 0 loops forever.
+    #[serde(default)]
+    pub plays: u32,
+    /// Plays forward, then backward.
+    #[serde(default)]
+    pub boomerang: bool,
+    /// Lowers fps and scale until the file fits.
+    pub max_size_mb: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioSplitMode {
+    Silence,
+    Chapters,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaylistFormat {
+    /// A cue sheet indexing the original file
+    Cue,
+    /// An M3U8 playlist of the split parts
+    M3u,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioSplitOptions {
+    pub mode: AudioSplitMode,
+    /// Level in dB below which audio counts as silence; defaults to -35.
+    pub noise_db: Option<f64>,
+    /// Defaults to 2.
+    pub min_silence_secs: Option<f64>,
+    /// Defaults to 30; shorter parts join a neighbour.
+    pub min_part_secs: Option<f64>,
+    /// Leaves the silence out of the parts instead of cutting gaps in half.
+    #[serde(default)]
+    pub trim_silence: bool,
+    pub playlist: Option<PlaylistFormat>,
+    pub output_dir: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +113,9 @@ pub struct VideoChapter {
 #[derive(Clone, Serialize, Debug)]
 #[serde(rename_all = "camelCase", tag = "event", content = "data")]
 pub enum FFmpegEvent {
+    ProcessStarted {
+        pid: u32,
+    },
     Progress {
         percent: f64,
         speed: String,
@@ -62,6 +143,47 @@ pub async fn compress_media(
     is_image: bool,
     settings: AppSettings, // To get binary path
     on_event: Channel<FFmpegEvent>,
+) -> Result<(), String> {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let forwarder = forward_events(rx, on_event);
+    let result = compress_media_internal(
+        &_app,
+        input_path,
+        output_path,
+        options,
+        is_audio,
+        is_image,
+        &settings,
+        tx,
+    )
+    .await;
+    let _ = forwarder.await;
+    result
+}
+
+/// Relays events from the internal runners to a frontend channel.
+fn forward_events(
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<FFmpegEvent>,
+    on_event: Channel<FFmpegEvent>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            let _ = on_event.send(event);
+        }
+    })
+}
+
+/// Channel-agnostic compression used by both the command and queued follow-up jobs.
+#[allow(clippy::too_many_arguments)]
+pub async fn compress_media_internal(
+    app: &impl PathContext,
+    input_path: String,
+    output_path: String,
+    options: CompressionOptions,
+    is_audio: bool,
+    is_image: bool,
+    settings: &AppSettings,
+    on_event: UnboundedSender<FFmpegEvent>,
 ) -> Result<(), String> {
     log::info!(
         "[FFmpeg] Starting compression: {} -> {}",
@@ -70,7 +192,23 @@ pub async fn compress_media(
     );
     log::debug!("[FFmpeg] Options: {:?}", options);
 
-    let ffmpeg_path = crate::ytdlp::resolve_ffmpeg_path(&_app, &settings.binary_path_ffmpeg);
+    let ffmpeg_path = crate::ytdlp::resolve_ffmpeg_path(app, &settings.binary_path_ffmpeg);
+
+    if let Some(target_mb) = options.target_size_mb.filter(|mb| *mb > 0.0) {
+        if !is_audio && !is_image {
+            return compress_to_target_size(
+                app,
+                &ffmpeg_path,
+                input_path,
+                output_path,
+                &options,
+                target_mb,
+                settings,
+                on_event,
+            )
+            .await;
+        }
+    }
 
     let mut args = vec!["-hide_banner".to_string(), "-y".to_string()];
 
@@ -152,9 +290,342 @@ pub async fn compress_media(
 
     args.push(output_path.clone());
 
-    // --- Execute ---
+    let output = run_ffmpeg(&ffmpeg_path, &args, &on_event, (0.0, 100.0), None).await?;
+    finish(output, output_path, &on_event)
+}
+
+/// Reports the result of the last ffmpeg run as `Completed` or `Error`.
+fn finish(
+    output: FFmpegOutput,
+    output_path: String,
+    on_event: &UnboundedSender<FFmpegEvent>,
+) -> Result<(), String> {
+    if output.status.success() {
+        log::info!(
+            "[FFmpeg] Compression completed successfully: {}",
+            output_path
+        );
+        let _ = on_event.send(FFmpegEvent::Completed { output_path });
+        Ok(())
+    } else {
+        let error_message = output.log_tail;
+        log::error!("[FFmpeg] Compression failed: {}", error_message);
+        let _ = on_event.send(FFmpegEvent::Error {
+            message: error_message.clone(),
+        });
+        Err(format!(
+            "FFmpeg exited with code {:?}: {}",
+            output.status.code(),
+            error_message
+        ))
+    }
+}
+
+/// Share of the target reserved for container overhead.
+const CONTAINER_OVERHEAD: f64 = 0.03;
+/// Below this the encode is unwatchable, so the target is rejected instead.
+const MIN_VIDEO_KBPS: u32 = 64;
+const MIN_AUDIO_KBPS: u32 = 32;
+/// Second passes tried at a lower bitrate before giving up on the target.
+const MAX_TARGET_ATTEMPTS: u32 = 3;
+/// Files ffmpeg writes next to the `-passlogfile` prefix.
+const PASSLOG_SUFFIXES: [&str; 4] = [
+    "-0.log",
+    "-0.log.mbtree",
+    "-0.log.temp",
+    "-0.log.mbtree.temp",
+];
+
+/// Splits a byte budget over `duration_secs` into (video, audio) kbps.
+/// Audio gets at most a quarter of the budget so short targets stay watchable.
+fn target_bitrates(
+    target_bytes: f64,
+    duration_secs: f64,
+    audio_kbps: u32,
+) -> Result<(u32, u32), String> {
+    if duration_secs <= 0.0 {
+        return Err("Could not determine the input duration".to_string());
+    }
+    let total_kbps = target_bytes * 8.0 / 1000.0 / duration_secs * (1.0 - CONTAINER_OVERHEAD);
+    let audio = (audio_kbps as f64)
+        .min(total_kbps / 4.0)
+        .max(MIN_AUDIO_KBPS as f64) as u32;
+    let video = total_kbps - audio as f64;
+    if video < MIN_VIDEO_KBPS as f64 {
+        return Err(format!(
+            "Target of {:.1} MB is too small for {:.0}s of video",
+            target_bytes / 1_000_000.0,
+            duration_secs
+        ));
+    }
+    Ok((video as u32, audio))
+}
+
+/// Video bitrate for the next attempt after an output of `actual_bytes`
+/// overshot `target_bytes`, with a 5% safety margin.
+fn retry_video_kbps(video_kbps: u32, actual_bytes: u64, target_bytes: f64) -> u32 {
+    let scaled = video_kbps as f64 * target_bytes / actual_bytes.max(1) as f64 * 0.95;
+    (scaled as u32).min(video_kbps.saturating_sub(1))
+}
+
+/// `"128k"` → 128; falls back to the preset's default audio bitrate.
+fn audio_kbps(options: &CompressionOptions) -> u32 {
+    options
+        .audio_bitrate
+        .as_deref()
+        .and_then(|b| b.trim().trim_end_matches(['k', 'K']).parse().ok())
+        .unwrap_or(if options.preset == "wa" { 96 } else { 128 })
+}
+
+/// Arguments for one pass of a two-pass encode. VP9/Opus for WebM output,
+/// x264/AAC otherwise; pass 1 analyses only and discards its output.
+#[allow(clippy::too_many_arguments)]
+fn two_pass_args(
+    input: &str,
+    output: &str,
+    pass: u32,
+    passlog: &str,
+    video_kbps: u32,
+    audio_kbps: u32,
+    options: &CompressionOptions,
+    hardware_decoding: bool,
+) -> Vec<String> {
+    let webm = output.to_lowercase().ends_with(".webm");
+    let mut args = vec!["-hide_banner".to_string(), "-y".to_string()];
+    if hardware_decoding {
+        args.push("-hwaccel".to_string());
+        args.push("auto".to_string());
+    }
+    args.push("-i".to_string());
+    args.push(input.to_string());
+
+    if options.resolution != "original" {
+        args.push("-vf".to_string());
+        args.push(format!("scale=-2:{}", options.resolution));
+    }
+    if webm {
+        args.extend(["-c:v", "libvpx-vp9", "-row-mt", "1"].map(String::from));
+    } else {
+        args.extend(["-c:v", "libx264", "-preset"].map(String::from));
+        args.push(options.speed_preset.clone());
+    }
+    args.push("-b:v".to_string());
+    args.push(format!("{}k", video_kbps));
+    args.push("-pass".to_string());
+    args.push(pass.to_string());
+    args.push("-passlogfile".to_string());
+    args.push(passlog.to_string());
+
+    if pass == 1 {
+        args.extend(["-an", "-f", "null", "-"].map(String::from));
+    } else {
+        args.push("-c:a".to_string());
+        args.push(if webm { "libopus" } else { "aac" }.to_string());
+        args.push("-b:a".to_string());
+        args.push(format!("{}k", audio_kbps));
+        args.push(output.to_string());
+    }
+    args
+}
+
+/// Runs ffprobe with `entries` and returns its bare `value` output.
+async fn probe_value(
+    app: &impl PathContext,
+    input_path: &str,
+    entries: &[&str],
+) -> Result<String, String> {
+    let ffprobe_path = crate::ytdlp::resolve_binary_path(app, "ffprobe");
+
     #[allow(unused_mut)]
-    let mut std_command = std::process::Command::new(&ffmpeg_path);
+    let mut std_command = std::process::Command::new(&ffprobe_path);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        std_command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let output = Command::from(std_command)
+        .args(["-v", "error"])
+        .args(entries)
+        .args(["-of", "default=noprint_wrappers=1:nokey=1", input_path])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Reads the container duration in seconds via ffprobe.
+async fn probe_duration(app: &impl PathContext, input_path: &str) -> Result<f64, String> {
+    probe_value(app, input_path, &["-show_entries", "format=duration"])
+        .await?
+        .parse::<f64>()
+        .ok()
+        .filter(|d| *d > 0.0)
+        .ok_or_else(|| "Could not determine the input duration".to_string())
+}
+
+/// Height of the first video stream.
+async fn probe_video_height(app: &impl PathContext, input_path: &str) -> Option<u32> {
+    let entries = ["-select_streams", "v:0", "-show_entries", "stream=height"];
+    probe_value(app, input_path, &entries)
+        .await
+        .ok()?
+        .lines()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Two-pass bitrate encode aiming at `target_mb`. Pass 1 reports 0–50%,
+/// pass 2 50–100%; an overshooting result is re-encoded at a lower bitrate
+/// reusing the pass 1 statistics.
+#[allow(clippy::too_many_arguments)]
+async fn compress_to_target_size(
+    app: &impl PathContext,
+    ffmpeg_path: &str,
+    input_path: String,
+    output_path: String,
+    options: &CompressionOptions,
+    target_mb: f64,
+    settings: &AppSettings,
+    on_event: UnboundedSender<FFmpegEvent>,
+) -> Result<(), String> {
+    let target_bytes = target_mb * 1_000_000.0;
+    let duration = probe_duration(app, &input_path).await?;
+    let (mut video_kbps, audio_kbps) =
+        match target_bitrates(target_bytes, duration, audio_kbps(options)) {
+            Ok(rates) => rates,
+            Err(message) => {
+                let _ = on_event.send(FFmpegEvent::Error {
+                    message: message.clone(),
+                });
+                return Err(message);
+            }
+        };
+    if options.encoder != "cpu" && options.encoder != "auto" {
+        log::info!(
+            "[FFmpeg] Target size mode uses a software encoder, ignoring {}",
+            options.encoder
+        );
+    }
+
+    let passlog = format!("{}.passlog", output_path);
+    let outcome: Result<TargetOutcome, String> = async {
+        let mut actual = 0;
+        for attempt in 1..=MAX_TARGET_ATTEMPTS {
+            if attempt > 1 {
+                let next = retry_video_kbps(video_kbps, actual, target_bytes);
+                if next < MIN_VIDEO_KBPS {
+                    break;
+                }
+                let _ = on_event.send(FFmpegEvent::Log {
+                    message: format!(
+                        "Output is {:.2} MB, over the {} MB target; retrying at {}k",
+                        actual as f64 / 1_000_000.0,
+                        target_mb,
+                        next
+                    ),
+                    level: "warning".to_string(),
+                });
+                video_kbps = next;
+            }
+            log::info!(
+                "[FFmpeg] Target {} MB, attempt {}: video {}k, audio {}k",
+                target_mb,
+                attempt,
+                video_kbps,
+                audio_kbps
+            );
+
+            // Retries reuse the pass 1 statistics and only redo pass 2
+            let passes: &[u32] = if attempt == 1 { &[1, 2] } else { &[2] };
+            for &pass in passes {
+                let args = two_pass_args(
+                    &input_path,
+                    &output_path,
+                    pass,
+                    &passlog,
+                    video_kbps,
+                    audio_kbps,
+                    options,
+                    settings.hardware_decoding,
+                );
+                let span = if pass == 1 { (0.0, 50.0) } else { (50.0, 50.0) };
+                let output = run_ffmpeg(ffmpeg_path, &args, &on_event, span, None).await?;
+                if !output.status.success() {
+                    return Ok(TargetOutcome::Failed(output));
+                }
+            }
+
+            actual = std::fs::metadata(&output_path)
+                .map(|m| m.len())
+                .map_err(|e| e.to_string())?;
+            if actual as f64 <= target_bytes {
+                return Ok(TargetOutcome::Fits);
+            }
+        }
+        Ok(TargetOutcome::Oversize(actual))
+    }
+    .await;
+
+    for suffix in PASSLOG_SUFFIXES {
+        let _ = std::fs::remove_file(format!("{}{}", passlog, suffix));
+    }
+
+    match outcome? {
+        TargetOutcome::Failed(output) => finish(output, output_path, &on_event),
+        TargetOutcome::Fits => {
+            log::info!(
+                "[FFmpeg] Compression completed successfully: {}",
+                output_path
+            );
+            let _ = on_event.send(FFmpegEvent::Completed { output_path });
+            Ok(())
+        }
+        TargetOutcome::Oversize(actual) => {
+            // The file is kept; it is still the closest the encoder got
+            let message = format!(
+                "Output is {:.2} MB, still over the {} MB target",
+                actual as f64 / 1_000_000.0,
+                target_mb
+            );
+            log::error!("[FFmpeg] {}", message);
+            let _ = on_event.send(FFmpegEvent::Error {
+                message: message.clone(),
+            });
+            Err(message)
+        }
+    }
+}
+
+/// Result of an encode constrained to a maximum file size.
+enum TargetOutcome {
+    Fits,
+    Failed(FFmpegOutput),
+    Oversize(u64),
+}
+
+/// Exit status of an ffmpeg run plus the last lines it logged.
+struct FFmpegOutput {
+    status: std::process::ExitStatus,
+    log_tail: String,
+}
+
+/// Spawns ffmpeg and relays its progress, mapped into `span` (start, width)
+/// so multi-pass jobs can report one continuous percentage. `expected_secs`
+/// overrides the input duration when the output is trimmed or stretched.
+async fn run_ffmpeg(
+    ffmpeg_path: &str,
+    args: &[String],
+    on_event: &UnboundedSender<FFmpegEvent>,
+    span: (f64, f64),
+    expected_secs: Option<f64>,
+) -> Result<FFmpegOutput, String> {
+    #[allow(unused_mut)]
+    let mut std_command = std::process::Command::new(ffmpeg_path);
 
     #[cfg(windows)]
     {
@@ -166,116 +637,138 @@ pub async fn compress_media(
     }
 
     let mut command = Command::from(std_command);
-    command.args(&args);
+    command.args(args);
     command.stdout(Stdio::piped());
     command.stderr(Stdio::piped());
 
     let mut child = command
         .spawn()
         .map_err(|e| format!("Failed to spawn ffmpeg: {}", e))?;
+    if let Some(pid) = child.id() {
+        let _ = on_event.send(FFmpegEvent::ProcessStarted { pid });
+    }
 
     // --- Progress Parsing (Regex based) ---
-    // Fixed: potential memory leak by ensuring parser stops when child exits
+    // The parser runs until stderr reaches EOF, so lines still buffered when
+    // ffmpeg exits are not lost.
     let stderr = child.stderr.take().ok_or("Failed to open stderr")?;
-    let reader = AsyncBufReader::new(stderr);
+    let mut reader = AsyncBufReader::new(stderr);
     let on_event_clone = on_event.clone();
 
-    // Cancellation channel for the parser task
-    let (cancel_tx, mut cancel_rx) = tokio::sync::oneshot::channel::<()>();
-
-    let parser_handle = tokio::spawn(async move {
-        let mut total_duration_secs = 0.0;
+    let mut parser_handle = tokio::spawn(async move {
+        let mut total_duration_secs = expected_secs.unwrap_or(0.0);
         let mut last_percent = 0.0;
-        let mut lines = reader.lines();
+        let mut tail: VecDeque<String> = VecDeque::new();
+        let mut buf = Vec::new();
 
-        loop {
-            tokio::select! {
-                line_result = lines.next_line() => {
-                    match line_result {
-                        Ok(Some(l)) => {
-                             // 1. Parse Duration
-                            if total_duration_secs == 0.0 {
-                                if let Some(cap) = DURATION_RE.captures(&l) {
-                                    total_duration_secs = parse_time(&cap[1]);
-                                }
-                            }
-
-                            // 2. Parse Progress
-                            if let Some(t_cap) = TIME_RE.captures(&l) {
-                                let current_time_secs = parse_time(&t_cap[1]);
-                                let mut percent = 0.0;
-                                if total_duration_secs > 0.0 {
-                                    percent = (current_time_secs / total_duration_secs) * 100.0;
-                                }
-
-                                let speed = SPEED_RE
-                                    .captures(&l)
-                                    .map(|c| c[1].to_string())
-                                    .unwrap_or_else(|| "N/A".to_string());
-
-                                let eta = ETA_RE
-                                    .captures(&l)
-                                    .map(|c| c[1].to_string())
-                                    .unwrap_or_else(|| "N/A".to_string());
-
-                                let threshold = if total_duration_secs < 30.0 {
-                                    0.01 // 1%
-                                } else {
-                                    0.1 // 0.1%
-                                };
-
-                                if (percent - last_percent).abs() > threshold || percent >= 100.0 {
-                                    let _ = on_event_clone.send(FFmpegEvent::Progress {
-                                        percent: percent.min(100.0),
-                                        speed,
-                                        eta,
-                                    });
-                                    last_percent = percent;
-                                }
-                            } else {
-                                // Log other messages
-                                let _ = on_event_clone.send(FFmpegEvent::Log {
-                                    message: l,
-                                    level: "info".to_string(),
-                                });
-                            }
-                        }
-                        Ok(None) => break, // EOF
-                        Err(_) => break, // Error
-                    }
+        while let Ok(Some(l)) = next_stderr_line(&mut reader, &mut buf).await {
+            // 1. Parse Duration
+            if total_duration_secs == 0.0 {
+                if let Some(cap) = DURATION_RE.captures(&l) {
+                    total_duration_secs = parse_time(&cap[1]);
                 }
-                _ = &mut cancel_rx => {
-                    log::info!("FFmpeg parser cancelled");
-                    break;
+            }
+
+            // 2. Parse Progress. `[filter @ 0x…]` lines (silencedetect and
+            // the like) are log output even if they mention a time.
+            let progress = if l.starts_with('[') {
+                None
+            } else {
+                TIME_RE.captures(&l)
+            };
+            if let Some(t_cap) = progress {
+                let current_time_secs = parse_time(&t_cap[1]);
+                let mut percent = 0.0;
+                if total_duration_secs > 0.0 {
+                    percent = (current_time_secs / total_duration_secs) * 100.0;
                 }
+
+                let speed = SPEED_RE
+                    .captures(&l)
+                    .map(|c| c[1].to_string())
+                    .unwrap_or_else(|| "N/A".to_string());
+
+                let eta = ETA_RE
+                    .captures(&l)
+                    .map(|c| c[1].to_string())
+                    .unwrap_or_else(|| "N/A".to_string());
+
+                let threshold = if total_duration_secs < 30.0 {
+                    0.01 // 1%
+                } else {
+                    0.1 // 0.1%
+                };
+
+                if (percent - last_percent).abs() > threshold || percent >= 100.0 {
+                    let _ = on_event_clone.send(FFmpegEvent::Progress {
+                        percent: span.0 + percent.min(100.0) * span.1 / 100.0,
+                        speed,
+                        eta,
+                    });
+                    last_percent = percent;
+                }
+            } else {
+                // Keep the tail for error reports; stderr is consumed here
+                if tail.len() == 10 {
+                    tail.pop_front();
+                }
+                tail.push_back(l.clone());
+                // Log other messages
+                let _ = on_event_clone.send(FFmpegEvent::Log {
+                    message: l,
+                    level: "info".to_string(),
+                });
             }
         }
+        Vec::from(tail).join("\n")
     });
 
     let output = child.wait_with_output().await.map_err(|e| e.to_string())?;
 
-    // Stop the parser task
-    let _ = cancel_tx.send(());
-    let _ = parser_handle.await; // Wait for cleanup
+    // Let the parser drain what is left; only a pipe held open by a stray
+    // process keeps it from reaching EOF
+    let log_tail =
+        match tokio::time::timeout(std::time::Duration::from_secs(5), &mut parser_handle).await {
+            Ok(tail) => tail.unwrap_or_default(),
+            Err(_) => {
+                log::info!("FFmpeg parser cancelled");
+                parser_handle.abort();
+                String::new()
+            }
+        };
 
-    if output.status.success() {
-        log::info!(
-            "[FFmpeg] Compression completed successfully: {}",
-            output_path
-        );
-        let _ = on_event.send(FFmpegEvent::Completed { output_path });
-        Ok(())
-    } else {
-        let error_message = String::from_utf8_lossy(&output.stderr).to_string();
-        log::error!("[FFmpeg] Compression failed: {}", error_message);
-        let _ = on_event.send(FFmpegEvent::Error {
-            message: error_message.clone(),
-        });
-        Err(format!(
-            "FFmpeg exited with code {:?}: {}",
-            output.status.code(),
-            error_message
-        ))
+    Ok(FFmpegOutput {
+        status: output.status,
+        log_tail,
+    })
+}
+
+/// Reads the next stderr line. ffmpeg ends its status line with `\r` rather
+/// than `\n`, so both count as line ends; empty lines are skipped.
+async fn next_stderr_line<R>(reader: &mut R, buf: &mut Vec<u8>) -> std::io::Result<Option<String>>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    buf.clear();
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Ok((!buf.is_empty()).then(|| String::from_utf8_lossy(buf).into_owned()));
+        }
+        match available.iter().position(|b| *b == b'\n' || *b == b'\r') {
+            Some(end) => {
+                buf.extend_from_slice(&available[..end]);
+                reader.consume(end + 1);
+                if !buf.is_empty() {
+                    return Ok(Some(String::from_utf8_lossy(buf).into_owned()));
+                }
+            }
+            None => {
+                let len = available.len();
+                buf.extend_from_slice(available);
+                reader.consume(len);
+            }
+        }
     }
 }
 
@@ -311,10 +804,60 @@ pub async fn split_media_chapters(
     settings: AppSettings,
     on_event: Channel<FFmpegEvent>,
 ) -> Result<(), String> {
-    let ffmpeg_path = crate::ytdlp::resolve_ffmpeg_path(&_app, &settings.binary_path_ffmpeg);
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let forwarder = forward_events(rx, on_event);
+    let result =
+        split_media_chapters_internal(&_app, input_path, chapters, None, &settings, tx).await;
+    let _ = forwarder.await;
+    result
+}
+
+/// Splits `input_path` into one file per chapter, written next to the input
+/// or into `output_dir` when given.
+pub async fn split_media_chapters_internal(
+    app: &impl PathContext,
+    input_path: String,
+    chapters: Vec<VideoChapter>,
+    output_dir: Option<&str>,
+    settings: &AppSettings,
+    on_event: UnboundedSender<FFmpegEvent>,
+) -> Result<(), String> {
+    let ffmpeg_path = crate::ytdlp::resolve_ffmpeg_path(app, &settings.binary_path_ffmpeg);
+    let split = split_parts(
+        &ffmpeg_path,
+        &input_path,
+        &chapters,
+        output_dir,
+        (0.0, 100.0),
+        &on_event,
+    )
+    .await;
+    if let Err(e) = split {
+        let _ = on_event.send(FFmpegEvent::Error { message: e.clone() });
+        return Err(e);
+    }
+
+    let _ = on_event.send(FFmpegEvent::Completed {
+        output_path: "All chapters processed".to_string(),
+    });
+    Ok(())
+}
 
-    let path_obj = std::path::Path::new(&input_path);
-    let parent = path_obj.parent().unwrap_or(std::path::Path::new(""));
+/// Stream-copies each chapter to `<stem> - NN - <title>.<ext>` and returns
+/// the written paths. Progress is mapped into `span` like `run_ffmpeg`.
+async fn split_parts(
+    ffmpeg_path: &str,
+    input_path: &str,
+    chapters: &[VideoChapter],
+    output_dir: Option<&str>,
+    span: (f64, f64),
+    on_event: &UnboundedSender<FFmpegEvent>,
+) -> Result<Vec<String>, String> {
+    let path_obj = std::path::Path::new(input_path);
+    let parent = output_dir
+        .map(std::path::Path::new)
+        .or_else(|| path_obj.parent())
+        .unwrap_or(std::path::Path::new(""));
     let stem = path_obj
         .file_stem()
         .and_then(|s| s.to_str())
@@ -334,6 +877,7 @@ pub async fn split_media_chapters(
 
     let mut accumulated_time = 0.0;
     let mut last_percent = 0.0;
+    let mut written = Vec::new();
 
     for (index, chapter) in chapters.iter().enumerate() {
         let safe_title = chapter
@@ -346,7 +890,7 @@ pub async fn split_media_chapters(
             "-hide_banner".to_string(),
             "-y".to_string(),
             "-i".to_string(),
-            input_path.clone(),
+            input_path.to_string(),
         ];
         args.push("-ss".to_string());
         args.push(chapter.start_time.to_string());
@@ -359,7 +903,7 @@ pub async fn split_media_chapters(
         args.push(output_path.to_string_lossy().to_string());
 
         #[allow(unused_mut)]
-        let mut std_command = std::process::Command::new(&ffmpeg_path);
+        let mut std_command = std::process::Command::new(ffmpeg_path);
 
         #[cfg(target_os = "windows")]
         {
@@ -376,6 +920,9 @@ pub async fn split_media_chapters(
         let mut child = command
             .spawn()
             .map_err(|e| format!("Failed to spawn ffmpeg for chapter {}: {}", index + 1, e))?;
+        if let Some(pid) = child.id() {
+            let _ = on_event.send(FFmpegEvent::ProcessStarted { pid });
+        }
 
         let stderr = child.stderr.take().ok_or("Failed to open stderr")?;
         let reader = AsyncBufReader::new(stderr);
@@ -391,7 +938,7 @@ pub async fn split_media_chapters(
                 // Throttle updates
                 if (percent - last_percent).abs() > 0.5 || percent >= 100.0 {
                     let _ = on_event.send(FFmpegEvent::Progress {
-                        percent: percent.min(100.0),
+                        percent: span.0 + percent.min(100.0) * span.1 / 100.0,
                         speed: "N/A".to_string(), // Copy usually doesn't report speed reliably or is too fast
                         eta: "N/A".to_string(),
                     });
@@ -403,24 +950,797 @@ pub async fn split_media_chapters(
         let output = child.wait_with_output().await.map_err(|e| e.to_string())?;
 
         if !output.status.success() {
-            let err_msg = format!(
+            return Err(format!(
                 "Failed to split chapter {}: Exit code {:?}",
                 index + 1,
                 output.status.code()
-            );
-            let _ = on_event.send(FFmpegEvent::Error {
-                message: err_msg.clone(),
-            });
-            return Err(err_msg);
+            ));
         }
 
         accumulated_time += chapter.end_time - chapter.start_time;
+        written.push(output_path.to_string_lossy().to_string());
     }
+    Ok(written)
+}
 
-    let _ = on_event.send(FFmpegEvent::Completed {
-        output_path: "All chapters processed".to_string(),
+#[tauri::command]
+pub async fn split_audio(
+    _app: AppHandle,
+    input_path: String,
+    options: AudioSplitOptions,
+    settings: AppSettings,
+    on_event: Channel<FFmpegEvent>,
+) -> Result<(), String> {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let forwarder = forward_events(rx, on_event);
+    let result = split_audio_internal(&_app, input_path, options, &settings, tx).await;
+    let _ = forwarder.await;
+    result
+}
+
+fn value_after(line: &str, key: &str) -> Option<f64> {
+    line.split_once(key)?
+        .1
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// `silence_start`/`silence_end` pairs from silencedetect's log. A silence
+/// still open when the file ends has no end.
+fn parse_silences(lines: &[String]) -> Vec<(f64, Option<f64>)> {
+    let mut silences: Vec<(f64, Option<f64>)> = Vec::new();
+    for line in lines {
+        if let Some(start) = value_after(line, "silence_start:") {
+            silences.push((start.max(0.0), None));
+        } else if let Some(end) = value_after(line, "silence_end:") {
+            if let Some(last) = silences.last_mut().filter(|s| s.1.is_none()) {
+                last.1 = Some(end);
+            }
+        }
+    }
+    silences
+}
+
+/// The sounding parts between `silences`. Gaps are cut in the middle, or
+/// left out with `trim`. A part shorter than `min_part` runs on into the
+/// next one, so short pauses don't produce fragments.
+fn silence_parts(
+    silences: &[(f64, Option<f64>)],
+    duration: f64,
+    trim: bool,
+    min_part: f64,
+) -> Vec<(f64, f64)> {
+    // Silences this close to an edge lead in or out rather than separate parts
+    const EDGE_SECS: f64 = 0.05;
+    let mut start = 0.0;
+    let mut end = duration;
+    let mut gaps = Vec::new();
+    for &(s, e) in silences {
+        let e = e.unwrap_or(duration).min(duration);
+        if s <= EDGE_SECS {
+            if trim {
+                start = e;
+            }
+        } else if e >= duration - EDGE_SECS {
+            if trim {
+                end = s;
+            }
+        } else {
+            gaps.push((s, e));
+        }
+    }
+
+    let mut parts: Vec<(f64, f64)> = Vec::new();
+    let mut part_start = start;
+    for (s, e) in gaps {
+        let (part_end, next_start) = if trim {
+            (s, e)
+        } else {
+            ((s + e) / 2.0, (s + e) / 2.0)
+        };
+        if part_end - part_start >= min_part {
+            parts.push((part_start, part_end));
+            part_start = next_start;
+        }
+    }
+    if end > part_start {
+        match parts.last_mut() {
+            Some(last) if end - part_start < min_part => last.1 = end,
+            _ => parts.push((part_start, end)),
+        }
+    }
+    parts
+}
+
+/// Runs silencedetect over the first audio stream and returns its findings.
+/// `-nostats` keeps the status line out of the log the markers are read
+/// from, so this pass reports no progress of its own.
+async fn detect_silence(
+    ffmpeg_path: &str,
+    input_path: &str,
+    options: &AudioSplitOptions,
+    on_event: &UnboundedSender<FFmpegEvent>,
+) -> Result<Vec<String>, String> {
+    let filter = format!(
+        "silencedetect=noise={}dB:d={}",
+        options.noise_db.unwrap_or(-35.0),
+        options.min_silence_secs.unwrap_or(2.0).max(0.1)
+    );
+    let args: Vec<String> = [
+        "-hide_banner",
+        "-nostats",
+        "-i",
+        input_path,
+        "-map",
+        "0:a:0",
+        "-af",
+        &filter,
+        "-f",
+        "null",
+        "-",
+    ]
+    .map(String::from)
+    .to_vec();
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let relay = on_event.clone();
+    let collector = tokio::spawn(async move {
+        let mut found = Vec::new();
+        while let Some(event) = rx.recv().await {
+            match event {
+                FFmpegEvent::Log { message, .. } if message.contains("silence_") => {
+                    found.push(message)
+                }
+                other => {
+                    let _ = relay.send(other);
+                }
+            }
+        }
+        found
     });
-    Ok(())
+    let output = run_ffmpeg(ffmpeg_path, &args, &tx, (0.0, 40.0), None).await;
+    drop(tx);
+    let found = collector.await.unwrap_or_default();
+
+    let output = output?;
+    if !output.status.success() {
+        return Err(format!("Silence detection failed: {}", output.log_tail));
+    }
+    Ok(found)
+}
+
+/// `mm:ss:ff` with 75 frames per second, as cue sheets count time.
+fn cue_time(secs: f64) -> String {
+    let frames = (secs.max(0.0) * 75.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}",
+        frames / 75 / 60,
+        frames / 75 % 60,
+        frames % 75
+    )
+}
+
+/// A cue sheet placing each part as a track of the original file.
+fn cue_sheet(input_path: &str, parts: &[VideoChapter]) -> String {
+    let path = std::path::Path::new(input_path);
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let stem = path.file_stem().and_then(|n| n.to_str()).unwrap_or("");
+    let file_type = match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+        .as_str()
+    {
+        "mp3" => "MP3",
+        "aif" | "aiff" => "AIFF",
+        _ => "WAVE",
+    };
+    // Cue strings can't contain double quotes
+    let quoted = |s: &str| format!("\"{}\"", s.replace('"', "'"));
+
+    let mut sheet = format!(
+        "TITLE {}\nFILE {} {}\n",
+        quoted(stem),
+        quoted(name),
+        file_type
+    );
+    for (i, part) in parts.iter().enumerate() {
+        sheet.push_str(&format!(
+            "  TRACK {:02} AUDIO\n    TITLE {}\n    INDEX 01 {}\n",
+            i + 1,
+            quoted(&part.title),
+            cue_time(part.start_time)
+        ));
+    }
+    sheet
+}
+
+/// An extended M3U of the parts, by file name since it sits next to them.
+fn m3u_playlist(parts: &[VideoChapter], files: &[String]) -> String {
+    let mut playlist = String::from("#EXTM3U\n");
+    for (part, file) in parts.iter().zip(files) {
+        let name = std::path::Path::new(file)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(file);
+        playlist.push_str(&format!(
+            "#EXTINF:{},{}\n{}\n",
+            (part.end_time - part.start_time).round() as u64,
+            part.title,
+            name
+        ));
+    }
+    playlist
+}
+
+/// Splits an audio file at silences or at its chapters, optionally writing
+/// a cue sheet (next to the input) or an M3U8 (next to the parts).
+pub async fn split_audio_internal(
+    app: &impl PathContext,
+    input_path: String,
+    options: AudioSplitOptions,
+    settings: &AppSettings,
+    on_event: UnboundedSender<FFmpegEvent>,
+) -> Result<(), String> {
+    log::info!("[FFmpeg] Splitting {} by {:?}", input_path, options.mode);
+    let ffmpeg_path = crate::ytdlp::resolve_ffmpeg_path(app, &settings.binary_path_ffmpeg);
+
+    let outcome: Result<String, String> = async {
+        let (parts, span) = match options.mode {
+            AudioSplitMode::Chapters => {
+                let chapters: Vec<VideoChapter> = probe_chapters(app, &input_path)
+                    .await?
+                    .into_iter()
+                    .filter(|c| c.end_time > c.start_time)
+                    .collect();
+                if chapters.is_empty() {
+                    return Err("The file has no chapters".to_string());
+                }
+                (chapters, (0.0, 100.0))
+            }
+            AudioSplitMode::Silence => {
+                let duration = probe_duration(app, &input_path).await?;
+                let found = detect_silence(&ffmpeg_path, &input_path, &options, &on_event).await?;
+                let min_part = options.min_part_secs.unwrap_or(30.0).max(0.0);
+                let parts: Vec<VideoChapter> = silence_parts(
+                    &parse_silences(&found),
+                    duration,
+                    options.trim_silence,
+                    min_part,
+                )
+                .into_iter()
+                .enumerate()
+                .map(|(i, (start, end))| VideoChapter {
+                    title: format!("Part {}", i + 1),
+                    start_time: start,
+                    end_time: end,
+                })
+                .collect();
+                if parts.is_empty() {
+                    return Err("No audio found between the silences".to_string());
+                }
+                let _ = on_event.send(FFmpegEvent::Log {
+                    message: format!("Found {} parts", parts.len()),
+                    level: "info".to_string(),
+                });
+                (parts, (40.0, 60.0))
+            }
+        };
+
+        let files = split_parts(
+            &ffmpeg_path,
+            &input_path,
+            &parts,
+            options.output_dir.as_deref(),
+            span,
+            &on_event,
+        )
+        .await?;
+
+        let (playlist_path, content) = match options.playlist {
+            None => return Ok(files[0].clone()),
+            Some(PlaylistFormat::Cue) => (
+                crate::follow_up::sibling_path(&input_path, "", Some("cue")),
+                cue_sheet(&input_path, &parts),
+            ),
+            Some(PlaylistFormat::M3u) => (
+                std::path::Path::new(&files[0])
+                    .with_file_name(format!(
+                        "{}.m3u8",
+                        std::path::Path::new(&input_path)
+                            .file_stem()
+                            .and_then(|s| s.to_str())
+                            .unwrap_or("playlist")
+                    ))
+                    .to_string_lossy()
+                    .to_string(),
+                m3u_playlist(&parts, &files),
+            ),
+        };
+        std::fs::write(&playlist_path, content)
+            .map_err(|e| format!("Failed to write {}: {}", playlist_path, e))?;
+        Ok(playlist_path)
+    }
+    .await;
+
+    match outcome {
+        Ok(output_path) => {
+            log::info!("[FFmpeg] Audio split completed: {}", output_path);
+            let _ = on_event.send(FFmpegEvent::Completed { output_path });
+            Ok(())
+        }
+        Err(e) => {
+            log::error!("[FFmpeg] Audio split failed: {}", e);
+            let _ = on_event.send(FFmpegEvent::Error { message: e.clone() });
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn export_animated(
+    _app: AppHandle,
+    input_path: String,
+    output_path: String,
+    options: AnimatedExportOptions,
+    settings: AppSettings,
+    on_event: Channel<FFmpegEvent>,
+) -> Result<(), String> {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let forwarder = forward_events(rx, on_event);
+    let result =
+        export_animated_internal(&_app, input_path, output_path, options, &settings, tx).await;
+    let _ = forwarder.await;
+    result
+}
+
+const MIN_ANIMATED_FPS: u32 = 8;
+const MIN_ANIMATED_HEIGHT: u32 = 120;
+const MAX_ANIMATED_ATTEMPTS: u32 = 5;
+
+/// `fps`/`scale` (and the boomerang reversal) shared by both GIF passes.
+fn animated_chain(fps: u32, height: u32, boomerang: bool) -> String {
+    let mut chain = format!("fps={}", fps);
+    if height > 0 {
+        chain.push_str(&format!(",scale=-2:'min({},ih)':flags=lanczos", height));
+    }
+    if boomerang {
+        chain.push_str(",split[fw][bw];[bw]reverse[rv];[fw][rv]concat=n=2:v=1:a=0");
+    }
+    chain
+}
+
+/// `-ss`/`-t` input options for the requested range.
+fn animated_trim_args(options: &AnimatedExportOptions) -> Vec<String> {
+    let start = options.start.unwrap_or(0.0).max(0.0);
+    let mut args = Vec::new();
+    if start > 0.0 {
+        args.push("-ss".to_string());
+        args.push(format!("{:.3}", start));
+    }
+    if let Some(end) = options.end.filter(|e| *e > start) {
+        args.push("-t".to_string());
+        args.push(format!("{:.3}", end - start));
+    }
+    args
+}
+
+/// The GIF muxer counts repeats (-1 = play once); WebP and APNG count plays.
+fn loop_args(format: AnimatedFormat, plays: u32) -> Vec<String> {
+    let (flag, value) = match format {
+        AnimatedFormat::Gif => (
+            "-loop",
+            match plays {
+                0 => 0,
+                1 => -1,
+                n => n as i64 - 1,
+            },
+        ),
+        AnimatedFormat::Webp => ("-loop", plays as i64),
+        AnimatedFormat::Apng => ("-plays", plays as i64),
+    };
+    vec![flag.to_string(), value.to_string()]
+}
+
+/// First GIF pass: a palette tuned to the frames that will be encoded.
+fn palette_args(
+    input: &str,
+    palette: &str,
+    options: &AnimatedExportOptions,
+    fps: u32,
+    height: u32,
+) -> Vec<String> {
+    let mut args = vec!["-hide_banner".to_string(), "-y".to_string()];
+    args.extend(animated_trim_args(options));
+    args.push("-i".to_string());
+    args.push(input.to_string());
+    args.push("-filter_complex".to_string());
+    args.push(format!(
+        "[0:v]{},palettegen=stats_mode=diff[p]",
+        animated_chain(fps, height, options.boomerang)
+    ));
+    args.extend(["-map", "[p]", "-frames:v", "1"].map(String::from));
+    args.push(palette.to_string());
+    args
+}
+
+/// Final encode; `palette` is the first-pass output for high quality GIFs.
+fn animated_encode_args(
+    input: &str,
+    output: &str,
+    palette: Option<&str>,
+    options: &AnimatedExportOptions,
+    fps: u32,
+    height: u32,
+) -> Vec<String> {
+    let chain = animated_chain(fps, height, options.boomerang);
+    let fast = options.quality.as_deref() == Some("fast");
+
+    let mut args = vec!["-hide_banner".to_string(), "-y".to_string()];
+    args.extend(animated_trim_args(options));
+    args.push("-i".to_string());
+    args.push(input.to_string());
+    if let Some(palette) = palette {
+        args.push("-i".to_string());
+        args.push(palette.to_string());
+    }
+    args.push("-filter_complex".to_string());
+    args.push(match palette {
+        Some(_) => format!(
+            "[0:v]{}[x];[x][1:v]paletteuse=dither=bayer:bayer_scale=5:diff_mode=rectangle[out]",
+            chain
+        ),
+        None => format!("[0:v]{}[out]", chain),
+    });
+    args.extend(["-map", "[out]", "-an"].map(String::from));
+
+    match options.format {
+        AnimatedFormat::Gif => args.extend(["-f", "gif"].map(String::from)),
+        AnimatedFormat::Webp => {
+            args.extend(["-c:v", "libwebp", "-lossless", "0", "-q:v"].map(String::from));
+            args.push(if fast { "60" } else { "80" }.to_string());
+            args.push("-compression_level".to_string());
+            args.push(if fast { "3" } else { "6" }.to_string());
+            args.extend(["-f", "webp"].map(String::from));
+        }
+        AnimatedFormat::Apng => args.extend(["-c:v", "apng", "-f", "apng"].map(String::from)),
+    }
+    args.extend(loop_args(options.format, options.plays));
+    args.push(output.to_string());
+    args
+}
+
+/// Smaller (fps, height) after an output of `actual` bytes overshot `target`.
+/// Size grows roughly with fps × height², so the frame rate takes half of the
+/// reduction and the scale the rest, each within its minimum.
+fn shrink_animated(fps: u32, height: u32, actual: u64, target: f64) -> Option<(u32, u32)> {
+    let factor = (target / actual.max(1) as f64 * 0.9).min(1.0);
+    let new_fps =
+        ((fps as f64 * factor.sqrt()).round() as u32).clamp(MIN_ANIMATED_FPS.min(fps), fps);
+    let remaining = factor * fps as f64 / new_fps.max(1) as f64;
+    let new_height = if remaining < 1.0 {
+        ((height as f64 * remaining.sqrt()) as u32 / 2 * 2)
+            .clamp(MIN_ANIMATED_HEIGHT.min(height), height)
+    } else {
+        height
+    };
+    if (new_fps, new_height) == (fps, height) {
+        None
+    } else {
+        Some((new_fps, new_height))
+    }
+}
+
+/// Channel-agnostic animated export used by the command and queued jobs.
+pub async fn export_animated_internal(
+    app: &impl PathContext,
+    input_path: String,
+    output_path: String,
+    options: AnimatedExportOptions,
+    settings: &AppSettings,
+    on_event: UnboundedSender<FFmpegEvent>,
+) -> Result<(), String> {
+    log::info!(
+        "[FFmpeg] Animated {:?} export: {} -> {}",
+        options.format,
+        input_path,
+        output_path
+    );
+    let ffmpeg_path = crate::ytdlp::resolve_ffmpeg_path(app, &settings.binary_path_ffmpeg);
+
+    let mut fps = options.fps.unwrap_or(15).max(1);
+    let mut height = options.height.unwrap_or(480);
+    let start = options.start.unwrap_or(0.0).max(0.0);
+    let clip_secs = match options.end.filter(|e| *e > start) {
+        Some(end) => Some(end - start),
+        None => probe_duration(app, &input_path)
+            .await
+            .ok()
+            .map(|d| d - start),
+    };
+    // Boomerang plays the range twice
+    let passes = if options.boomerang { 2.0 } else { 1.0 };
+    let expected_secs = clip_secs.filter(|d| *d > 0.0).map(|d| d * passes);
+
+    let use_palette =
+        options.format == AnimatedFormat::Gif && options.quality.as_deref() != Some("fast");
+    let palette = format!("{}.palette.png", output_path);
+    let max_bytes = options
+        .max_size_mb
+        .filter(|mb| *mb > 0.0)
+        .map(|mb| mb * 1_000_000.0);
+
+    let outcome: Result<TargetOutcome, String> = async {
+        let mut actual = 0;
+        for attempt in 1..=MAX_ANIMATED_ATTEMPTS {
+            if attempt > 1 {
+                let target = max_bytes.unwrap_or_default();
+                if height == 0 {
+                    height = probe_video_height(app, &input_path).await.unwrap_or(480);
+                }
+                let Some((next_fps, next_height)) = shrink_animated(fps, height, actual, target)
+                else {
+                    break;
+                };
+                let _ = on_event.send(FFmpegEvent::Log {
+                    message: format!(
+                        "Output is {:.2} MB, over the {} MB limit; retrying at {} fps, {}p",
+                        actual as f64 / 1_000_000.0,
+                        target / 1_000_000.0,
+                        next_fps,
+                        next_height
+                    ),
+                    level: "warning".to_string(),
+                });
+                fps = next_fps;
+                height = next_height;
+            }
+
+            let span = if use_palette {
+                let args = palette_args(&input_path, &palette, &options, fps, height);
+                let output =
+                    run_ffmpeg(&ffmpeg_path, &args, &on_event, (0.0, 20.0), expected_secs).await?;
+                if !output.status.success() {
+                    return Ok(TargetOutcome::Failed(output));
+                }
+                (20.0, 80.0)
+            } else {
+                (0.0, 100.0)
+            };
+
+            let args = animated_encode_args(
+                &input_path,
+                &output_path,
+                use_palette.then_some(palette.as_str()),
+                &options,
+                fps,
+                height,
+            );
+            let output = run_ffmpeg(&ffmpeg_path, &args, &on_event, span, expected_secs).await?;
+            if !output.status.success() {
+                return Ok(TargetOutcome::Failed(output));
+            }
+
+            actual = std::fs::metadata(&output_path)
+                .map(|m| m.len())
+                .map_err(|e| e.to_string())?;
+            match max_bytes {
+                Some(max) if actual as f64 > max => continue,
+                _ => return Ok(TargetOutcome::Fits),
+            }
+        }
+        Ok(TargetOutcome::Oversize(actual))
+    }
+    .await;
+    let _ = std::fs::remove_file(&palette);
+
+    let message = match outcome? {
+        TargetOutcome::Fits => {
+            log::info!("[FFmpeg] Animated export completed: {}", output_path);
+            let _ = on_event.send(FFmpegEvent::Completed { output_path });
+            return Ok(());
+        }
+        TargetOutcome::Failed(output) => format!(
+            "FFmpeg exited with code {:?}: {}",
+            output.status.code(),
+            output.log_tail
+        ),
+        // The file is kept; it is still the smallest the settings allowed
+        TargetOutcome::Oversize(actual) => format!(
+            "Output is {:.2} MB, still over the {} MB limit at {} fps, {}p",
+            actual as f64 / 1_000_000.0,
+            options.max_size_mb.unwrap_or_default(),
+            fps,
+            height
+        ),
+    };
+    log::error!("[FFmpeg] Animated export failed: {}", message);
+    let _ = on_event.send(FFmpegEvent::Error {
+        message: message.clone(),
+    });
+    Err(message)
+}
+
+#[tauri::command]
+pub async fn burn_subtitles(
+    _app: AppHandle,
+    input_path: String,
+    subtitle_path: String,
+    output_path: String,
+    style: Option<SubtitleStyle>,
+    settings: AppSettings,
+    on_event: Channel<FFmpegEvent>,
+) -> Result<(), String> {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let forwarder = forward_events(rx, on_event);
+    let result = burn_subtitles_internal(
+        &_app,
+        input_path,
+        subtitle_path,
+        output_path,
+        style,
+        &settings,
+        tx,
+    )
+    .await;
+    let _ = forwarder.await;
+    result
+}
+
+/// Re-encodes the video with the captions drawn in; audio is copied and
+/// existing subtitle streams are dropped since they are now in the picture.
+/// Audio for a re-encoded file: copied when the target container accepts
+/// the source codec, otherwise Opus for WebM and AAC for MP4/MOV.
+fn audio_args(output_path: &str, source_codec: Option<&str>) -> &'static [&'static str] {
+    let lower = output_path.to_lowercase();
+    let (accepted, encode): (&[&str], &'static [&'static str]) = if lower.ends_with(".webm") {
+        (&["opus", "vorbis"], &["-c:a", "libopus", "-b:a", "160k"])
+    } else if [".mp4", ".m4v", ".mov"].iter().any(|e| lower.ends_with(e)) {
+        (
+            &["aac", "mp3", "ac3", "eac3", "alac", "opus"],
+            &["-c:a", "aac", "-b:a", "192k"],
+        )
+    } else {
+        return &["-c:a", "copy"];
+    };
+    match source_codec {
+        Some(codec) if accepted.contains(&codec) => &["-c:a", "copy"],
+        // An unknown codec is only trusted where it was trusted before
+        None if !lower.ends_with(".webm") => &["-c:a", "copy"],
+        _ => encode,
+    }
+}
+
+fn burn_args(
+    input_path: &str,
+    filter: &str,
+    output_path: &str,
+    audio_codec: Option<&str>,
+) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-y".into(),
+        "-i".into(),
+        input_path.into(),
+        "-vf".into(),
+        filter.into(),
+        "-sn".into(),
+    ];
+    let video: &[&str] = if output_path.to_lowercase().ends_with(".webm") {
+        &["-c:v", "libvpx-vp9", "-crf", "32", "-b:v", "0"]
+    } else {
+        &["-c:v", "libx264", "-crf", "20", "-preset", "medium"]
+    };
+    args.extend(video.iter().map(|a| a.to_string()));
+    args.extend(
+        audio_args(output_path, audio_codec)
+            .iter()
+            .map(|a| a.to_string()),
+    );
+    args.push(output_path.into());
+    args
+}
+
+pub async fn burn_subtitles_internal(
+    app: &impl PathContext,
+    input_path: String,
+    subtitle_path: String,
+    output_path: String,
+    style: Option<SubtitleStyle>,
+    settings: &AppSettings,
+    on_event: UnboundedSender<FFmpegEvent>,
+) -> Result<(), String> {
+    log::info!(
+        "[FFmpeg] Burning subtitles {} into {} -> {}",
+        subtitle_path,
+        input_path,
+        output_path
+    );
+    if !std::path::Path::new(&subtitle_path).is_file() {
+        return Err(format!("Subtitle file not found: {}", subtitle_path));
+    }
+    let ffmpeg_path = crate::ytdlp::resolve_ffmpeg_path(app, &settings.binary_path_ffmpeg);
+
+    let filter = crate::subtitles::burn_filter(&subtitle_path, style.as_ref());
+    let entries = [
+        "-select_streams",
+        "a:0",
+        "-show_entries",
+        "stream=codec_name",
+    ];
+    let audio_codec = probe_value(app, &input_path, &entries)
+        .await
+        .ok()
+        .filter(|c| !c.is_empty());
+    let args = burn_args(&input_path, &filter, &output_path, audio_codec.as_deref());
+    let output = run_ffmpeg(&ffmpeg_path, &args, &on_event, (0.0, 100.0), None).await?;
+    finish(output, output_path, &on_event)
+}
+
+#[derive(Deserialize)]
+struct ProbeChapters {
+    #[serde(default)]
+    chapters: Vec<ProbeChapter>,
+}
+
+#[derive(Deserialize)]
+struct ProbeChapter {
+    start_time: String,
+    end_time: String,
+    #[serde(default)]
+    tags: std::collections::HashMap<String, String>,
+}
+
+/// Reads the chapter list embedded in a local file via ffprobe.
+pub async fn probe_chapters(
+    app: &impl PathContext,
+    input_path: &str,
+) -> Result<Vec<VideoChapter>, String> {
+    let ffprobe_path = crate::ytdlp::resolve_binary_path(app, "ffprobe");
+
+    #[allow(unused_mut)]
+    let mut std_command = std::process::Command::new(&ffprobe_path);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        std_command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let output = Command::from(std_command)
+        .args(["-v", "error", "-show_chapters", "-of", "json", input_path])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "ffprobe failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let probe: ProbeChapters = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Unparseable ffprobe output: {}", e))?;
+
+    Ok(probe
+        .chapters
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| VideoChapter {
+            title: c
+                .tags
+                .get("title")
+                .cloned()
+                .unwrap_or_else(|| format!("Chapter {}", i + 1)),
+            start_time: c.start_time.parse().unwrap_or(0.0),
+            end_time: c.end_time.parse().unwrap_or(0.0),
+        })
+        .collect())
 }
 
 // ============================================================================
@@ -468,4 +1788,263 @@ mod tests {
         assert!((parse_time("05:45") - 345.0).abs() < 0.001); // 5 min 45 sec = 345 sec
         assert!((parse_time("00:30") - 30.0).abs() < 0.001); // 30 sec
     }
+
+    // --- target size tests ---
+
+    fn options(resolution: &str) -> CompressionOptions {
+        CompressionOptions {
+            resolution: resolution.to_string(),
+            encoder: "cpu".to_string(),
+            crf: 23,
+            preset: "balanced".to_string(),
+            audio_bitrate: None,
+            speed_preset: "medium".to_string(),
+            target_size_mb: Some(8.0),
+        }
+    }
+
+    #[test]
+    fn target_bitrates_fit_budget() {
+        // 8 MB over 60s is ~1035 kbps after overhead
+        let (video, audio) = target_bitrates(8_000_000.0, 60.0, 128).unwrap();
+        assert_eq!(audio, 128);
+        assert_eq!(video, 906);
+        // Tight budgets shrink audio to a quarter of the total
+        let (_, audio) = target_bitrates(1_000_000.0, 60.0, 128).unwrap();
+        assert_eq!(audio, 32);
+        assert!(target_bitrates(100_000.0, 600.0, 128).is_err());
+        assert!(target_bitrates(8_000_000.0, 0.0, 128).is_err());
+
+        assert_eq!(retry_video_kbps(1000, 10_000_000, 8_000_000.0), 760);
+        // Always lower, even when the overshoot is tiny
+        assert_eq!(retry_video_kbps(1000, 8_000_001, 8_000_000.0), 949);
+        assert_eq!(audio_kbps(&options("original")), 128);
+    }
+
+    #[test]
+    fn two_pass_args_per_container() {
+        let opts = options("720");
+        let first = two_pass_args("in.mkv", "out.mp4", 1, "log", 900, 128, &opts, false).join(" ");
+        assert_eq!(
+            first,
+            "-hide_banner -y -i in.mkv -vf scale=-2:720 -c:v libx264 -preset medium -b:v 900k \
+             -pass 1 -passlogfile log -an -f null -"
+        );
+        let second = two_pass_args("in.mkv", "out.webm", 2, "log", 900, 96, &opts, true).join(" ");
+        assert!(second.starts_with("-hide_banner -y -hwaccel auto"));
+        assert!(second.contains("-c:v libvpx-vp9"));
+        assert!(second.ends_with("-pass 2 -passlogfile log -c:a libopus -b:a 96k out.webm"));
+    }
+
+    // --- animated export tests ---
+
+    fn animated(format: AnimatedFormat) -> AnimatedExportOptions {
+        AnimatedExportOptions {
+            format,
+            fps: None,
+            height: None,
+            quality: None,
+            start: Some(5.0),
+            end: Some(8.0),
+            plays: 0,
+            boomerang: false,
+            max_size_mb: None,
+        }
+    }
+
+    #[test]
+    fn gif_uses_two_pass_palette() {
+        let mut opts = animated(AnimatedFormat::Gif);
+        opts.boomerang = true;
+        let chain = "fps=12,scale=-2:'min(360,ih)':flags=lanczos,\
+                     split[fw][bw];[bw]reverse[rv];[fw][rv]concat=n=2:v=1:a=0";
+
+        let pass1 = palette_args("in.mp4", "p.png", &opts, 12, 360);
+        assert_eq!(pass1[2..6], ["-ss", "5.000", "-t", "3.000"]);
+        assert!(pass1.contains(&format!("[0:v]{},palettegen=stats_mode=diff[p]", chain)));
+
+        let pass2 = animated_encode_args("in.mp4", "out.gif", Some("p.png"), &opts, 12, 360);
+        let joined = pass2.join(" ");
+        assert!(joined.contains("-i in.mp4 -i p.png -filter_complex"));
+        assert!(joined.contains(&format!("[0:v]{}[x];[x][1:v]paletteuse", chain)));
+        assert!(joined.ends_with("-map [out] -an -f gif -loop 0 out.gif"));
+    }
+
+    #[test]
+    fn webp_and_apng_loop_flags() {
+        let mut opts = animated(AnimatedFormat::Webp);
+        opts.plays = 3;
+        let webp = animated_encode_args("in.mp4", "out.webp", None, &opts, 15, 0).join(" ");
+        assert!(webp.contains("-filter_complex [0:v]fps=15[out]"));
+        assert!(webp.contains("-c:v libwebp -lossless 0 -q:v 80"));
+        assert!(webp.ends_with("-loop 3 out.webp"));
+
+        assert_eq!(loop_args(AnimatedFormat::Apng, 0), ["-plays", "0"]);
+        // The GIF muxer counts repeats, not plays
+        assert_eq!(loop_args(AnimatedFormat::Gif, 1), ["-loop", "-1"]);
+        assert_eq!(loop_args(AnimatedFormat::Gif, 3), ["-loop", "2"]);
+    }
+
+    #[test]
+    fn shrink_lowers_fps_and_scale() {
+        assert_eq!(
+            shrink_animated(15, 480, 2_000_000, 1_000_000.0),
+            Some((10, 394))
+        );
+        assert_eq!(
+            shrink_animated(15, 480, 1_000_001, 1_000_000.0),
+            Some((14, 470))
+        );
+        // Already at the floor: nothing left to try
+        assert_eq!(shrink_animated(8, 120, 5_000_000, 1_000_000.0), None);
+        assert_eq!(
+            shrink_animated(8, 480, 4_000_000, 1_000_000.0),
+            Some((8, 226))
+        );
+    }
+
+    #[test]
+    fn silence_splits_at_gap_midpoints_or_trims() {
+        let log: Vec<String> = [
+            "[silencedetect @ 0x1] silence_start: -0.01",
+            "[silencedetect @ 0x1] silence_end: 1.5 | silence_duration: 1.51",
+            "[silencedetect @ 0x1] silence_start: 100",
+            "[silencedetect @ 0x1] silence_end: 104 | silence_duration: 4",
+            "[silencedetect @ 0x1] silence_start: 110",
+            "[silencedetect @ 0x1] silence_end: 112 | silence_duration: 2",
+            "[silencedetect @ 0x1] silence_start: 297",
+        ]
+        .map(String::from)
+        .to_vec();
+        let silences = parse_silences(&log);
+        assert_eq!(
+            silences,
+            vec![
+                (0.0, Some(1.5)),
+                (100.0, Some(104.0)),
+                (110.0, Some(112.0)),
+                (297.0, None)
+            ]
+        );
+
+        // The pause at 110 s would leave an 8 s part, so it is not a cut
+        assert_eq!(
+            silence_parts(&silences, 300.0, false, 30.0),
+            vec![(0.0, 102.0), (102.0, 300.0)]
+        );
+        assert_eq!(
+            silence_parts(&silences, 300.0, true, 30.0),
+            vec![(1.5, 100.0), (104.0, 297.0)]
+        );
+        assert_eq!(
+            silence_parts(&silences, 300.0, true, 0.0),
+            vec![(1.5, 100.0), (104.0, 110.0), (112.0, 297.0)]
+        );
+        // A short tail joins the part before it
+        assert_eq!(
+            silence_parts(&[(100.0, Some(104.0))], 110.0, false, 30.0),
+            vec![(0.0, 110.0)]
+        );
+    }
+
+    #[test]
+    fn silence_markers_survive_status_lines() {
+        // ffmpeg ends its status line with \r and the next log line follows it
+        let stderr: &[u8] = b"Input #0, mp3, from 'talk.mp3':\n  \
+            Duration: 00:05:00.00, start: 0.025057, bitrate: 128 kb/s\n\
+            size=N/A time=00:01:39.50 bitrate=N/A speed= 199x    \r\
+            [silencedetect @ 0x600003a0c000] silence_start: 100.002\n\
+            size=N/A time=00:01:44.20 bitrate=N/A speed= 198x    \r\
+            [silencedetect @ 0x600003a0c000] silence_end: 104.1 | silence_duration: 4.098\r\n\
+            size=N/A time=00:05:00.00 bitrate=N/A speed= 200x    \n";
+        let lines = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut reader = stderr;
+                let mut buf = Vec::new();
+                let mut lines = Vec::new();
+                while let Some(line) = next_stderr_line(&mut reader, &mut buf).await.unwrap() {
+                    lines.push(line);
+                }
+                lines
+            });
+
+        assert_eq!(lines.len(), 7);
+        assert!(lines[3].starts_with("[silencedetect"));
+        assert_eq!(parse_silences(&lines), vec![(100.002, Some(104.1))]);
+        let status = lines
+            .iter()
+            .filter(|l| !l.starts_with('[') && TIME_RE.is_match(l))
+            .count();
+        assert_eq!(status, 3);
+    }
+
+    #[test]
+    fn cue_sheet_and_m3u_list_the_parts() {
+        let parts = vec![
+            VideoChapter {
+                title: "Intro".to_string(),
+                start_time: 0.0,
+                end_time: 61.2,
+            },
+            VideoChapter {
+                title: "The \"Drop\"".to_string(),
+                start_time: 61.2,
+                end_time: 185.0,
+            },
+        ];
+        assert_eq!(
+            cue_sheet("/music/set.mp3", &parts),
+            "TITLE \"set\"\nFILE \"set.mp3\" MP3\n\
+             \x20 TRACK 01 AUDIO\n    TITLE \"Intro\"\n    INDEX 01 00:00:00\n\
+             \x20 TRACK 02 AUDIO\n    TITLE \"The 'Drop'\"\n    INDEX 01 01:01:15\n"
+        );
+        let files = vec![
+            "/out/set - 01 - Intro.mp3".to_string(),
+            "/out/set - 02 - Drop.mp3".to_string(),
+        ];
+        assert_eq!(
+            m3u_playlist(&parts, &files),
+            "#EXTM3U\n#EXTINF:61,Intro\nset - 01 - Intro.mp3\n\
+             #EXTINF:124,The \"Drop\"\nset - 02 - Drop.mp3\n"
+        );
+    }
+
+    #[test]
+    fn burn_in_reencodes_video_and_copies_audio() {
+        let mp4 = burn_args("in.mkv", "subtitles=filename=s.srt", "out.mp4", Some("aac")).join(" ");
+        assert_eq!(
+            mp4,
+            "-hide_banner -y -i in.mkv -vf subtitles=filename=s.srt -sn \
+             -c:v libx264 -crf 20 -preset medium -c:a copy out.mp4"
+        );
+        let mp4 = burn_args("in.mkv", "f", "out.mp4", Some("vorbis")).join(" ");
+        assert!(mp4.ends_with("-c:a aac -b:a 192k out.mp4"));
+        let mkv = burn_args("in.mkv", "f", "out.mkv", Some("flac")).join(" ");
+        assert!(mkv.ends_with("-c:a copy out.mkv"));
+    }
+
+    #[test]
+    fn burn_in_to_webm_keeps_only_webm_audio() {
+        let webm = burn_args(
+            "in.webm",
+            "subtitles=filename=s.vtt",
+            "out.WEBM",
+            Some("opus"),
+        );
+        assert_eq!(
+            webm.join(" "),
+            "-hide_banner -y -i in.webm -vf subtitles=filename=s.vtt -sn \
+             -c:v libvpx-vp9 -crf 32 -b:v 0 -c:a copy out.WEBM"
+        );
+        for codec in [Some("aac"), Some("mp3"), None] {
+            let webm = burn_args("in.mp4", "f", "out.webm", codec).join(" ");
+            assert!(
+                webm.ends_with("-c:v libvpx-vp9 -crf 32 -b:v 0 -c:a libopus -b:a 160k out.webm"),
+                "{:?}",
+                codec
+            );
+        }
+    }
 }
@@ -0,0 +1,159 @@
+use crate::download_queue::{DownloadTask, QueueState, TaskStatus};
+use crate::history_io::{ExportFilter, ExportFormat, ImportSummary};
+use crate::history_store::{HistoryPage, HistoryQuery, HistorySite, HistoryStore};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tauri::{Manager, State};
+
+fn store(app: &tauri::AppHandle) -> Result<Arc<HistoryStore>, String> {
+    app.try_state::<Arc<HistoryStore>>()
+        .map(|s| s.inner().clone())
+        .ok_or_else(|| "History database is not available".to_string())
+}
+
+/// Paginated search over archived downloads.
+#[tauri::command]
+pub async fn query_history(
+    app: tauri::AppHandle,
+    query: Option<HistoryQuery>,
+) -> Result<HistoryPage, String> {
+    let store = store(&app)?;
+    let query = query.unwrap_or_default();
+    tokio::task::spawn_blocking(move || store.query(&query))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Distinct sites in the history with item counts, for the site filter.
+#[tauri::command]
+pub async fn get_history_sites(app: tauri::AppHandle) -> Result<Vec<HistorySite>, String> {
+    let store = store(&app)?;
+    tokio::task::spawn_blocking(move || store.sites())
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn delete_history_items(
+    app: tauri::AppHandle,
+    ids: Vec<String>,
+) -> Result<usize, String> {
+    log::info!("User deleted {} history item(s)", ids.len());
+    let removed = store(&app)?.delete(&ids)?;
+    crate::previews::remove_cached(&app, &removed);
+    Ok(removed.len())
+}
+
+#[tauri::command]
+pub async fn clear_history(app: tauri::AppHandle) -> Result<usize, String> {
+    log::info!("User cleared download history");
+    let store = store(&app)?;
+    let ids: Vec<String> = store
+        .query_all(&HistoryQuery::default())?
+        .into_iter()
+        .map(|t| t.id)
+        .collect();
+    let removed = store.clear()?;
+    crate::previews::remove_cached(&app, &ids);
+    Ok(removed)
+}
+
+/// Writes queue and archived history records matching `filter` to `path`.
+/// Returns the number of records exported.
+#[tauri::command]
+pub async fn export_history(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<QueueState>>,
+    path: String,
+    format: ExportFormat,
+    filter: Option<ExportFilter>,
+) -> Result<usize, String> {
+    let filter = filter.unwrap_or_default();
+
+    let mut records: Vec<DownloadTask> = {
+        let tasks = state.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks
+            .values()
+            .filter(|t| t.job.is_none() && filter.matches(t))
+            .cloned()
+            .collect()
+    };
+    if let Ok(store) = store(&app) {
+        let query = filter.as_history_query();
+        let archived = tokio::task::spawn_blocking(move || store.query_all(&query))
+            .await
+            .map_err(|e| e.to_string())??;
+        let seen: HashSet<String> = records.iter().map(|t| t.id.clone()).collect();
+        records.extend(archived.into_iter().filter(|t| !seen.contains(&t.id)));
+    }
+    records.sort_by_key(crate::history_io::finished_at);
+
+    let content = crate::history_io::render(format, &records)?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write export: {}", e))?;
+
+    log::info!(
+        "User exported {} history record(s) as {:?} to {}",
+        records.len(),
+        format,
+        path
+    );
+    Ok(records.len())
+}
+
+/// Merges a JSON export into the queue, skipping URLs and files that are already
+/// known. Completed records go straight to the history database when it's available.
+#[tauri::command]
+pub async fn import_history(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<QueueState>>,
+    path: String,
+) -> Result<ImportSummary, String> {
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read import: {}", e))?;
+    let (incoming, invalid) = crate::history_io::parse_import(&content)?;
+
+    let store = store(&app).ok();
+    let (mut urls, mut paths) = match &store {
+        Some(store) => store.known_keys()?,
+        None => Default::default(),
+    };
+    let existing_ids: HashSet<String> = {
+        let tasks = state.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        for t in tasks.values() {
+            urls.insert(crate::ytdlp::sanitize_url(t.url.trim()));
+            if let Some(p) = t.file_path.as_ref().filter(|p| !p.is_empty()) {
+                paths.insert(p.clone());
+            }
+        }
+        tasks.keys().cloned().collect()
+    };
+
+    let (accepted, duplicates) =
+        crate::history_io::dedupe_import(incoming, &mut urls, &mut paths, &existing_ids);
+    let imported = accepted.len();
+
+    let (completed, rest): (Vec<DownloadTask>, Vec<DownloadTask>) = match &store {
+        Some(_) => accepted
+            .into_iter()
+            .partition(|t| t.status == TaskStatus::Completed),
+        None => (Vec::new(), accepted),
+    };
+    if let Some(store) = &store {
+        store.archive(&completed)?;
+    }
+    state.enqueue_all(rest);
+    crate::download_queue::emit_queue_update(&app, &state);
+
+    log::info!(
+        "User imported {} record(s) from {} ({} duplicates, {} invalid)",
+        imported,
+        path,
+        duplicates,
+        invalid
+    );
+    Ok(ImportSummary {
+        imported,
+        duplicates,
+        invalid,
+    })
+}
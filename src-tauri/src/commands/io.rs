@@ -1,17 +1,81 @@
+use crate::batch_file::{self, BatchEntry, BatchError, BatchParseResult};
+use crate::download_queue::{DownloadTask, QueueState};
+use crate::duplicates::{enqueue_with_policy, DuplicatePolicy, EnqueueResult};
+use crate::ytdlp::{SupportedSites, YtDlpOptions};
+use serde::Serialize;
 use std::fs;
-use tauri::command;
+use std::sync::Arc;
+use tauri::{command, State};
 
+fn read_batch(path: &str, options: &YtDlpOptions) -> Result<BatchParseResult, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let format = batch_file::detect_format(path, &content);
+    Ok(batch_file::parse(&content, format, options))
+}
+
+/// URLs only, for callers that don't need the per-line options.
 #[command]
 pub fn parse_batch_file(path: String) -> Result<Vec<String>, String> {
-    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let result = read_batch(&path, &YtDlpOptions::default())?;
+    Ok(result.entries.into_iter().map(|e| e.url).collect())
+}
+
+/// Parses a text, yt-dlp, CSV or JSON batch file. Each entry's `options` is
+/// `options` with that line's overrides applied, ready for `add_to_queue`.
+#[command]
+pub fn parse_batch_entries(
+    path: String,
+    options: Option<YtDlpOptions>,
+) -> Result<BatchParseResult, String> {
+    read_batch(&path, &options.unwrap_or_default())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchImportResult {
+    pub added: Vec<EnqueueResult>,
+    pub errors: Vec<BatchError>,
+}
+
+/// Parses a batch file and enqueues every valid entry.
+#[command]
+pub async fn import_batch_file(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    path: String,
+    options: Option<YtDlpOptions>,
+    duplicate_policy: Option<DuplicatePolicy>,
+    sites: State<'_, Arc<SupportedSites>>,
+) -> Result<BatchImportResult, String> {
+    let parsed = read_batch(&path, &options.unwrap_or_default())?;
+    let policy = duplicate_policy.unwrap_or_default();
+    let mut errors = parsed.errors;
+    let mut added = Vec::new();
 
-    let urls: Vec<String> = content
-        .lines()
-        .map(|l| l.trim())
-        .filter(|l| !l.is_empty() && !l.starts_with('#'))
-        .filter(|l| l.starts_with("http://") || l.starts_with("https://")) // Basic validation
-        .map(|l| l.to_string())
-        .collect();
+    for BatchEntry {
+        position,
+        url,
+        options,
+    } in parsed.entries
+    {
+        if !sites.matches(&url) {
+            errors.push(BatchError {
+                position,
+                message: format!("URL not supported: {}", url),
+            });
+            continue;
+        }
+        let task = DownloadTask::new_pending(url, options);
+        added.push(enqueue_with_policy(&app, &state, task, policy).await);
+    }
+    errors.sort_by_key(|e| e.position);
 
-    Ok(urls)
+    log::info!(
+        "[Batch] Imported {} ({:?}): {} entries, {} errors",
+        path,
+        parsed.format,
+        added.len(),
+        errors.len()
+    );
+    Ok(BatchImportResult { added, errors })
 }
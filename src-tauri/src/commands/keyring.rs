@@ -42,3 +42,56 @@ pub async fn delete_credential(service: String, username: String) -> Result<(),
     entry.delete_credential().map_err(|e| e.to_string())?;
     Ok(())
 }
+
+// Local HTTP API bearer token (see server.rs)
+const API_TOKEN_SERVICE: &str = "sceneclip:local-api";
+const API_TOKEN_USER: &str = "api-token";
+
+fn generate_api_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Returns the API token stored in the keyring, generating one on first run.
+pub fn get_or_create_api_token() -> Result<String, String> {
+    let entry = Entry::new(API_TOKEN_SERVICE, API_TOKEN_USER).map_err(|e| e.to_string())?;
+    match entry.get_password() {
+        Ok(token) if !token.is_empty() => Ok(token),
+        Ok(_) | Err(keyring::Error::NoEntry) => {
+            let token = generate_api_token();
+            entry.set_password(&token).map_err(|e| e.to_string())?;
+            log::info!("[API] Generated new local API token");
+            Ok(token)
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Replaces the stored API token; existing clients must be updated.
+pub fn rotate_api_token() -> Result<String, String> {
+    let token = generate_api_token();
+    let entry = Entry::new(API_TOKEN_SERVICE, API_TOKEN_USER).map_err(|e| e.to_string())?;
+    entry.set_password(&token).map_err(|e| e.to_string())?;
+    Ok(token)
+}
+
+#[command]
+pub async fn get_api_token(app: tauri::AppHandle) -> Result<String, String> {
+    use tauri::Manager;
+    // Prefer the token the server is actually using (may be session-only if the keyring failed)
+    if let Some(token) = app.try_state::<crate::server::ApiToken>() {
+        return Ok(token.0.read().unwrap_or_else(|e| e.into_inner()).clone());
+    }
+    get_or_create_api_token()
+}
+
+#[command]
+pub async fn regenerate_api_token(app: tauri::AppHandle) -> Result<String, String> {
+    let token = rotate_api_token()?;
+    crate::server::set_api_token(&app, token.clone());
+    log::info!("[API] Local API token regenerated");
+    Ok(token)
+}
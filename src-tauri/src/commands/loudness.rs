@@ -0,0 +1,73 @@
+use crate::download_queue::QueueState;
+use crate::loudness::{self, LoudnessReport, LoudnessTarget};
+use std::sync::Arc;
+use tauri::State;
+
+fn ffmpeg_path(app: &tauri::AppHandle) -> String {
+    let settings = crate::ytdlp::load_settings(app);
+    crate::ytdlp::resolve_ffmpeg_path(app, &settings.binary_path_ffmpeg)
+}
+
+/// Keeps the report on the task so the levels can be audited from history.
+fn record(
+    app: &tauri::AppHandle,
+    state: &QueueState,
+    task_id: Option<&str>,
+    report: &LoudnessReport,
+) -> Result<(), String> {
+    if let Some(id) = task_id {
+        crate::history_store::update_task_record(app, state, id, |t| {
+            t.loudness = Some(report.clone());
+        })?;
+    }
+    Ok(())
+}
+
+/// Measures integrated loudness, true peak and LRA of `input_path`.
+#[tauri::command]
+pub async fn analyze_loudness(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<QueueState>>,
+    input_path: String,
+    task_id: Option<String>,
+    target: Option<LoudnessTarget>,
+) -> Result<LoudnessReport, String> {
+    let report =
+        loudness::analyze(&ffmpeg_path(&app), &input_path, target.unwrap_or_default()).await?;
+    record(&app, &state, task_id.as_deref(), &report)?;
+    Ok(report)
+}
+
+/// Two-pass loudnorm to `target`. Writes `<name>_normalized.<ext>` next to
+/// the input unless `output_path` is given (which may be the input itself).
+#[tauri::command]
+pub async fn normalize_loudness(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<QueueState>>,
+    input_path: String,
+    output_path: Option<String>,
+    task_id: Option<String>,
+    target: Option<LoudnessTarget>,
+) -> Result<LoudnessReport, String> {
+    if !std::path::Path::new(&input_path).is_file() {
+        return Err(format!("File not found: {}", input_path));
+    }
+    let output_path = output_path
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| crate::follow_up::sibling_path(&input_path, "_normalized", None));
+
+    log::info!(
+        "User requested loudness normalization: {} -> {}",
+        input_path,
+        output_path
+    );
+    let report = loudness::normalize(
+        &ffmpeg_path(&app),
+        &input_path,
+        &output_path,
+        target.unwrap_or_default(),
+    )
+    .await?;
+    record(&app, &state, task_id.as_deref(), &report)?;
+    Ok(report)
+}
@@ -1,5 +1,9 @@
+use crate::download_queue::QueueState;
+use crate::history_store::HistoryStore;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tauri::{Manager, State};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LanguageOption {
@@ -23,6 +27,53 @@ pub struct ParsedMetadata {
     pub filesize_approx: Option<u64>,
 }
 
+/// Editable tags of a finished file, under ffmpeg's generic key names. In
+/// an edit, `None` leaves a tag alone and an empty string removes it; values
+/// may use `{field}` templates over yt-dlp's info JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaTags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    /// `3` or `3/12`
+    pub track: Option<String>,
+    pub date: Option<String>,
+    pub genre: Option<String>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaTagInfo {
+    pub path: String,
+    pub tags: MediaTags,
+    pub has_cover: bool,
+}
+
+/// What happens to the embedded cover when tags are written.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum CoverArt {
+    #[default]
+    Keep,
+    Remove,
+    /// Any image ffmpeg can read; formats other than JPEG/PNG are converted.
+    Image {
+        path: String,
+    },
+}
+
+/// Outcome of tagging one history item in a batch.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagResult {
+    pub task_id: String,
+    pub info: Option<MediaTagInfo>,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct YtDlpFormat {
     height: Option<u32>,
@@ -209,3 +260,82 @@ pub fn parse_video_metadata(raw_json: serde_json::Value) -> Result<ParsedMetadat
         filesize_approx: meta.filesize_approx,
     })
 }
+
+/// Reads title, artist, album, track, date, comment and cover presence of a
+/// finished file.
+#[tauri::command]
+pub async fn read_media_tags(app: tauri::AppHandle, path: String) -> Result<MediaTagInfo, String> {
+    crate::tagging::read(&app, &path).await
+}
+
+/// Writes `tags` to one file. Templates are filled from `info`, or from the
+/// `.info.json` yt-dlp left next to the file.
+#[tauri::command]
+pub async fn write_media_tags(
+    app: tauri::AppHandle,
+    path: String,
+    tags: MediaTags,
+    cover: Option<CoverArt>,
+    info: Option<serde_json::Value>,
+) -> Result<MediaTagInfo, String> {
+    let info = info.unwrap_or_else(|| crate::tagging::info_json(&path, None));
+    let tags = crate::tagging::render_tags(&tags, &info);
+    crate::tagging::write(&app, &path, &tags, &cover.unwrap_or_default()).await
+}
+
+/// Applies the same edit to the files of several history items, rendering
+/// templates per item. A failing item doesn't stop the others; retitled
+/// items get the new title in history too.
+#[tauri::command]
+pub async fn tag_history_items(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<QueueState>>,
+    task_ids: Vec<String>,
+    tags: MediaTags,
+    cover: Option<CoverArt>,
+) -> Result<Vec<TagResult>, String> {
+    let store = app
+        .try_state::<Arc<HistoryStore>>()
+        .map(|s| s.inner().clone());
+    let cover = cover.unwrap_or_default();
+
+    let mut results = Vec::new();
+    for id in task_ids {
+        let outcome = async {
+            // The newest completed tasks are still in the queue, not in SQLite
+            let queued = state
+                .tasks
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(&id)
+                .cloned();
+            let task = match (queued, &store) {
+                (Some(task), _) => task,
+                (None, Some(store)) => store.get(&id)?.ok_or("History item not found")?,
+                (None, None) => return Err("History database is not available".to_string()),
+            };
+            let path = task
+                .file_path
+                .clone()
+                .ok_or("The download has no file on disk")?;
+            let info = crate::tagging::info_json(&path, Some(&task));
+            let rendered = crate::tagging::render_tags(&tags, &info);
+            let written = crate::tagging::write(&app, &path, &rendered, &cover).await?;
+            if let Some(title) = rendered.title.filter(|t| !t.is_empty()) {
+                crate::history_store::update_task_record(&app, &state, &id, |t| t.title = title)?;
+            }
+            Ok::<_, String>(written)
+        }
+        .await;
+
+        if let Err(e) = &outcome {
+            log::warn!("[Tagging] Skipped {}: {}", id, e);
+        }
+        results.push(TagResult {
+            task_id: id,
+            error: outcome.as_ref().err().cloned(),
+            info: outcome.ok(),
+        });
+    }
+    Ok(results)
+}
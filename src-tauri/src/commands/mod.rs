@@ -2,15 +2,21 @@ pub mod analysis;
 pub mod download;
 pub mod ffmpeg;
 pub mod filesystem;
+pub mod history;
 pub mod integrity;
 pub mod io;
 pub mod keyring;
+pub mod loudness;
 pub mod metadata;
 pub mod notifications;
 pub mod power;
+pub mod previews;
 pub mod process;
 pub mod queue; // Added
 pub mod settings;
 pub mod stats;
+pub mod subscriptions;
+pub mod subtitles;
 pub mod system;
 pub mod updater;
+pub mod watch_folders;
@@ -0,0 +1,29 @@
+use crate::download_queue::QueueState;
+use crate::previews::{self, PreviewRequest, TaskPreviews};
+use std::sync::Arc;
+use tauri::State;
+
+/// Generates a poster, thumbnails or a contact sheet for `input_path`. With a
+/// `task_id` the images are cached per task and recorded on the queue item or
+/// history entry, so they are only generated once.
+#[tauri::command]
+pub async fn generate_previews(
+    app: tauri::AppHandle,
+    state: State<'_, Arc<QueueState>>,
+    input_path: String,
+    request: PreviewRequest,
+    task_id: Option<String>,
+    width: Option<u32>,
+) -> Result<TaskPreviews, String> {
+    let dir = previews::cache_dir(&app, task_id.as_deref(), &input_path)?;
+    let generated = previews::generate(&app, &input_path, &dir, &request, width).await?;
+
+    if let Some(id) = task_id {
+        crate::history_store::update_task_record(&app, &state, &id, |t| {
+            t.previews
+                .get_or_insert_with(TaskPreviews::default)
+                .merge(generated.clone());
+        })?;
+    }
+    Ok(generated)
+}
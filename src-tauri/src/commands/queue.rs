@@ -1,4 +1,9 @@
-use crate::download_queue::{DownloadTask, QueueState, TaskStatus};
+use crate::download_queue::{
+    DownloadTask, GroupProgress, QueueState, TaskGroup, TaskPriority, TaskStatus,
+};
+use crate::duplicates::{enqueue_with_policy, DuplicatePolicy, EnqueueResult};
+use crate::follow_up::FollowUpAction;
+use crate::playlist::{PlaylistEntry, PlaylistInfo};
 use crate::ytdlp::{SupportedSites, YtDlpOptions};
 use std::sync::Arc;
 use tauri::State;
@@ -9,8 +14,12 @@ pub async fn add_to_queue(
     app: tauri::AppHandle,
     url: String,
     options: YtDlpOptions,
+    follow_ups: Option<Vec<FollowUpAction>>,
+    duplicate_policy: Option<DuplicatePolicy>,
+    video_id: Option<String>,
+    extractor: Option<String>,
     sites: State<'_, Arc<SupportedSites>>,
-) -> Result<String, String> {
+) -> Result<EnqueueResult, String> {
     // VALIDATE URL against supported sites FIRST
     if !sites.matches(&url) {
         log::warn!("[Queue] Rejected unsupported URL: {}", url);
@@ -19,47 +28,173 @@ pub async fn add_to_queue(
         ));
     }
 
-    let id = uuid::Uuid::new_v4().to_string();
+    let mut task = DownloadTask::new_pending(url, options);
+    task.follow_ups = follow_ups.unwrap_or_default();
+    task.video_id = video_id;
+    task.extractor = extractor;
+
+    log::info!(
+        "User added new task to queue: {} (ID: {})",
+        task.url,
+        task.id
+    );
+
+    Ok(enqueue_with_policy(&app, &state, task, duplicate_policy.unwrap_or_default()).await)
+}
+
+/// Lists the entries of a playlist/channel URL without downloading anything,
+/// so the UI can show a selection dialog before enqueueing.
+#[tauri::command]
+pub async fn expand_playlist(
+    app: tauri::AppHandle,
+    url: String,
+    sites: State<'_, Arc<SupportedSites>>,
+) -> Result<PlaylistInfo, String> {
+    if !sites.matches(&url) {
+        log::warn!("[Queue] Rejected unsupported playlist URL: {}", url);
+        return Err(
+            "URL not supported. This site is not in the yt-dlp supported sites list.".to_string(),
+        );
+    }
 
-    log::info!("User added new task to queue: {} (ID: {})", url, id);
+    let settings = crate::ytdlp::load_settings(&app);
+    let ytdlp_path = crate::ytdlp::resolve_ytdlp_path(&app, &settings.binary_path_yt_dlp);
+    crate::playlist::fetch_playlist(&ytdlp_path, &url, &settings).await
+}
 
-    // We construct the task
-    // Note: 'title', 'speed', etc. are just placeholders initially
-    let task = DownloadTask {
-        id: id.clone(),
+/// Enqueues a playlist as a group of child tasks sharing `options`.
+/// `entries` comes from `expand_playlist`; when omitted the URL is expanded here.
+/// `items` narrows the selection using yt-dlp style ranges (e.g. "1-5,8").
+#[tauri::command]
+pub async fn add_playlist_to_queue(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    url: String,
+    options: YtDlpOptions,
+    entries: Option<Vec<PlaylistEntry>>,
+    items: Option<String>,
+    title: Option<String>,
+    sites: State<'_, Arc<SupportedSites>>,
+) -> Result<String, String> {
+    if !sites.matches(&url) {
+        log::warn!("[Queue] Rejected unsupported playlist URL: {}", url);
+        return Err(
+            "URL not supported. This site is not in the yt-dlp supported sites list.".to_string(),
+        );
+    }
+
+    let (entries, playlist_title) = match entries {
+        Some(entries) => (entries, title),
+        None => {
+            let settings = crate::ytdlp::load_settings(&app);
+            let ytdlp_path = crate::ytdlp::resolve_ytdlp_path(&app, &settings.binary_path_yt_dlp);
+            let info = crate::playlist::fetch_playlist(&ytdlp_path, &url, &settings).await?;
+            (info.entries, title.or(Some(info.title)))
+        }
+    };
+
+    let selected: Vec<PlaylistEntry> = match items.as_deref().map(str::trim) {
+        Some(spec) if !spec.is_empty() => {
+            let max_index = entries.iter().map(|e| e.index as usize).max().unwrap_or(0);
+            let wanted = crate::playlist::parse_playlist_items(spec, max_index)?;
+            entries
+                .into_iter()
+                .filter(|e| wanted.contains(&(e.index as usize)))
+                .collect()
+        }
+        _ => entries,
+    };
+
+    if selected.is_empty() {
+        return Err("No playlist entries selected".to_string());
+    }
+
+    let group = TaskGroup {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: playlist_title.unwrap_or_else(|| url.clone()),
         url,
-        title: "Queued...".to_string(),
-        status: TaskStatus::Pending,
-        progress: 0.0,
-        speed: Some("-".to_string()),
-        eta: Some("-".to_string()),
-        path: options.path.clone().unwrap_or_default(),
-        error_message: None,
         added_at: std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs(),
-        // New fields initialized to None
-        pid: None,
-        status_detail: None,
-        eta_raw: None,
-        speed_raw: None,
-        total_size: None,
-        range: None,
-        format: options.format.clone(),
-        file_path: None,
-        // Frontend sends scheduledTime in milliseconds (JS Date.getTime()),
-        // but queue processor compares against seconds (.as_secs()), so convert ms -> s
-        scheduled_time: options.scheduled_time.map(|ms| ms / 1000),
-        retry_count: Some(0),
-        ytdlp_command: None,
-        file_size: None,
-        completed_at: None,
-        options,
     };
+    let group_id = group.id.clone();
+
+    let children: Vec<DownloadTask> = selected
+        .into_iter()
+        .map(|entry| {
+            let mut task = DownloadTask::new_pending(entry.url, options.clone());
+            if let Some(t) = entry.title {
+                task.title = t;
+            }
+            task.playlist_index = Some(entry.index);
+            task.video_id = entry.id;
+            task.extractor = entry.ie_key;
+            task
+        })
+        .collect();
 
-    state.add_task(task, &app);
-    Ok(id)
+    log::info!(
+        "User added playlist \"{}\" to queue: {} entries (Group: {})",
+        group.title,
+        children.len(),
+        group_id
+    );
+
+    state.add_group(group, children, &app);
+    Ok(group_id)
+}
+
+#[tauri::command]
+pub async fn get_group_progress(
+    state: State<'_, Arc<QueueState>>,
+    group_id: Option<String>,
+) -> Result<Vec<GroupProgress>, String> {
+    match group_id {
+        Some(id) => state
+            .group_progress(&id)
+            .map(|p| vec![p])
+            .ok_or_else(|| "Group not found".to_string()),
+        None => Ok(state.all_group_progress()),
+    }
+}
+
+#[tauri::command]
+pub async fn pause_group(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    group_id: String,
+) -> Result<usize, String> {
+    log::info!("User paused group: {}", group_id);
+    state.pause_group(&group_id, &app)
+}
+
+#[tauri::command]
+pub async fn resume_group(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    group_id: String,
+) -> Result<usize, String> {
+    log::info!("User resumed group: {}", group_id);
+    state.resume_group(&group_id, &app)
+}
+
+#[tauri::command]
+pub async fn remove_group(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    group_id: String,
+) -> Result<(), String> {
+    log::info!("User removed group: {}", group_id);
+    let removed = state.remove_group(&group_id, &app);
+    for task in removed {
+        let _ = crate::commands::download::cancel_download_internal(
+            task.id.clone(),
+            state.inner().clone(),
+        )
+        .await;
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -68,47 +203,56 @@ pub async fn remove_from_queue(
     app: tauri::AppHandle,
     id: String,
 ) -> Result<(), String> {
-    // remove_task now kills process tree FIRST, then removes from maps,
-    // then aborts tokio handle. This ensures all processes are dead.
-    let removed_task_opt = state.remove_task(&id, &app);
-
-    if let Some(task) = removed_task_opt {
+    if let Some(task) = remove_task_with_cleanup(state.inner(), &app, &id).await {
         log::info!("User removed task: {} (URL: {})", id, task.url);
+    }
+    Ok(())
+}
 
-        // Safety: also try cancel_download_internal in case abort handle
-        // was stored elsewhere or kill_on_drop needs to trigger
-        let _ =
-            crate::commands::download::cancel_download_internal(id, state.inner().clone()).await;
-
-        // File Cleanup
-        if let Some(path_str) = task.file_path {
-            let path = std::path::Path::new(&path_str);
-            let mut candidates = Vec::new();
-
-            // ONLY remove the main file if it's NOT completed or stopped.
-            // If it IS completed, the user might want to keep it even if removing from history.
-            if !matches!(task.status, TaskStatus::Completed | TaskStatus::Stopped) {
-                candidates.push(path.to_path_buf());
-            }
+/// Removes a task and deletes its partial/temp files. Shared by the Tauri
+/// command and the local HTTP API.
+pub async fn remove_task_with_cleanup(
+    state: &Arc<QueueState>,
+    app: &tauri::AppHandle,
+    id: &str,
+) -> Option<DownloadTask> {
+    // remove_task now kills process tree FIRST, then removes from maps,
+    // then aborts tokio handle. This ensures all processes are dead.
+    let task = state.remove_task(id, app)?;
+
+    // Safety: also try cancel_download_internal in case abort handle
+    // was stored elsewhere or kill_on_drop needs to trigger
+    let _ =
+        crate::commands::download::cancel_download_internal(id.to_string(), state.clone()).await;
+
+    // File Cleanup
+    if let Some(path_str) = &task.file_path {
+        let path = std::path::Path::new(path_str);
+        let mut candidates = Vec::new();
+
+        // ONLY remove the main file if it's NOT completed or stopped.
+        // If it IS completed, the user might want to keep it even if removing from history.
+        if !matches!(task.status, TaskStatus::Completed | TaskStatus::Stopped) {
+            candidates.push(path.to_path_buf());
+        }
 
-            // Always try to remove partial/temp files associated with this download
-            if let Some(file_name) = path.file_name() {
-                if let Some(parent) = path.parent() {
-                    candidates.push(parent.join(format!("{}.part", file_name.to_string_lossy())));
-                    candidates.push(parent.join(format!("{}.ytdl", file_name.to_string_lossy())));
+        // Always try to remove partial/temp files associated with this download
+        if let Some(file_name) = path.file_name() {
+            if let Some(parent) = path.parent() {
+                candidates.push(parent.join(format!("{}.part", file_name.to_string_lossy())));
+                candidates.push(parent.join(format!("{}.ytdl", file_name.to_string_lossy())));
 
-                    if let Some(stem) = path.file_stem() {
-                        candidates.push(parent.join(format!("{}.part", stem.to_string_lossy())));
-                    }
+                if let Some(stem) = path.file_stem() {
+                    candidates.push(parent.join(format!("{}.part", stem.to_string_lossy())));
                 }
             }
+        }
 
-            for p in candidates {
-                let _ = std::fs::remove_file(p);
-            }
+        for p in candidates {
+            let _ = std::fs::remove_file(p);
         }
     }
-    Ok(())
+    Some(task)
 }
 
 #[tauri::command]
@@ -121,59 +265,67 @@ pub async fn pause_task(
     let res = state.pause_task(&id, &app);
 
     if res.is_ok() {
-        // Hybrid State Machine: Phase 1 (Soft Pause) -> Phase 2 (Hard Pause)
-        let state_arc = (*state).clone();
-        let app_handle = app.clone();
-        let task_id = id.clone();
-
-        tokio::spawn(async move {
-            tokio::time::sleep(tokio::time::Duration::from_secs(180)).await;
-
-            let (should_hard_pause, pid) = {
-                let tasks = state_arc.tasks.lock().unwrap_or_else(|e| e.into_inner());
-                if let Some(task) = tasks.get(&task_id) {
-                    // Check if the task is still Soft Paused (has a PID and is in Paused state)
-                    if task.status == TaskStatus::Paused && task.pid.is_some() {
-                        (true, task.pid.unwrap())
-                    } else {
-                        (false, 0)
-                    }
+        schedule_hard_pause(state.inner().clone(), app, id);
+    }
+
+    res
+}
+
+/// Hybrid State Machine: Phase 1 (Soft Pause) -> Phase 2 (Hard Pause).
+/// After 3 minutes a still-suspended task is killed and flushed to disk.
+pub fn schedule_hard_pause(
+    state_arc: Arc<QueueState>,
+    app_handle: tauri::AppHandle,
+    task_id: String,
+) {
+    tokio::spawn(async move {
+        tokio::time::sleep(tokio::time::Duration::from_secs(180)).await;
+
+        let (should_hard_pause, pid) = {
+            let tasks = state_arc.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(task) = tasks.get(&task_id) {
+                // Check if the task is still Soft Paused (has a PID and is in Paused state)
+                if task.status == TaskStatus::Paused && task.pid.is_some() {
+                    (true, task.pid.unwrap())
                 } else {
                     (false, 0)
                 }
-            };
-
-            if should_hard_pause {
-                log::info!(
-                    "Task {} soft-pause expired (3 mins). Transitioning to Hard Pause...",
-                    task_id
-                );
-                // Wake up process so it can gracefully flush to `.part` disk file when killed
-                let _ = crate::commands::process::resume_process(pid);
-                // Gracefully kill the process tree
-                let _ = crate::commands::process::kill_process_tree(pid);
-
-                {
-                    let mut tasks = state_arc.tasks.lock().unwrap_or_else(|e| e.into_inner());
-                    if let Some(task) = tasks.get_mut(&task_id) {
-                        task.pid = None; // Nullifying PID uniquely represents PausedHard
-                        task.status_detail = Some("Paused (Hibernating)".to_string());
-                    }
-                }
-
-                let mut handles = state_arc.abort_handles.lock().unwrap_or_else(|e| e.into_inner());
-                if let Some(handle) = handles.remove(&task_id) {
-                    handle.abort(); // Clear thread resources
+            } else {
+                (false, 0)
+            }
+        };
+
+        if should_hard_pause {
+            log::info!(
+                "Task {} soft-pause expired (3 mins). Transitioning to Hard Pause...",
+                task_id
+            );
+            // Wake up process so it can gracefully flush to `.part` disk file when killed
+            let _ = crate::commands::process::resume_process(pid);
+            // Gracefully kill the process tree
+            let _ = crate::commands::process::kill_process_tree(pid);
+
+            {
+                let mut tasks = state_arc.tasks.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(task) = tasks.get_mut(&task_id) {
+                    task.pid = None; // Nullifying PID uniquely represents PausedHard
+                    task.status_detail = Some("Paused (Hibernating)".to_string());
                 }
+            }
 
-                state_arc.save();
-                // Send event to UI so status_detail updates
-                crate::download_queue::emit_queue_update(&app_handle, &state_arc);
+            let mut handles = state_arc
+                .abort_handles
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            if let Some(handle) = handles.remove(&task_id) {
+                handle.abort(); // Clear thread resources
             }
-        });
-    }
 
-    res
+            state_arc.save();
+            // Send event to UI so status_detail updates
+            crate::download_queue::emit_queue_update(&app_handle, &state_arc);
+        }
+    });
 }
 
 #[tauri::command]
@@ -186,19 +338,153 @@ pub async fn resume_task(
     state.resume_task(&id, &app)
 }
 
+/// Re-queues a stopped or failed task, keeping its ID and partial files.
 #[tauri::command]
-pub async fn pause_queue(_state: State<'_, Arc<QueueState>>) -> Result<(), String> {
-    // TODO: implement global pause
+pub async fn retry_task(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<(), String> {
+    log::info!("User retried task: {}", id);
+    state.retry_task(&id, &app)
+}
+
+#[tauri::command]
+pub async fn pause_queue(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    log::info!("User paused queue");
+    state.pause_all(&app);
     Ok(())
 }
 
 #[tauri::command]
-pub async fn resume_queue(state: State<'_, Arc<QueueState>>) -> Result<(), String> {
-    // TODO: implement global resume
-    state.notify.notify_one();
+pub async fn resume_queue(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    log::info!("User resumed queue");
+    state.resume_all(&app);
     Ok(())
 }
 
+#[tauri::command]
+pub async fn move_task(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    id: String,
+    index: usize,
+) -> Result<(), String> {
+    log::info!("User moved task {} to position {}", id, index);
+    state.move_task(&id, index, &app)
+}
+
+#[tauri::command]
+pub async fn move_task_to_top(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<(), String> {
+    log::info!("User moved task {} to top", id);
+    state.move_task_to_top(&id, &app)
+}
+
+#[tauri::command]
+pub async fn move_task_to_bottom(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<(), String> {
+    log::info!("User moved task {} to bottom", id);
+    state.move_task_to_bottom(&id, &app)
+}
+
+#[tauri::command]
+pub async fn set_task_priority(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    id: String,
+    priority: TaskPriority,
+) -> Result<(), String> {
+    log::info!("User set task {} priority to {:?}", id, priority);
+    state.set_task_priority(&id, priority, &app)
+}
+
+/// Replaces a task's follow-up actions. On an already completed download they
+/// are queued right away.
+#[tauri::command]
+pub async fn set_task_follow_ups(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    id: String,
+    follow_ups: Vec<FollowUpAction>,
+) -> Result<usize, String> {
+    log::info!("User set {} follow-up(s) on task {}", follow_ups.len(), id);
+    let completed_file = {
+        let mut tasks = state.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let task = tasks
+            .get_mut(&id)
+            .ok_or_else(|| "Task not found".to_string())?;
+        if task.job.is_some() {
+            return Err("Follow-up tasks can't have follow-ups of their own".to_string());
+        }
+        task.follow_ups = follow_ups;
+        match (&task.status, &task.file_path) {
+            (TaskStatus::Completed, Some(path)) => Some(path.clone()),
+            _ => None,
+        }
+    };
+    state.save();
+
+    Ok(match completed_file {
+        Some(path) => state.spawn_follow_ups(&id, &path, &app),
+        None => {
+            crate::download_queue::emit_queue_update(&app, &state);
+            0
+        }
+    })
+}
+
+/// Queues `action` as a standalone local job for every media file in `inputs`
+/// (files or folders), writing results to `output_dir` or next to each file.
+/// Jobs persist with the queue and run under the `concurrentTranscodes` limit.
+/// Returns the new task IDs.
+#[tauri::command]
+pub async fn add_local_jobs(
+    state: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    inputs: Vec<String>,
+    action: FollowUpAction,
+    recursive: Option<bool>,
+    output_dir: Option<String>,
+    priority: Option<TaskPriority>,
+) -> Result<Vec<String>, String> {
+    let files = crate::follow_up::collect_media_files(&inputs, recursive.unwrap_or(false))?;
+    if files.is_empty() {
+        return Err("No media files found".to_string());
+    }
+    log::info!(
+        "User queued {} for {} local file(s)",
+        action.label(),
+        files.len()
+    );
+
+    let tasks: Vec<_> = files
+        .into_iter()
+        .map(|file| {
+            let mut task =
+                crate::follow_up::local_job_task(action.clone(), file, output_dir.clone());
+            task.priority = priority.unwrap_or_default();
+            task
+        })
+        .collect();
+    let ids = tasks.iter().map(|t| t.id.clone()).collect();
+    state.enqueue_all(tasks);
+    crate::download_queue::emit_queue_update(&app, &state);
+    Ok(ids)
+}
+
 #[tauri::command]
 pub async fn get_queue_state(
     state: State<'_, Arc<QueueState>>,
@@ -252,7 +538,8 @@ pub async fn add_history_item(
     file_path: String,
     file_size: Option<String>,
 ) -> Result<String, String> {
-    let id = uuid::Uuid::new_v4().to_string();
+    let mut task = DownloadTask::new_pending(url, Default::default());
+    let id = task.id.clone();
 
     log::info!(
         "Adding history item: {} (ID: {}, Path: {})",
@@ -262,43 +549,26 @@ pub async fn add_history_item(
     );
 
     // Create a completed task for history
-    let task = DownloadTask {
-        id: id.clone(),
-        url,
-        title,
-        status: TaskStatus::Completed,
-        progress: 100.0,
-        speed: None,
-        eta: None,
-        path: std::path::Path::new(&file_path)
-            .parent()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_default(),
-        error_message: None,
-        added_at: std::time::SystemTime::now()
+    task.title = title;
+    task.status = TaskStatus::Completed;
+    task.progress = 100.0;
+    task.speed = None;
+    task.eta = None;
+    task.path = std::path::Path::new(&file_path)
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+    task.status_detail = Some("Exported".to_string());
+    task.total_size = file_size.clone();
+    task.file_path = Some(file_path);
+    task.retry_count = None;
+    task.file_size = file_size; // Use same string for display
+    task.completed_at = Some(
+        std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
-            .as_secs(),
-        pid: None,
-        status_detail: Some("Exported".to_string()),
-        eta_raw: None,
-        speed_raw: None,
-        total_size: file_size.clone(),
-        range: None,
-        format: None,
-        file_path: Some(file_path),
-        scheduled_time: None,
-        retry_count: None,
-        ytdlp_command: None,
-        file_size, // Use same string for display
-        completed_at: Some(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_millis() as u64,
-        ),
-        options: Default::default(),
-    };
+            .as_millis() as u64,
+    );
 
     state.add_task(task, &app);
     Ok(id)
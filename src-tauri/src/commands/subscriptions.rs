@@ -0,0 +1,111 @@
+use crate::download_queue::QueueState;
+use crate::subscriptions::{Subscription, SubscriptionState};
+use crate::ytdlp::{SupportedSites, YtDlpOptions};
+use std::sync::Arc;
+use tauri::State;
+
+#[tauri::command]
+pub async fn list_subscriptions(
+    subs: State<'_, Arc<SubscriptionState>>,
+) -> Result<Vec<Subscription>, String> {
+    Ok(subs.list())
+}
+
+#[tauri::command]
+pub async fn add_subscription(
+    subs: State<'_, Arc<SubscriptionState>>,
+    queue: State<'_, Arc<QueueState>>,
+    url: String,
+    options: YtDlpOptions,
+    interval_hours: Option<u32>,
+    title: Option<String>,
+    sites: State<'_, Arc<SupportedSites>>,
+) -> Result<Subscription, String> {
+    if !sites.matches(&url) {
+        log::warn!("[Subscriptions] Rejected unsupported URL: {}", url);
+        return Err(
+            "URL not supported. This site is not in the yt-dlp supported sites list.".to_string(),
+        );
+    }
+
+    let url = crate::ytdlp::sanitize_url(&url);
+    if subs.list().iter().any(|s| s.url == url) {
+        return Err("Already subscribed to this URL".to_string());
+    }
+
+    let sub = Subscription {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: title
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| url.clone()),
+        url,
+        options,
+        interval_hours: interval_hours.unwrap_or(24).max(1),
+        enabled: true,
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        last_sync_at: None,
+        last_error: None,
+        last_new_count: None,
+    };
+
+    log::info!("User added subscription: {} (ID: {})", sub.url, sub.id);
+    subs.add(sub.clone());
+
+    // First sync runs right away instead of waiting for the next processor tick
+    queue.notify.notify_one();
+    Ok(sub)
+}
+
+#[tauri::command]
+pub async fn update_subscription(
+    subs: State<'_, Arc<SubscriptionState>>,
+    id: String,
+    title: Option<String>,
+    options: Option<YtDlpOptions>,
+    interval_hours: Option<u32>,
+    enabled: Option<bool>,
+) -> Result<Subscription, String> {
+    subs.update(&id, |s| {
+        if let Some(t) = title.filter(|t| !t.trim().is_empty()) {
+            s.title = t;
+        }
+        if let Some(o) = options {
+            s.options = o;
+        }
+        if let Some(h) = interval_hours {
+            s.interval_hours = h.max(1);
+        }
+        if let Some(e) = enabled {
+            s.enabled = e;
+        }
+    })
+}
+
+/// Removes a subscription. Queued tasks it already created are left untouched;
+/// `delete_archive` also forgets which entries were downloaded.
+#[tauri::command]
+pub async fn remove_subscription(
+    subs: State<'_, Arc<SubscriptionState>>,
+    id: String,
+    delete_archive: Option<bool>,
+) -> Result<(), String> {
+    log::info!("User removed subscription: {}", id);
+    subs.remove(&id, delete_archive.unwrap_or(false))
+        .map(|_| ())
+        .ok_or_else(|| "Subscription not found".to_string())
+}
+
+/// Syncs a subscription immediately, returning the number of newly queued entries.
+#[tauri::command]
+pub async fn sync_subscription_now(
+    subs: State<'_, Arc<SubscriptionState>>,
+    queue: State<'_, Arc<QueueState>>,
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<usize, String> {
+    log::info!("User triggered subscription sync: {}", id);
+    crate::subscriptions::run_sync(app, queue.inner().clone(), subs.inner().clone(), id).await
+}
@@ -0,0 +1,59 @@
+use crate::subtitles::{parse_timestamp, SubtitleFormat, SubtitleTrack};
+
+fn parse_range(value: Option<&str>, label: &str) -> Result<Option<f64>, String> {
+    match value.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(v) => parse_timestamp(v)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid {} time: {}", label, v)),
+        None => Ok(None),
+    }
+}
+
+/// Parses an SRT, VTT or ASS file for editing.
+#[tauri::command]
+pub async fn read_subtitles(path: String) -> Result<SubtitleTrack, String> {
+    SubtitleTrack::load(&path)
+}
+
+/// Saves an edited track; the extension of `path` picks the format.
+#[tauri::command]
+pub async fn write_subtitles(path: String, track: SubtitleTrack) -> Result<(), String> {
+    log::info!("[Subtitles] Saving {} cues to {}", track.cues.len(), path);
+    track.save(&path)
+}
+
+/// Converts `input_path` to the format of `output_path`. `range_start` /
+/// `range_end` (same notation as the download range) keep only that window,
+/// rebased to zero, and `shift_secs` moves the result. Returns the number of
+/// cues written.
+#[tauri::command]
+pub async fn convert_subtitles(
+    input_path: String,
+    output_path: String,
+    shift_secs: Option<f64>,
+    range_start: Option<String>,
+    range_end: Option<String>,
+) -> Result<usize, String> {
+    if SubtitleFormat::from_path(&output_path).is_none() {
+        return Err(format!("Unsupported subtitle format: {}", output_path));
+    }
+    let start = parse_range(range_start.as_deref(), "start")?;
+    let end = parse_range(range_end.as_deref(), "end")?;
+
+    let mut track = SubtitleTrack::load(&input_path)?;
+    if start.is_some() || end.is_some() {
+        track.clip(start.unwrap_or(0.0), end);
+    }
+    if let Some(shift) = shift_secs.filter(|s| *s != 0.0) {
+        track.shift(shift);
+    }
+    track.save(&output_path)?;
+
+    log::info!(
+        "[Subtitles] Converted {} -> {} ({} cues)",
+        input_path,
+        output_path,
+        track.cues.len()
+    );
+    Ok(track.cues.len())
+}
@@ -0,0 +1,106 @@
+use crate::download_queue::QueueState;
+use crate::follow_up::{collect_media_files, FollowUpAction};
+use crate::watch_folders::{WatchFolder, WatchFolderState};
+use std::sync::Arc;
+use tauri::State;
+
+#[tauri::command]
+pub async fn list_watch_folders(
+    watch: State<'_, Arc<WatchFolderState>>,
+) -> Result<Vec<WatchFolder>, String> {
+    Ok(watch.list())
+}
+
+/// Starts watching `path`. Files already in it are skipped unless
+/// `process_existing` is set.
+#[tauri::command]
+pub async fn add_watch_folder(
+    watch: State<'_, Arc<WatchFolderState>>,
+    queue: State<'_, Arc<QueueState>>,
+    path: String,
+    action: FollowUpAction,
+    output_dir: Option<String>,
+    recursive: Option<bool>,
+    process_existing: Option<bool>,
+) -> Result<WatchFolder, String> {
+    if !std::path::Path::new(&path).is_dir() {
+        return Err(format!("Not a folder: {}", path));
+    }
+    if watch.list().iter().any(|w| w.path == path) {
+        return Err("This folder is already watched".to_string());
+    }
+
+    let recursive = recursive.unwrap_or(false);
+    let processed = if process_existing.unwrap_or(false) {
+        Default::default()
+    } else {
+        collect_media_files(std::slice::from_ref(&path), recursive)?
+            .into_iter()
+            .collect()
+    };
+
+    let folder = WatchFolder {
+        id: uuid::Uuid::new_v4().to_string(),
+        path,
+        action,
+        output_dir: output_dir.filter(|d| !d.trim().is_empty()),
+        recursive,
+        enabled: true,
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        last_error: None,
+        last_queued_count: None,
+        processed,
+    };
+
+    log::info!(
+        "User added watch folder: {} ({}, ID: {})",
+        folder.path,
+        folder.action.label(),
+        folder.id
+    );
+    watch.add(folder.clone());
+    queue.notify.notify_one();
+    Ok(folder)
+}
+
+#[tauri::command]
+pub async fn update_watch_folder(
+    watch: State<'_, Arc<WatchFolderState>>,
+    id: String,
+    action: Option<FollowUpAction>,
+    output_dir: Option<String>,
+    recursive: Option<bool>,
+    enabled: Option<bool>,
+) -> Result<WatchFolder, String> {
+    watch.update(&id, |w| {
+        if let Some(a) = action {
+            w.action = a;
+        }
+        // An empty string moves results back next to the source files
+        if let Some(d) = output_dir {
+            w.output_dir = Some(d).filter(|d| !d.trim().is_empty());
+        }
+        if let Some(r) = recursive {
+            w.recursive = r;
+        }
+        if let Some(e) = enabled {
+            w.enabled = e;
+        }
+    })
+}
+
+/// Stops watching a folder. Jobs it already queued are left untouched.
+#[tauri::command]
+pub async fn remove_watch_folder(
+    watch: State<'_, Arc<WatchFolderState>>,
+    id: String,
+) -> Result<(), String> {
+    log::info!("User removed watch folder: {}", id);
+    watch
+        .remove(&id)
+        .map(|_| ())
+        .ok_or_else(|| "Watch folder not found".to_string())
+}
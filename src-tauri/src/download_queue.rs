@@ -1,7 +1,8 @@
+use crate::bandwidth::BandwidthLimit;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex,
@@ -9,7 +10,7 @@ use std::sync::{
 use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_notification::NotificationExt;
 
-use tokio::sync::Notify;
+use tokio::sync::{broadcast, Notify};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -25,6 +26,16 @@ pub enum TaskStatus {
     Stopped,
 }
 
+/// Declaration order is pick order: `High` tasks start before `Normal`, then `Low`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskPriority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DownloadTask {
@@ -52,17 +63,319 @@ pub struct DownloadTask {
     pub file_size: Option<String>,
     pub completed_at: Option<u64>,
     pub options: crate::ytdlp::YtDlpOptions,
+    // Playlist/channel expansion: children share the parent group ID
+    pub group_id: Option<String>,
+    pub playlist_index: Option<u32>,
+    // --limit-rate the running process was started with ("" = unlimited)
+    pub applied_speed_limit: Option<String>,
+    #[serde(default)]
+    pub priority: TaskPriority,
+    // Post-download chaining: follow-ups become child tasks that carry a `job`
+    #[serde(default)]
+    pub follow_ups: Vec<crate::follow_up::FollowUpAction>,
+    pub parent_id: Option<String>,
+    pub job: Option<crate::follow_up::FollowUpJob>,
+    pub uploader: Option<String>,
+    // Extractor video ID and extractor key, used for duplicate detection
+    pub video_id: Option<String>,
+    pub extractor: Option<String>,
+    // Files saved so far by a multi-clip task, reset when a run starts
+    #[serde(default)]
+    pub clip_files: Vec<String>,
+    // Poster/thumbnails/contact sheet generated on request
+    pub previews: Option<crate::previews::TaskPreviews>,
+    // Last loudness analysis or normalization of the downloaded file
+    pub loudness: Option<crate::loudness::LoudnessReport>,
+}
+
+impl DownloadTask {
+    /// Builds a fresh `Pending` task for the given URL and options.
+    pub fn new_pending(url: String, options: crate::ytdlp::YtDlpOptions) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            url,
+            title: "Queued...".to_string(),
+            status: TaskStatus::Pending,
+            progress: 0.0,
+            speed: Some("-".to_string()),
+            eta: Some("-".to_string()),
+            path: options.path.clone().unwrap_or_default(),
+            error_message: None,
+            added_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            pid: None,
+            status_detail: None,
+            eta_raw: None,
+            speed_raw: None,
+            total_size: None,
+            range: None,
+            format: options.format.clone(),
+            file_path: None,
+            // Frontend sends scheduledTime in milliseconds (JS Date.getTime()),
+            // but queue processor compares against seconds (.as_secs()), so convert ms -> s
+            scheduled_time: options.scheduled_time.map(|ms| ms / 1000),
+            retry_count: Some(0),
+            ytdlp_command: None,
+            file_size: None,
+            completed_at: None,
+            options,
+            group_id: None,
+            playlist_index: None,
+            applied_speed_limit: None,
+            priority: TaskPriority::Normal,
+            follow_ups: Vec::new(),
+            parent_id: None,
+            job: None,
+            uploader: None,
+            video_id: None,
+            extractor: None,
+            clip_files: Vec::new(),
+            previews: None,
+            loudness: None,
+        }
+    }
+}
+
+/// A playlist/channel whose entries were expanded into individual tasks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskGroup {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub added_at: u64,
+}
+
+/// Aggregated progress of all tasks belonging to a `TaskGroup`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupProgress {
+    pub group_id: String,
+    pub title: String,
+    pub url: String,
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub active: usize,
+    pub paused: usize,
+    pub pending: usize,
+    pub progress: f64,
+}
+
+/// Queue events mirrored to external listeners (SSE in `server.rs`).
+/// `name()` is the SSE event name; the data is this enum as JSON, tagged with
+/// the same name in `type`:
+/// - `queue_update`: `{"type", "tasks"}`, the full ordered task list
+/// - `task_progress`: `{"type", "taskId", "status", "progress", "speed", "eta", "totalSize"}`
+/// - `task_output`: `{"type", "taskId", "line", "level"}`
+///
+/// The webview's Tauri events differ: `queue_update` carries the bare task
+/// array, `task_output` has no `type`, and there is no `task_progress`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QueueEvent {
+    QueueUpdate {
+        tasks: Vec<DownloadTask>,
+    },
+    #[serde(rename_all = "camelCase")]
+    TaskProgress {
+        task_id: String,
+        status: TaskStatus,
+        progress: f64,
+        speed: Option<String>,
+        eta: Option<String>,
+        total_size: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    TaskOutput {
+        task_id: String,
+        line: String,
+        level: String,
+    },
+}
+
+impl QueueEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            QueueEvent::QueueUpdate { .. } => "queue_update",
+            QueueEvent::TaskProgress { .. } => "task_progress",
+            QueueEvent::TaskOutput { .. } => "task_output",
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 struct PersistedQueue {
+    /// Schema version; files written before versioning read as 0
+    #[serde(default)]
+    version: u32,
     tasks: HashMap<String, DownloadTask>,
     queue_order: Vec<String>,
+    #[serde(default)]
+    groups: HashMap<String, TaskGroup>,
+    #[serde(default)]
+    paused: bool,
+}
+
+/// Bump when `PersistedQueue` changes shape and add a step to `migrate_persisted`.
+const QUEUE_SCHEMA_VERSION: u32 = 1;
+
+/// Loosely-typed view of `queue.json`, decoded task by task so a single entry
+/// that no longer matches `DownloadTask` can't take the whole queue with it.
+#[derive(Deserialize)]
+struct RawPersistedQueue {
+    #[serde(default)]
+    version: u32,
+    tasks: HashMap<String, serde_json::Value>,
+    queue_order: Vec<String>,
+    #[serde(default)]
+    groups: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    paused: bool,
+}
+
+struct DecodedQueue {
+    data: PersistedQueue,
+    /// Raw tasks that failed to deserialize, keyed by ID
+    rejected: HashMap<String, serde_json::Value>,
+    file_version: u32,
+}
+
+/// Upgrades older layouts in place. v0 -> v1 only introduced the header;
+/// fields added since are covered by `#[serde(default)]`.
+fn migrate_persisted(raw: &mut RawPersistedQueue) {
+    if raw.version < 1 {
+        raw.version = 1;
+    }
+}
+
+fn decode_persisted(content: &str) -> Result<DecodedQueue, String> {
+    let mut raw: RawPersistedQueue =
+        serde_json::from_str(content).map_err(|e| format!("Invalid queue file: {}", e))?;
+    let file_version = raw.version;
+    migrate_persisted(&mut raw);
+
+    let mut tasks = HashMap::new();
+    let mut rejected = HashMap::new();
+    for (id, value) in raw.tasks {
+        match serde_json::from_value::<DownloadTask>(value.clone()) {
+            Ok(task) => {
+                tasks.insert(id, task);
+            }
+            Err(e) => {
+                log::warn!(
+                    "[Queue] Task {} could not be read ({}), quarantining",
+                    id,
+                    e
+                );
+                rejected.insert(id, value);
+            }
+        }
+    }
+
+    let groups = raw
+        .groups
+        .into_iter()
+        .filter_map(|(id, value)| {
+            serde_json::from_value::<TaskGroup>(value)
+                .map_err(|e| log::warn!("[Queue] Dropping unreadable group {}: {}", id, e))
+                .ok()
+                .map(|group| (id, group))
+        })
+        .collect();
+
+    let queue_order = raw
+        .queue_order
+        .into_iter()
+        .filter(|id| tasks.contains_key(id))
+        .collect();
+
+    Ok(DecodedQueue {
+        data: PersistedQueue {
+            version: QUEUE_SCHEMA_VERSION,
+            tasks,
+            queue_order,
+            groups,
+            paused: raw.paused,
+        },
+        rejected,
+        file_version,
+    })
+}
+
+/// `queue.json` + suffix, e.g. `queue.json.bak`.
+pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn read_queue_file(path: &Path) -> Result<Option<DecodedQueue>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    decode_persisted(&content).map(Some)
+}
+
+/// Writes to a temp file, fsyncs it and renames it over `path`, keeping the
+/// previous version as `<path>.bak`. A crash at any point leaves either the
+/// old or the new file intact.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = with_suffix(path, ".tmp");
+    {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    if path.exists() {
+        fs::rename(path, with_suffix(path, ".bak"))?;
+    }
+    fs::rename(&tmp, path)?;
+
+    // Persist the directory entries too, otherwise the renames may not survive power loss
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        if let Ok(dir) = fs::File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+/// Appends unreadable tasks to `<path>.quarantine.json` so they can be recovered by hand.
+fn quarantine_tasks(path: &Path, rejected: HashMap<String, serde_json::Value>) {
+    let quarantine_path = with_suffix(path, ".quarantine.json");
+    let mut existing: HashMap<String, serde_json::Value> = fs::read_to_string(&quarantine_path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    let count = rejected.len();
+    existing.extend(rejected);
+    match serde_json::to_vec_pretty(&existing) {
+        Ok(json) => match write_atomic(&quarantine_path, &json) {
+            Ok(()) => log::warn!(
+                "[Queue] Moved {} unreadable task(s) to {}",
+                count,
+                quarantine_path.display()
+            ),
+            Err(e) => log::error!("[Queue] Failed to write quarantine file: {}", e),
+        },
+        Err(e) => log::error!("[Queue] Failed to serialize quarantined tasks: {}", e),
+    }
 }
 
 pub struct QueueState {
     pub tasks: Arc<Mutex<HashMap<String, DownloadTask>>>,
     pub queue_order: Arc<Mutex<Vec<String>>>,
+    pub groups: Arc<Mutex<HashMap<String, TaskGroup>>>,
     pub abort_handles: Arc<Mutex<HashMap<String, tokio::task::AbortHandle>>>,
     pub notify: Arc<Notify>,
     pub persistence_path: Option<PathBuf>, // FIX: Store Path, NOT AppHandle
@@ -72,6 +385,9 @@ pub struct QueueState {
     pub active_keepawake: Arc<Mutex<Option<keepawake::KeepAwake>>>,
     pub previous_active_count: Arc<Mutex<usize>>,
     pub dirty: Arc<AtomicBool>,
+    /// Global pause: while set, the processor does not start new tasks
+    pub paused: Arc<AtomicBool>,
+    pub events: broadcast::Sender<QueueEvent>,
 }
 
 impl QueueState {
@@ -81,9 +397,23 @@ impl QueueState {
             .and_then(|app| app.path().app_data_dir().ok())
             .map(|dir| dir.join("queue.json"));
 
+        let state = Self::with_persistence_path(persistence_path);
+
+        // We can't refresh cache here because we don't store app handle,
+        // but normally refresh_settings_cache is called by processor which has handle
+        if let Some(app) = &app_handle {
+            state.refresh_settings_cache(app);
+        }
+
+        state
+    }
+
+    /// Builds a queue backed by an explicit `queue.json` path (no Tauri runtime needed).
+    pub fn with_persistence_path(persistence_path: Option<PathBuf>) -> Self {
         let state = Self {
             tasks: Arc::new(Mutex::new(HashMap::new())),
             queue_order: Arc::new(Mutex::new(Vec::new())),
+            groups: Arc::new(Mutex::new(HashMap::new())),
             abort_handles: Arc::new(Mutex::new(HashMap::new())),
             notify: Arc::new(Notify::new()),
             persistence_path,
@@ -93,16 +423,11 @@ impl QueueState {
             active_keepawake: Arc::new(Mutex::new(None)),
             previous_active_count: Arc::new(Mutex::new(0)),
             dirty: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            events: broadcast::channel(256).0,
         };
         // Attempt to load existing queue
         state.load();
-
-        // We can't refresh cache here because we don't store app handle,
-        // but normally refresh_settings_cache is called by processor which has handle
-        if let Some(app) = &app_handle {
-            state.refresh_settings_cache(app);
-        }
-
         state
     }
 
@@ -121,18 +446,21 @@ impl QueueState {
         if let Some(path) = self.get_persistence_path() {
             let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
             let order = self.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+            let groups = self.groups.lock().unwrap_or_else(|e| e.into_inner());
 
             let data = PersistedQueue {
+                version: QUEUE_SCHEMA_VERSION,
                 tasks: tasks.clone(),
                 queue_order: order.clone(),
+                groups: groups.clone(),
+                paused: self.paused.load(Ordering::SeqCst),
             };
 
+            // Written while the task lock is held, so concurrent saves never share the temp file
             if let Ok(json) = serde_json::to_string_pretty(&data) {
-                if let Some(parent) = path.parent() {
-                    let _ = fs::create_dir_all(parent);
-                }
-                if let Ok(_) = fs::write(path, json) {
-                    self.dirty.store(false, Ordering::SeqCst);
+                match write_atomic(&path, json.as_bytes()) {
+                    Ok(()) => self.dirty.store(false, Ordering::SeqCst),
+                    Err(e) => log::error!("[Queue] Failed to save queue: {}", e),
                 }
             }
         }
@@ -172,40 +500,107 @@ impl QueueState {
     }
 
     pub fn load(&self) {
-        if let Some(path) = self.get_persistence_path() {
-            if path.exists() {
-                if let Ok(content) = fs::read_to_string(path) {
-                    if let Ok(data) = serde_json::from_str::<PersistedQueue>(&content) {
-                        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
-                        let mut order = self.queue_order.lock().unwrap_or_else(|e| e.into_inner());
-                        *tasks = data.tasks;
-                        *order = data.queue_order;
-                        log::info!(
-                            "Queue loaded successfully. Tasks: {}, Ordered: {}",
-                            tasks.len(),
-                            order.len()
-                        );
+        let Some(path) = self.get_persistence_path() else {
+            return;
+        };
 
-                        // Reset 'Downloading' tasks to 'Pending' or 'Stopped' on startup/reload
-                        for task in tasks.values_mut() {
-                            if matches!(
-                                task.status,
-                                TaskStatus::Downloading
-                                    | TaskStatus::FetchingInfo
-                                    | TaskStatus::Processing
-                                    | TaskStatus::Queued
-                            ) {
-                                task.status = TaskStatus::Stopped;
-                                task.status_detail = Some("Interrupted by Restart".to_string());
-                            }
-                        }
+        let (source, decoded) = match read_queue_file(&path) {
+            Ok(Some(decoded)) => (path.clone(), decoded),
+            primary => {
+                if let Err(e) = &primary {
+                    log::error!("[Queue] {} is unreadable: {}", path.display(), e);
+                    // Keep the damaged file for inspection; the next save would overwrite it
+                    let stamp = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs();
+                    let _ = fs::rename(&path, with_suffix(&path, &format!(".corrupt-{}", stamp)));
+                }
+                let backup = with_suffix(&path, ".bak");
+                match read_queue_file(&backup) {
+                    Ok(Some(decoded)) => {
+                        log::warn!("[Queue] Restored queue from {}", backup.display());
+                        (backup, decoded)
+                    }
+                    Ok(None) => return,
+                    Err(e) => {
+                        log::error!(
+                            "[Queue] Backup is unreadable too ({}), starting with an empty queue",
+                            e
+                        );
+                        return;
                     }
                 }
             }
+        };
+
+        if decoded.file_version > QUEUE_SCHEMA_VERSION {
+            // Written by a newer build: keep a pristine copy before we save in our older format
+            let keep = with_suffix(&path, &format!(".v{}", decoded.file_version));
+            log::warn!(
+                "[Queue] Queue file has schema v{} (this build understands v{}), copy kept at {}",
+                decoded.file_version,
+                QUEUE_SCHEMA_VERSION,
+                keep.display()
+            );
+            let _ = fs::copy(&source, &keep);
+        }
+        if !decoded.rejected.is_empty() {
+            quarantine_tasks(&path, decoded.rejected);
+        }
+
+        let data = decoded.data;
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let mut order = self.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+        *tasks = data.tasks;
+        *order = data.queue_order;
+        *self.groups.lock().unwrap_or_else(|e| e.into_inner()) = data.groups;
+        self.paused.store(data.paused, Ordering::SeqCst);
+        log::info!(
+            "Queue loaded successfully. Tasks: {}, Ordered: {}",
+            tasks.len(),
+            order.len()
+        );
+
+        // Reset 'Downloading' tasks to 'Pending' or 'Stopped' on startup/reload
+        for task in tasks.values_mut() {
+            if matches!(
+                task.status,
+                TaskStatus::Downloading
+                    | TaskStatus::FetchingInfo
+                    | TaskStatus::Processing
+                    | TaskStatus::Queued
+            ) {
+                task.status = TaskStatus::Stopped;
+                task.status_detail = Some("Interrupted by Restart".to_string());
+            }
         }
     }
 
     pub fn add_task(&self, task: DownloadTask, app: &AppHandle) {
+        self.enqueue(task);
+        // Instant UI Update
+        emit_queue_update(app, self);
+    }
+
+    /// Bulk `enqueue` with a single save, for imports.
+    pub fn enqueue_all(&self, new_tasks: Vec<DownloadTask>) {
+        {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            let mut order = self.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+            for task in new_tasks {
+                if !tasks.contains_key(&task.id) {
+                    order.push(task.id.clone());
+                }
+                tasks.insert(task.id.clone(), task);
+            }
+        }
+        self.save_now();
+        self.notify.notify_one();
+    }
+
+    /// Inserts (or replaces) a task and persists immediately, without emitting UI events.
+    pub fn enqueue(&self, task: DownloadTask) {
         {
             let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
             let mut order = self.queue_order.lock().unwrap_or_else(|e| e.into_inner());
@@ -216,8 +611,6 @@ impl QueueState {
             tasks.insert(task.id.clone(), task);
         }
         self.save_now(); // Critical event: additive
-                         // Instant UI Update
-        emit_queue_update(app, self);
         self.notify.notify_one();
     }
 
@@ -375,6 +768,9 @@ impl QueueState {
                     ordered_tasks.push(t.clone());
                 }
             }
+            let _ = self.events.send(QueueEvent::QueueUpdate {
+                tasks: ordered_tasks.clone(),
+            });
             let _ = app.emit("queue_update", ordered_tasks);
         }
         res
@@ -387,7 +783,11 @@ impl QueueState {
                 if let Some(pid) = task.pid {
                     match crate::commands::process::resume_process(pid) {
                         Ok(_) => {
-                            task.status = TaskStatus::Downloading;
+                            task.status = if task.job.is_some() {
+                                TaskStatus::Processing
+                            } else {
+                                TaskStatus::Downloading
+                            };
                             task.status_detail = Some("Resumed".to_string());
                             Ok(())
                         }
@@ -417,11 +817,571 @@ impl QueueState {
                     ordered_tasks.push(t.clone());
                 }
             }
+            let _ = self.events.send(QueueEvent::QueueUpdate {
+                tasks: ordered_tasks.clone(),
+            });
             let _ = app.emit("queue_update", ordered_tasks);
         }
         res
     }
 
+    /// Puts a stopped or failed task back in line under the same ID, so yt-dlp
+    /// continues from the `.part` files it left behind.
+    pub fn retry_task(&self, id: &str, app: &AppHandle) -> Result<(), String> {
+        {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            let task = tasks.get_mut(id).ok_or("Task not found")?;
+            if !matches!(task.status, TaskStatus::Stopped | TaskStatus::Error) {
+                return Err("Only stopped or failed tasks can be retried".to_string());
+            }
+            log::info!("[Queue] Retrying task {} in place", id);
+            // The PID belongs to a process that is gone (possibly reused by now)
+            task.pid = None;
+            task.status = TaskStatus::Pending;
+            task.status_detail = Some("Queued for retry".to_string());
+            task.error_message = None;
+            task.speed = None;
+            task.eta = None;
+        }
+        self.save_now();
+        emit_queue_update(app, self);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Moves a task to `index` in the queue order (clamped to the end).
+    pub fn move_task(&self, id: &str, index: usize, app: &AppHandle) -> Result<(), String> {
+        {
+            let mut order = self.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+            let pos = order
+                .iter()
+                .position(|x| x == id)
+                .ok_or_else(|| "Task not found".to_string())?;
+            let task_id = order.remove(pos);
+            let target = index.min(order.len());
+            order.insert(target, task_id);
+        }
+        self.save_now();
+        emit_queue_update(app, self);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    pub fn move_task_to_top(&self, id: &str, app: &AppHandle) -> Result<(), String> {
+        self.move_task(id, 0, app)
+    }
+
+    pub fn move_task_to_bottom(&self, id: &str, app: &AppHandle) -> Result<(), String> {
+        self.move_task(id, usize::MAX, app)
+    }
+
+    pub fn set_task_priority(
+        &self,
+        id: &str,
+        priority: TaskPriority,
+        app: &AppHandle,
+    ) -> Result<(), String> {
+        {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            let task = tasks
+                .get_mut(id)
+                .ok_or_else(|| "Task not found".to_string())?;
+            task.priority = priority;
+        }
+        self.save_now();
+        emit_queue_update(app, self);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Pauses the whole queue: no new tasks are started and running ones are suspended.
+    pub fn pause_all(&self, app: &AppHandle) -> usize {
+        self.paused.store(true, Ordering::SeqCst);
+
+        let active: Vec<String> = {
+            let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            tasks
+                .values()
+                .filter(|t| {
+                    t.pid.is_some()
+                        && matches!(
+                            t.status,
+                            TaskStatus::Downloading
+                                | TaskStatus::Processing
+                                | TaskStatus::FetchingInfo
+                        )
+                })
+                .map(|t| t.id.clone())
+                .collect()
+        };
+
+        let mut affected = 0;
+        for id in active {
+            if self.pause_task(&id, app).is_ok() {
+                // Tag so resume_all only wakes what the global pause suspended
+                self.update_task(&id, |t| {
+                    t.status_detail = Some("Paused (Queue)".to_string());
+                });
+                affected += 1;
+            }
+        }
+
+        log::info!("[Queue] Global pause ({} tasks suspended)", affected);
+        self.save_now();
+        emit_queue_update(app, self);
+        affected
+    }
+
+    pub fn resume_all(&self, app: &AppHandle) -> usize {
+        self.paused.store(false, Ordering::SeqCst);
+
+        let queue_paused: Vec<String> = {
+            let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            tasks
+                .values()
+                .filter(|t| {
+                    t.status == TaskStatus::Paused
+                        && t.status_detail.as_deref() == Some("Paused (Queue)")
+                })
+                .map(|t| t.id.clone())
+                .collect()
+        };
+
+        let affected = queue_paused
+            .iter()
+            .filter(|id| self.resume_task(id, app).is_ok())
+            .count();
+
+        log::info!("[Queue] Global resume ({} tasks resumed)", affected);
+        self.save_now();
+        emit_queue_update(app, self);
+        self.notify.notify_one();
+        affected
+    }
+
+    /// Brings running tasks in line with the bandwidth schedule. yt-dlp can't change
+    /// `--limit-rate` mid-download, so tasks are stopped and re-queued (they continue
+    /// from the `.part` file); "0" windows park them until the window ends.
+    pub fn apply_bandwidth_schedule(&self, settings: &crate::ytdlp::AppSettings, app: &AppHandle) {
+        let minute = crate::bandwidth::current_minute();
+        let mut to_park = Vec::new();
+        let mut to_restart = Vec::new();
+        let mut to_wake = Vec::new();
+        {
+            let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            for t in tasks.values() {
+                let limit = crate::bandwidth::effective_limit_at(settings, &t.options, minute);
+                match t.status {
+                    TaskStatus::Downloading if t.pid.is_some() => {
+                        if limit == BandwidthLimit::Paused {
+                            to_park.push(t.id.clone());
+                        } else if t
+                            .applied_speed_limit
+                            .as_ref()
+                            .is_some_and(|applied| *applied != limit.as_applied())
+                        {
+                            to_restart.push(t.id.clone());
+                        }
+                    }
+                    TaskStatus::Paused
+                        if t.status_detail.as_deref() == Some("Paused (Schedule)")
+                            && limit != BandwidthLimit::Paused =>
+                    {
+                        to_wake.push(t.id.clone());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if to_park.is_empty() && to_restart.is_empty() && to_wake.is_empty() {
+            return;
+        }
+
+        for id in &to_park {
+            log::info!("[Bandwidth] Pausing task {} for scheduled window", id);
+            self.stop_process(id, TaskStatus::Paused, "Paused (Schedule)");
+        }
+        for id in &to_restart {
+            log::info!("[Bandwidth] Restarting task {} with new speed limit", id);
+            self.stop_process(id, TaskStatus::Pending, "Restarting with new speed limit");
+        }
+        for id in &to_wake {
+            log::info!("[Bandwidth] Scheduled pause over, re-queueing task {}", id);
+            self.update_task(id, |t| {
+                t.status = TaskStatus::Pending;
+                t.status_detail = Some("Resuming from disk...".to_string());
+            });
+        }
+
+        self.save_now();
+        emit_queue_update(app, self);
+        self.notify.notify_one();
+    }
+
+    /// Kills a task's process tree and leaves it in `status` with its partial file on disk.
+    fn stop_process(&self, id: &str, status: TaskStatus, detail: &str) {
+        // Abort first so the monitor doesn't record the kill as a failed download
+        if let Some(handle) = self
+            .abort_handles
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id)
+        {
+            handle.abort();
+        }
+
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(task) = tasks.get_mut(id) {
+            if let Some(pid) = task.pid.take() {
+                let _ = crate::commands::process::kill_process_tree(pid);
+            }
+            task.status = status;
+            task.status_detail = Some(detail.to_string());
+            task.speed = Some("-".to_string());
+            task.eta = None;
+        }
+    }
+
+    /// Queues the follow-up actions of a completed task as child tasks placed right
+    /// after it. Returns the number of children created (0 if already spawned).
+    pub fn spawn_follow_ups(&self, parent_id: &str, file_path: &str, app: &AppHandle) -> usize {
+        let created = self.queue_follow_ups(parent_id, file_path);
+        if created > 0 {
+            emit_queue_update(app, self);
+            self.notify.notify_one();
+        }
+        created
+    }
+
+    /// Window-less half of `spawn_follow_ups`, also used by the CLI.
+    pub fn queue_follow_ups(&self, parent_id: &str, file_path: &str) -> usize {
+        let created = {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            let mut order = self.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+
+            let parent = match tasks.get(parent_id) {
+                Some(p) if p.status == TaskStatus::Completed && !p.follow_ups.is_empty() => {
+                    p.clone()
+                }
+                _ => return 0,
+            };
+            if tasks
+                .values()
+                .any(|t| t.parent_id.as_deref() == Some(parent_id))
+            {
+                return 0;
+            }
+
+            let mut insert_at = order
+                .iter()
+                .position(|id| id == parent_id)
+                .map(|p| p + 1)
+                .unwrap_or(order.len());
+            let mut created = 0;
+            for action in &parent.follow_ups {
+                let mut child =
+                    DownloadTask::new_pending(parent.url.clone(), parent.options.clone());
+                child.title = format!("{}: {}", action.label(), parent.title);
+                child.path = parent.path.clone();
+                child.format = None;
+                child.scheduled_time = None;
+                child.priority = parent.priority;
+                child.parent_id = Some(parent.id.clone());
+                child.status_detail = Some("Waiting to start...".to_string());
+                child.job = Some(crate::follow_up::FollowUpJob {
+                    action: action.clone(),
+                    input_path: file_path.to_string(),
+                    output_dir: None,
+                });
+
+                order.insert(insert_at, child.id.clone());
+                insert_at += 1;
+                tasks.insert(child.id.clone(), child);
+                created += 1;
+            }
+            created
+        };
+
+        log::info!(
+            "[Queue] Task {} completed, queued {} follow-up(s)",
+            parent_id,
+            created
+        );
+        self.save_now();
+        created
+    }
+
+    /// Drops tasks from the live queue without touching their processes or files
+    /// (used once they've been archived elsewhere). Returns how many were removed.
+    pub fn detach_tasks(&self, ids: &[String]) -> usize {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let mut order = self.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+        let before = tasks.len();
+        for id in ids {
+            tasks.remove(id);
+        }
+        order.retain(|id| tasks.contains_key(id));
+
+        let mut groups = self.groups.lock().unwrap_or_else(|e| e.into_inner());
+        groups.retain(|gid, _| {
+            tasks
+                .values()
+                .any(|t| t.group_id.as_deref() == Some(gid.as_str()))
+        });
+        before - tasks.len()
+    }
+
+    /// Registers a playlist group and enqueues all of its child tasks in one step.
+    pub fn add_group(&self, group: TaskGroup, children: Vec<DownloadTask>, app: &AppHandle) {
+        {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            let mut order = self.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+            let mut groups = self.groups.lock().unwrap_or_else(|e| e.into_inner());
+
+            for mut task in children {
+                task.group_id = Some(group.id.clone());
+                if !tasks.contains_key(&task.id) {
+                    order.push(task.id.clone());
+                }
+                tasks.insert(task.id.clone(), task);
+            }
+            groups.insert(group.id.clone(), group);
+        }
+        self.save_now(); // Critical event: additive
+        emit_queue_update(app, self);
+        self.notify.notify_one();
+    }
+
+    fn group_member_ids(&self, group_id: &str) -> Vec<String> {
+        let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let order = self.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+        order
+            .iter()
+            .filter(|id| {
+                tasks
+                    .get(*id)
+                    .map_or(false, |t| t.group_id.as_deref() == Some(group_id))
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn group_progress(&self, group_id: &str) -> Option<GroupProgress> {
+        let group = self
+            .groups
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(group_id)
+            .cloned()?;
+
+        let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let members: Vec<&DownloadTask> = tasks
+            .values()
+            .filter(|t| t.group_id.as_deref() == Some(group_id))
+            .collect();
+
+        let count =
+            |pred: fn(&TaskStatus) -> bool| members.iter().filter(|t| pred(&t.status)).count();
+        let total = members.len();
+        let progress = if total > 0 {
+            members
+                .iter()
+                .map(|t| {
+                    if t.status == TaskStatus::Completed {
+                        100.0
+                    } else {
+                        t.progress
+                    }
+                })
+                .sum::<f64>()
+                / total as f64
+        } else {
+            0.0
+        };
+
+        Some(GroupProgress {
+            group_id: group.id,
+            title: group.title,
+            url: group.url,
+            total,
+            completed: count(|s| *s == TaskStatus::Completed),
+            failed: count(|s| matches!(s, TaskStatus::Error | TaskStatus::Stopped)),
+            active: count(|s| {
+                matches!(
+                    s,
+                    TaskStatus::Downloading
+                        | TaskStatus::FetchingInfo
+                        | TaskStatus::Processing
+                        | TaskStatus::Queued
+                )
+            }),
+            paused: count(|s| *s == TaskStatus::Paused),
+            pending: count(|s| *s == TaskStatus::Pending),
+            progress,
+        })
+    }
+
+    pub fn all_group_progress(&self) -> Vec<GroupProgress> {
+        let ids: Vec<String> = self
+            .groups
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .cloned()
+            .collect();
+        ids.iter()
+            .filter_map(|id| self.group_progress(id))
+            .collect()
+    }
+
+    /// Pauses every member of a group: running downloads are suspended,
+    /// pending ones are parked as `Paused` so the processor skips them.
+    pub fn pause_group(&self, group_id: &str, app: &AppHandle) -> Result<usize, String> {
+        if !self
+            .groups
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(group_id)
+        {
+            return Err("Group not found".to_string());
+        }
+
+        let mut affected = 0;
+        for id in self.group_member_ids(group_id) {
+            let (has_pid, status) = {
+                let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+                match tasks.get(&id) {
+                    Some(t) => (t.pid.is_some(), t.status.clone()),
+                    None => continue,
+                }
+            };
+
+            match status {
+                TaskStatus::Downloading | TaskStatus::Processing | TaskStatus::FetchingInfo
+                    if has_pid =>
+                {
+                    if self.pause_task(&id, app).is_ok() {
+                        affected += 1;
+                    }
+                }
+                TaskStatus::Pending => {
+                    let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+                    if let Some(t) = tasks.get_mut(&id) {
+                        t.status = TaskStatus::Paused;
+                        t.status_detail = Some("Paused (Playlist)".to_string());
+                        affected += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        log::info!("[Queue] Paused group {} ({} tasks)", group_id, affected);
+        self.save();
+        emit_queue_update(app, self);
+        Ok(affected)
+    }
+
+    pub fn resume_group(&self, group_id: &str, app: &AppHandle) -> Result<usize, String> {
+        if !self
+            .groups
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(group_id)
+        {
+            return Err("Group not found".to_string());
+        }
+
+        let mut affected = 0;
+        for id in self.group_member_ids(group_id) {
+            let is_paused = self
+                .tasks
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(&id)
+                .map_or(false, |t| t.status == TaskStatus::Paused);
+
+            if is_paused && self.resume_task(&id, app).is_ok() {
+                affected += 1;
+            }
+        }
+
+        log::info!("[Queue] Resumed group {} ({} tasks)", group_id, affected);
+        emit_queue_update(app, self);
+        self.notify.notify_one();
+        Ok(affected)
+    }
+
+    /// Removes a group and all of its tasks (killing any running processes).
+    pub fn remove_group(&self, group_id: &str, app: &AppHandle) -> Vec<DownloadTask> {
+        // Same steps as `remove_task`, but under one lock with a single save and emit
+        let mut removed: Vec<DownloadTask> = Vec::new();
+        {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            let mut order = self.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+
+            let member_ids: Vec<String> = order
+                .iter()
+                .filter(|id| {
+                    tasks
+                        .get(*id)
+                        .is_some_and(|t| t.group_id.as_deref() == Some(group_id))
+                })
+                .cloned()
+                .collect();
+
+            for id in &member_ids {
+                let Some(task) = tasks.remove(id) else {
+                    continue;
+                };
+                if let Some(pid) = task.pid {
+                    if matches!(
+                        task.status,
+                        TaskStatus::Downloading
+                            | TaskStatus::Paused
+                            | TaskStatus::Processing
+                            | TaskStatus::FetchingInfo
+                            | TaskStatus::Queued
+                    ) {
+                        log::info!(
+                            "[Queue] Killing process tree for task {} (PID: {})",
+                            id,
+                            pid
+                        );
+                        let _ = crate::commands::process::kill_process_tree(pid);
+                    }
+                }
+                removed.push(task);
+            }
+            order.retain(|id| !member_ids.contains(id));
+        }
+
+        {
+            let mut handles = self.abort_handles.lock().unwrap_or_else(|e| e.into_inner());
+            for task in &removed {
+                if let Some(handle) = handles.remove(&task.id) {
+                    handle.abort();
+                }
+            }
+        }
+
+        self.groups
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(group_id);
+        self.save_now();
+        emit_queue_update(app, self);
+        self.notify.notify_one();
+
+        log::info!(
+            "[Queue] Removed group {} ({} tasks)",
+            group_id,
+            removed.len()
+        );
+        removed
+    }
+
     pub fn cleanup_old_tasks(&self, retention_days: u32, max_items: i32, app: &AppHandle) {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -483,6 +1443,16 @@ impl QueueState {
                     }
                 }
             }
+
+            // 3. Drop playlist groups whose tasks were all cleaned up
+            if removed {
+                let mut groups = self.groups.lock().unwrap_or_else(|e| e.into_inner());
+                groups.retain(|gid, _| {
+                    tasks
+                        .values()
+                        .any(|t| t.group_id.as_deref() == Some(gid.as_str()))
+                });
+            }
         }
 
         if removed {
@@ -532,9 +1502,20 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
             (if conc == 0 { 3 } else { conc }, days, max)
         };
 
-        // 3. Perform Auto-Cleanup
+        // 3. Move older completed tasks into the history database, then clean up the rest
+        crate::history_store::archive_completed(&app, &state, retention_days, max_items);
         state.cleanup_old_tasks(retention_days, max_items, &app);
 
+        // Kick off any subscription syncs whose interval has elapsed
+        crate::subscriptions::spawn_due_syncs(&app, &state);
+
+        // Queue jobs for new files in the watch folders
+        crate::watch_folders::scan_due(&app, &state);
+
+        // Re-apply the bandwidth schedule to running and schedule-paused tasks
+        let app_settings = crate::ytdlp::load_settings(&app);
+        state.apply_bandwidth_schedule(&app_settings, &app);
+
         // EXTRA: DEBOUNCED SAVER LOGIC
         // If dirty, we save every loop iteration (2s)
         if state.dirty.load(Ordering::SeqCst) {
@@ -559,6 +1540,16 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
                     )
                 })
                 .count();
+            // Local jobs have their own slots so a long transcode never starves downloads
+            let active_jobs = tasks
+                .values()
+                .filter(|t| {
+                    t.job.is_some()
+                        && matches!(t.status, TaskStatus::Processing | TaskStatus::Queued)
+                })
+                .count();
+            let active_downloads = active_count - active_jobs;
+            let job_limit = app_settings.concurrent_transcodes.max(1) as usize;
 
             let now = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -639,25 +1630,44 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
             }
 
             // Pick next task: Either 'Pending' or 'Scheduled' (if time hit)
-            let next = if active_count < limit {
+            let queue_paused = state.paused.load(Ordering::SeqCst);
+            let minute = crate::bandwidth::current_minute();
+            let next = if !queue_paused {
                 order
                     .iter()
-                    .find(|id| {
-                        if let Some(t) = tasks.get(*id) {
-                            // Case 1: Regular Pending task
-                            let is_pending =
-                                t.status == TaskStatus::Pending && t.scheduled_time.is_none();
-                            // Case 2: Scheduled task that hit its time
-                            let is_scheduled_hit = t.status == TaskStatus::Pending
-                                && t.scheduled_time.is_some()
-                                && t.scheduled_time.unwrap() <= now;
-
-                            is_pending || is_scheduled_hit
+                    .filter_map(|id| tasks.get(id))
+                    .filter(|t| {
+                        let has_slot = if t.job.is_some() {
+                            active_jobs < job_limit
                         } else {
-                            false
+                            active_downloads < limit
+                        };
+                        if !has_slot {
+                            return false;
+                        }
+                        // Hold back while a "0" bandwidth window is active
+                        if t.job.is_none()
+                            && crate::bandwidth::effective_limit_at(
+                                &app_settings,
+                                &t.options,
+                                minute,
+                            ) == BandwidthLimit::Paused
+                        {
+                            return false;
                         }
+                        // Case 1: Regular Pending task
+                        let is_pending =
+                            t.status == TaskStatus::Pending && t.scheduled_time.is_none();
+                        // Case 2: Scheduled task that hit its time
+                        let is_scheduled_hit = t.status == TaskStatus::Pending
+                            && t.scheduled_time.is_some()
+                            && t.scheduled_time.unwrap() <= now;
+
+                        is_pending || is_scheduled_hit
                     })
-                    .cloned()
+                    // Priority first; min_by_key keeps the earliest task among equals
+                    .min_by_key(|t| t.priority)
+                    .map(|t| t.id.clone())
             } else {
                 None
             };
@@ -684,6 +1694,22 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
             };
 
             if let Some(task) = task_data {
+                // Follow-up jobs run on a local file instead of going through yt-dlp
+                if let Some(job) = task.job.clone() {
+                    let join_handle = tokio::spawn(crate::follow_up::run_job(
+                        app_handle,
+                        state_cloned,
+                        task.id.clone(),
+                        job,
+                    ));
+                    state
+                        .abort_handles
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(id, join_handle.abort_handle());
+                    continue;
+                }
+
                 let join_handle = tokio::spawn(async move {
                     // Start download logic here
                     // ... (existing logs) ...
@@ -707,6 +1733,14 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
                         settings.download_path = task.path.clone();
                     }
 
+                    // Recorded so the processor can tell when the schedule moved on
+                    let applied = crate::bandwidth::effective_limit(&settings, &task.options);
+                    // Clips are re-downloaded from the first on every run
+                    state_cloned.update_task(&task.id, |t| {
+                        t.applied_speed_limit = Some(applied.as_applied());
+                        t.clip_files.clear();
+                    });
+
                     let gpu_type = crate::ytdlp::load_gpu_type(&app_handle);
 
                     let task_id = task.id.clone();
@@ -723,11 +1757,13 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
                                     ..
                                 } => {
                                     // Emit live terminal output for developer mode
-                                    let _ = app_monitor.emit("task_output", serde_json::json!({
-                                        "taskId": task_id,
-                                        "line": format!("[Process] Started with PID: {}", pid),
-                                        "level": "info"
-                                    }));
+                                    emit_task_output(
+                                        &app_monitor,
+                                        &state_monitor,
+                                        &task_id,
+                                        format!("[Process] Started with PID: {}", pid),
+                                        "info",
+                                    );
                                     state_monitor.update_task(&task_id, |t| {
                                         t.pid = Some(pid);
                                         t.status_detail = Some("Downloading...".to_string());
@@ -738,15 +1774,20 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
                                     title,
                                     ytdlp_command,
                                     file_path,
+                                    uploader,
+                                    video_id,
+                                    extractor,
                                     ..
                                 } => {
                                     // Emit live terminal output for developer mode
                                     if let Some(ref cmd) = ytdlp_command {
-                                        let _ = app_monitor.emit("task_output", serde_json::json!({
-                                            "taskId": task_id,
-                                            "line": format!("$ {}", cmd),
-                                            "level": "info"
-                                        }));
+                                        emit_task_output(
+                                            &app_monitor,
+                                            &state_monitor,
+                                            &task_id,
+                                            format!("$ {}", cmd),
+                                            "info",
+                                        );
                                     }
                                     state_monitor.update_task(&task_id, |t| {
                                         if let Some(ti) = title {
@@ -758,6 +1799,13 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
                                         if let Some(fp) = file_path {
                                             t.file_path = Some(fp);
                                         }
+                                        if uploader.is_some() {
+                                            t.uploader = uploader;
+                                        }
+                                        if video_id.is_some() {
+                                            t.video_id = video_id;
+                                            t.extractor = extractor;
+                                        }
                                         // Also status update if started
                                         t.status = TaskStatus::Downloading;
                                     });
@@ -792,8 +1840,36 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
                                         t.total_size = Some(total_size);
                                         t.status = TaskStatus::Downloading;
                                     });
+                                    emit_task_progress(&state_monitor, &task_id);
                                     emit_queue_update(&app_monitor, &state_monitor);
                                 }
+                                crate::commands::download::DownloadEvent::ClipProgress {
+                                    index,
+                                    count,
+                                    name,
+                                    file_path,
+                                    ..
+                                } => {
+                                    if let Some(ref path) = file_path {
+                                        emit_task_output(
+                                            &app_monitor,
+                                            &state_monitor,
+                                            &task_id,
+                                            format!("Clip {}/{} saved: {}", index, count, path),
+                                            "info",
+                                        );
+                                    }
+                                    state_monitor.update_task(&task_id, |t| {
+                                        if matches!(t.status, TaskStatus::Paused | TaskStatus::Stopped) {
+                                            return;
+                                        }
+                                        t.status_detail =
+                                            Some(format!("Clip {}/{}: {}", index, count, name));
+                                        if let Some(path) = file_path {
+                                            t.clip_files.push(path);
+                                        }
+                                    });
+                                }
                                 crate::commands::download::DownloadEvent::Completed {
                                     file_path,
                                     ..
@@ -828,6 +1904,11 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
                                         }
                                     });
                                     emit_queue_update(&app_monitor, &state_monitor);
+                                    state_monitor.spawn_follow_ups(
+                                        &task_id,
+                                        &file_path,
+                                        &app_monitor,
+                                    );
 
                                     // Notification
                                     let focus = app_monitor
@@ -854,11 +1935,13 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
                                     message, ..
                                 } => {
                                     // Emit live terminal output for developer mode
-                                    let _ = app_monitor.emit("task_output", serde_json::json!({
-                                        "taskId": task_id,
-                                        "line": format!("[ERROR] {}", message),
-                                        "level": "error"
-                                    }));
+                                    emit_task_output(
+                                        &app_monitor,
+                                        &state_monitor,
+                                        &task_id,
+                                        format!("[ERROR] {}", message),
+                                        "error",
+                                    );
                                     // AUTO-RETRY LOGIC
                                     let mut should_retry = false;
                                     let mut delay = 0;
@@ -937,11 +2020,13 @@ pub async fn start_queue_processor(app: AppHandle, state: Arc<QueueState>) {
                                     ..
                                 } => {
                                     // Emit live terminal output for developer mode
-                                    let _ = app_monitor.emit("task_output", serde_json::json!({
-                                        "taskId": task_id,
-                                        "line": message,
-                                        "level": level
-                                    }));
+                                    emit_task_output(
+                                        &app_monitor,
+                                        &state_monitor,
+                                        &task_id,
+                                        message.clone(),
+                                        &level,
+                                    );
                                     if level == "warning" || level == "error" {
                                         state_monitor.update_task(&task_id, |t| {
                                             if matches!(t.status, TaskStatus::Paused | TaskStatus::Stopped) {
@@ -1069,5 +2154,159 @@ pub fn emit_queue_update(app: &AppHandle, state: &QueueState) {
             ordered_tasks.push(t.clone());
         }
     }
+    let _ = state.events.send(QueueEvent::QueueUpdate {
+        tasks: ordered_tasks.clone(),
+    });
     let _ = app.emit("queue_update", ordered_tasks);
 }
+
+/// Sends a line of live terminal output to the webview and external listeners.
+pub fn emit_task_output(
+    app: &AppHandle,
+    state: &QueueState,
+    task_id: &str,
+    line: String,
+    level: &str,
+) {
+    let _ = app.emit(
+        "task_output",
+        serde_json::json!({
+            "taskId": task_id,
+            "line": line,
+            "level": level
+        }),
+    );
+    let _ = state.events.send(QueueEvent::TaskOutput {
+        task_id: task_id.to_string(),
+        line,
+        level: level.to_string(),
+    });
+}
+
+/// Publishes a lightweight progress snapshot for a single task.
+pub(crate) fn emit_task_progress(state: &QueueState, task_id: &str) {
+    let event = {
+        let tasks = state.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks.get(task_id).map(|t| QueueEvent::TaskProgress {
+            task_id: t.id.clone(),
+            status: t.status.clone(),
+            progress: t.progress,
+            speed: t.speed.clone(),
+            eta: t.eta.clone(),
+            total_size: t.total_size.clone(),
+        })
+    };
+    if let Some(event) = event {
+        let _ = state.events.send(event);
+    }
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_events_serialize_as_documented() {
+        let task = DownloadTask::new_pending(
+            "https://example.com/a".to_string(),
+            crate::ytdlp::YtDlpOptions::default(),
+        );
+        let update = QueueEvent::QueueUpdate {
+            tasks: vec![task.clone()],
+        };
+        let json = serde_json::to_value(&update).unwrap();
+        assert_eq!(json["type"], update.name());
+        assert_eq!(json["tasks"][0]["id"], task.id.as_str());
+
+        let progress = QueueEvent::TaskProgress {
+            task_id: task.id.clone(),
+            status: TaskStatus::Downloading,
+            progress: 42.5,
+            speed: Some("1.2MiB/s".to_string()),
+            eta: None,
+            total_size: Some("10MiB".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_value(&progress).unwrap(),
+            serde_json::json!({
+                "type": "task_progress",
+                "taskId": task.id,
+                "status": "downloading",
+                "progress": 42.5,
+                "speed": "1.2MiB/s",
+                "eta": null,
+                "totalSize": "10MiB",
+            })
+        );
+        assert_eq!(progress.name(), "task_progress");
+
+        let output = QueueEvent::TaskOutput {
+            task_id: task.id.clone(),
+            line: "[download] 42.5%".to_string(),
+            level: "info".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&output).unwrap(),
+            serde_json::json!({
+                "type": "task_output",
+                "taskId": task.id,
+                "line": "[download] 42.5%",
+                "level": "info",
+            })
+        );
+        assert_eq!(output.name(), "task_output");
+    }
+
+    fn temp_queue_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sceneclip-queue-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn legacy_file_loads_and_bad_tasks_are_quarantined() {
+        let good = DownloadTask::new_pending(
+            "https://example.com/a".to_string(),
+            crate::ytdlp::YtDlpOptions::default(),
+        );
+        let good_id = good.id.clone();
+        // No version header, one task from an incompatible schema
+        let content = serde_json::json!({
+            "tasks": {
+                (good_id.clone()): good,
+                "broken": { "id": "broken", "status": "teleporting" }
+            },
+            "queue_order": [good_id.clone(), "broken"]
+        })
+        .to_string();
+
+        let decoded = decode_persisted(&content).unwrap();
+        assert_eq!(decoded.file_version, 0);
+        assert_eq!(decoded.data.version, QUEUE_SCHEMA_VERSION);
+        assert_eq!(decoded.data.queue_order, vec![good_id.clone()]);
+        assert!(decoded.data.tasks.contains_key(&good_id));
+        assert!(decoded.rejected.contains_key("broken"));
+
+        assert!(decode_persisted("{\"tasks\": {").is_err());
+    }
+
+    #[test]
+    fn atomic_write_keeps_previous_copy() {
+        let path = temp_queue_path("queue.json");
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(
+            fs::read_to_string(with_suffix(&path, ".bak")).unwrap(),
+            "first"
+        );
+        assert!(!with_suffix(&path, ".tmp").exists());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
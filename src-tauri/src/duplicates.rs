@@ -0,0 +1,504 @@
+use crate::download_queue::{DownloadTask, QueueState, TaskStatus};
+use crate::history_store::HistoryStore;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+/// What to do when a URL being enqueued was already downloaded or queued.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicatePolicy {
+    #[default]
+    Skip,
+    Redownload,
+    /// Re-download, appending " (n)" to the filename so the existing file is kept.
+    RedownloadRenamed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicateSource {
+    Queue,
+    History,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchedOn {
+    Url,
+    VideoId,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateMatch {
+    pub task_id: String,
+    pub title: String,
+    pub url: String,
+    pub status: TaskStatus,
+    pub file_path: Option<String>,
+    pub source: DuplicateSource,
+    pub matched_on: MatchedOn,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum EnqueueStatus {
+    Added,
+    Skipped,
+    Redownloaded,
+    Renamed,
+}
+
+/// Outcome of an enqueue request. `task_id` is `None` when the add was skipped.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnqueueResult {
+    pub status: EnqueueStatus,
+    pub task_id: Option<String>,
+    pub duplicate: Option<DuplicateMatch>,
+    pub filename: Option<String>,
+}
+
+/// The keys a new task is compared on.
+pub struct DuplicateKey {
+    pub canonical_url: String,
+    pub video_id: Option<String>,
+    pub extractor: Option<String>,
+}
+
+impl DuplicateKey {
+    pub fn for_task(task: &DownloadTask) -> Self {
+        Self {
+            canonical_url: crate::ytdlp::sanitize_url(task.url.trim()),
+            video_id: task.video_id.clone().filter(|v| !v.is_empty()),
+            extractor: task.extractor.clone(),
+        }
+    }
+
+    /// Finished, running and still-waiting (pending, paused, scheduled) tasks
+    /// count; stopped and failed tasks and follow-up jobs never do.
+    pub fn matches(&self, task: &DownloadTask) -> Option<MatchedOn> {
+        let counts = !matches!(task.status, TaskStatus::Stopped | TaskStatus::Error);
+        if task.job.is_some() || !counts {
+            return None;
+        }
+        if crate::ytdlp::sanitize_url(task.url.trim()) == self.canonical_url {
+            return Some(MatchedOn::Url);
+        }
+        let same_id = self.video_id.is_some() && task.video_id == self.video_id;
+        // IDs are only unique per extractor; an unknown extractor on either side still counts
+        let same_extractor = match (&self.extractor, &task.extractor) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            _ => true,
+        };
+        (same_id && same_extractor).then_some(MatchedOn::VideoId)
+    }
+}
+
+fn to_match(task: DownloadTask, source: DuplicateSource, matched_on: MatchedOn) -> DuplicateMatch {
+    DuplicateMatch {
+        task_id: task.id,
+        title: task.title,
+        url: task.url,
+        status: task.status,
+        file_path: task.file_path,
+        source,
+        matched_on,
+    }
+}
+
+/// Looks in the active queue first, then in the archived history.
+pub fn find_duplicate(
+    queue: &QueueState,
+    history: Option<&HistoryStore>,
+    key: &DuplicateKey,
+) -> Option<DuplicateMatch> {
+    let queued = {
+        let tasks = queue.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks
+            .values()
+            .filter_map(|t| key.matches(t).map(|on| (t.clone(), on)))
+            .max_by_key(|(t, _)| t.added_at)
+    };
+    if let Some((task, on)) = queued {
+        return Some(to_match(task, DuplicateSource::Queue, on));
+    }
+
+    match history?.find_duplicate(
+        &key.canonical_url,
+        key.video_id.as_deref(),
+        key.extractor.as_deref(),
+    ) {
+        Ok(Some(task)) => {
+            let on = key.matches(&task).unwrap_or(MatchedOn::Url);
+            Some(to_match(task, DuplicateSource::History, on))
+        }
+        Ok(None) => None,
+        Err(e) => {
+            log::warn!("[Duplicates] History lookup failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Asks yt-dlp for the extractor key and video ID of `url` without downloading.
+pub async fn probe_video_id(ytdlp_path: String, url: String) -> Option<(String, String)> {
+    #[allow(unused_mut)]
+    let mut std_cmd = std::process::Command::new(&ytdlp_path);
+    std_cmd.args([
+        "--no-playlist",
+        "--no-warnings",
+        "--skip-download",
+        "--print",
+        "%(extractor_key)s %(id)s",
+        &url,
+    ]);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        std_cmd.creation_flags(0x08000000);
+    }
+    let mut cmd = tokio::process::Command::from(std_cmd);
+    cmd.kill_on_drop(true);
+
+    let output = tokio::time::timeout(std::time::Duration::from_secs(30), cmd.output())
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        log::warn!("[Duplicates] Could not resolve the video ID of {}", url);
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (extractor, id) = stdout.lines().next()?.trim().split_once(' ')?;
+    (!id.is_empty() && id != "NA").then(|| (extractor.to_string(), id.to_string()))
+}
+
+/// Fills in the task's extractor and video ID through `probe` so a copy under
+/// another URL is caught before it downloads. Skipped when the ID is already
+/// known, when the URL alone is a duplicate, and for `Redownload`.
+async fn resolve_video_id<F, Fut>(
+    queue: &QueueState,
+    history: Option<&HistoryStore>,
+    task: &mut DownloadTask,
+    policy: DuplicatePolicy,
+    probe: F,
+) where
+    F: FnOnce(String) -> Fut,
+    Fut: std::future::Future<Output = Option<(String, String)>>,
+{
+    if policy == DuplicatePolicy::Redownload || task.video_id.is_some() {
+        return;
+    }
+    if find_duplicate(queue, history, &DuplicateKey::for_task(task)).is_some() {
+        return;
+    }
+    if let Some((extractor, id)) = probe(task.url.clone()).await {
+        task.extractor = Some(extractor);
+        task.video_id = Some(id);
+    }
+}
+
+/// File names already on disk in `output_dir` or recorded in the history.
+fn existing_file_names(history: Option<&HistoryStore>, output_dir: Option<&Path>) -> Vec<String> {
+    let mut names: Vec<String> = output_dir
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    match history.map(|h| h.known_keys()) {
+        Some(Ok((_, paths))) => names.extend(paths.iter().filter_map(|p| {
+            Path::new(p)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+        })),
+        Some(Err(e)) => log::warn!("[Duplicates] History lookup failed: {}", e),
+        None => {}
+    }
+    names
+}
+
+/// "<base> (n)" with the first `n >= 2` not used as a filename in the queue
+/// and not part of any name in `existing`. The filename template may wrap
+/// the name (e.g. "<name> [1080p].mp4"), so any file containing it counts.
+fn renamed_filename(base: &str, queue: &QueueState, existing: &[String]) -> String {
+    let tasks = queue.tasks.lock().unwrap_or_else(|e| e.into_inner());
+    let taken = |name: &str| {
+        // Same characters `sanitize_filename` replaces in the written name
+        let on_disk = name.replace(|c: char| "\\/:*?\"<>|%".contains(c), "_");
+        tasks
+            .values()
+            .any(|t| t.options.custom_filename.as_deref() == Some(name))
+            || existing.iter().any(|file| file.contains(&on_disk))
+    };
+    (2..)
+        .map(|n| format!("{} ({})", base, n))
+        .find(|name| !taken(name))
+        .unwrap_or_default()
+}
+
+/// Resolves the video ID, then applies `policy` to `task`, renaming it if
+/// needed. The returned result has a `task_id` exactly when the task should
+/// be added.
+async fn check_task<F, Fut>(
+    queue: &QueueState,
+    history: Option<&HistoryStore>,
+    output_dir: Option<&Path>,
+    task: &mut DownloadTask,
+    policy: DuplicatePolicy,
+    probe: F,
+) -> EnqueueResult
+where
+    F: FnOnce(String) -> Fut,
+    Fut: std::future::Future<Output = Option<(String, String)>>,
+{
+    resolve_video_id(queue, history, task, policy, probe).await;
+    let key = DuplicateKey::for_task(task);
+    let Some(duplicate) = find_duplicate(queue, history, &key) else {
+        return EnqueueResult {
+            status: EnqueueStatus::Added,
+            task_id: Some(task.id.clone()),
+            duplicate: None,
+            filename: None,
+        };
+    };
+
+    log::info!(
+        "[Duplicates] {} matches {:?} task {} ({:?}), policy {:?}",
+        task.url,
+        duplicate.source,
+        duplicate.task_id,
+        duplicate.matched_on,
+        policy
+    );
+
+    let (status, filename) = match policy {
+        DuplicatePolicy::Skip => {
+            return EnqueueResult {
+                status: EnqueueStatus::Skipped,
+                task_id: None,
+                duplicate: Some(duplicate),
+                filename: None,
+            };
+        }
+        DuplicatePolicy::Redownload => (EnqueueStatus::Redownloaded, None),
+        DuplicatePolicy::RedownloadRenamed => {
+            let base = task
+                .options
+                .custom_filename
+                .clone()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| duplicate.title.clone());
+            let existing = existing_file_names(history, output_dir);
+            let name = renamed_filename(&base, queue, &existing);
+            task.options.custom_filename = Some(name.clone());
+            (EnqueueStatus::Renamed, Some(name))
+        }
+    };
+
+    EnqueueResult {
+        status,
+        task_id: Some(task.id.clone()),
+        duplicate: Some(duplicate),
+        filename,
+    }
+}
+
+/// Adds `task` unless it duplicates something already queued or downloaded,
+/// in which case `policy` decides whether (and under which name) it is added anyway.
+/// Tasks without a video ID are probed with yt-dlp first, so a copy under
+/// another URL (e.g. `youtu.be` vs. `youtube.com`) is found too.
+pub async fn enqueue_with_policy(
+    app: &AppHandle,
+    queue: &QueueState,
+    mut task: DownloadTask,
+    policy: DuplicatePolicy,
+) -> EnqueueResult {
+    let history = app
+        .try_state::<Arc<HistoryStore>>()
+        .map(|s| s.inner().clone());
+    let settings = crate::ytdlp::load_settings(app);
+    let ytdlp_path = crate::ytdlp::resolve_ytdlp_path(app, &settings.binary_path_yt_dlp);
+
+    let output_dir = if task.path.is_empty() {
+        settings.download_path.clone()
+    } else {
+        task.path.clone()
+    };
+    let output_dir = (!output_dir.is_empty()).then(|| PathBuf::from(output_dir));
+
+    let result = check_task(
+        queue,
+        history.as_deref(),
+        output_dir.as_deref(),
+        &mut task,
+        policy,
+        |url| probe_video_id(ytdlp_path, url),
+    )
+    .await;
+    if result.task_id.is_some() {
+        queue.add_task(task, app);
+    }
+    result
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(url: &str, video_id: Option<&str>, extractor: Option<&str>) -> DownloadTask {
+        let mut t = DownloadTask::new_pending(url.to_string(), Default::default());
+        t.status = TaskStatus::Completed;
+        t.video_id = video_id.map(String::from);
+        t.extractor = extractor.map(String::from);
+        t
+    }
+
+    #[test]
+    fn matches_on_canonical_url() {
+        let key = DuplicateKey::for_task(&task(
+            "https://www.youtube.com/watch?v=abc&si=tracking",
+            None,
+            None,
+        ));
+        let existing = task("https://www.youtube.com/watch?v=abc", None, None);
+        assert_eq!(key.matches(&existing), Some(MatchedOn::Url));
+
+        let mut failed = existing.clone();
+        failed.status = TaskStatus::Error;
+        assert_eq!(key.matches(&failed), None);
+    }
+
+    #[test]
+    fn waiting_tasks_count_but_stopped_and_failed_do_not() {
+        let key = DuplicateKey::for_task(&task("https://vimeo.com/1", None, None));
+        let mut existing = task("https://vimeo.com/1", None, None);
+        for status in [TaskStatus::Stopped, TaskStatus::Error] {
+            existing.status = status.clone();
+            assert_eq!(key.matches(&existing), None, "{:?}", status);
+        }
+        for status in [
+            TaskStatus::Pending,
+            TaskStatus::Paused,
+            TaskStatus::Queued,
+            TaskStatus::Downloading,
+        ] {
+            existing.status = status.clone();
+            assert_eq!(key.matches(&existing), Some(MatchedOn::Url), "{:?}", status);
+        }
+
+        existing.options.scheduled_time = Some(u64::MAX);
+        existing.status = TaskStatus::Pending;
+        assert_eq!(key.matches(&existing), Some(MatchedOn::Url));
+    }
+
+    #[test]
+    fn matches_on_video_id_within_extractor() {
+        let key =
+            DuplicateKey::for_task(&task("https://youtu.be/abc", Some("abc"), Some("Youtube")));
+        let same = task(
+            "https://www.youtube.com/watch?v=abc",
+            Some("abc"),
+            Some("youtube"),
+        );
+        let other_site = task("https://vimeo.com/abc", Some("abc"), Some("Vimeo"));
+        let unknown = task("https://example.com/v/abc", Some("abc"), None);
+
+        assert_eq!(key.matches(&same), Some(MatchedOn::VideoId));
+        assert_eq!(key.matches(&other_site), None);
+        assert_eq!(key.matches(&unknown), Some(MatchedOn::VideoId));
+    }
+
+    #[tokio::test]
+    async fn enqueue_probes_the_video_id_of_new_urls() {
+        let queue = QueueState::with_persistence_path(None);
+        queue.enqueue(task(
+            "https://www.youtube.com/watch?v=abc",
+            Some("abc"),
+            Some("Youtube"),
+        ));
+        let probe = |_: String| async { Some(("Youtube".to_string(), "abc".to_string())) };
+
+        let mut short =
+            DownloadTask::new_pending("https://youtu.be/abc".to_string(), Default::default());
+        let result = check_task(&queue, None, None, &mut short, DuplicatePolicy::Skip, probe).await;
+        assert_eq!(result.status, EnqueueStatus::Skipped);
+        assert_eq!(result.task_id, None);
+        assert_eq!(result.duplicate.unwrap().matched_on, MatchedOn::VideoId);
+        assert_eq!(short.video_id.as_deref(), Some("abc"));
+
+        // Redownload adds anyway, so there is nothing to probe for
+        let mut again =
+            DownloadTask::new_pending("https://youtu.be/abc".to_string(), Default::default());
+        let result = check_task(
+            &queue,
+            None,
+            None,
+            &mut again,
+            DuplicatePolicy::Redownload,
+            |_| async { unreachable!("probed for Redownload") },
+        )
+        .await;
+        assert_eq!(result.status, EnqueueStatus::Added);
+        assert_eq!(result.task_id.as_deref(), Some(again.id.as_str()));
+
+        // An unresolvable URL is only compared on its address
+        let mut other =
+            DownloadTask::new_pending("https://vimeo.com/1".to_string(), Default::default());
+        let result = check_task(
+            &queue,
+            None,
+            None,
+            &mut other,
+            DuplicatePolicy::Skip,
+            |_| async { None },
+        )
+        .await;
+        assert_eq!(result.status, EnqueueStatus::Added);
+    }
+
+    #[tokio::test]
+    async fn renamed_copies_skip_names_used_on_disk_or_in_the_queue() {
+        let dir = std::env::temp_dir().join(format!("sceneclip-dupes-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Clip (2) [1080p].mp4"), b"").unwrap();
+
+        let queue = QueueState::with_persistence_path(None);
+        let mut original = task("https://vimeo.com/1", None, None);
+        original.title = "Clip".to_string();
+        queue.enqueue(original);
+        let options = crate::ytdlp::YtDlpOptions {
+            custom_filename: Some("Clip (3)".to_string()),
+            ..Default::default()
+        };
+        queue.enqueue(DownloadTask::new_pending(
+            "https://vimeo.com/2".to_string(),
+            options,
+        ));
+
+        let mut copy =
+            DownloadTask::new_pending("https://vimeo.com/1".to_string(), Default::default());
+        let result = check_task(
+            &queue,
+            None,
+            Some(&dir),
+            &mut copy,
+            DuplicatePolicy::RedownloadRenamed,
+            |_| async { None },
+        )
+        .await;
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(result.status, EnqueueStatus::Renamed);
+        assert_eq!(result.filename.as_deref(), Some("Clip (4)"));
+        assert_eq!(copy.options.custom_filename.as_deref(), Some("Clip (4)"));
+    }
+}
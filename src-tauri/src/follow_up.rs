@@ -0,0 +1,654 @@
+use crate::commands::ffmpeg::{AnimatedExportOptions, CompressionOptions, FFmpegEvent};
+use crate::download_queue::{
+    emit_queue_update, emit_task_output, DownloadTask, QueueState, TaskStatus,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+use tauri::AppHandle;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Work to run on a download's output file once it reaches `Completed`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum FollowUpAction {
+    Compress {
+        options: CompressionOptions,
+    },
+    SplitChapters,
+    ExtractAudio {
+        /// Output container/extension, e.g. "mp3", "m4a", "opus". Defaults to mp3.
+        format: Option<String>,
+        bitrate: Option<String>,
+    },
+    ExportAnimated {
+        options: AnimatedExportOptions,
+    },
+    /// Shell command; `{file}` stands for the output path, which is passed
+    /// in `SCENECLIP_FILE` rather than spliced into the command line.
+    RunCommand {
+        command: String,
+    },
+}
+
+impl FollowUpAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FollowUpAction::Compress { .. } => "Compress",
+            FollowUpAction::SplitChapters => "Split chapters",
+            FollowUpAction::ExtractAudio { .. } => "Extract audio",
+            FollowUpAction::ExportAnimated { .. } => "Animated export",
+            FollowUpAction::RunCommand { .. } => "Run command",
+        }
+    }
+}
+
+/// A follow-up queued as its own task, bound to the parent's output file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowUpJob {
+    pub action: FollowUpAction,
+    pub input_path: String,
+    /// Directory for the results; next to the input when unset.
+    #[serde(default)]
+    pub output_dir: Option<String>,
+}
+
+fn is_audio_path(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    [".mp3", ".m4a", ".wav", ".opus", ".ogg", ".flac"]
+        .iter()
+        .any(|ext| lower.ends_with(ext))
+}
+
+fn is_image_path(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    [".gif", ".webp", ".png", ".jpg", ".jpeg"]
+        .iter()
+        .any(|ext| lower.ends_with(ext))
+}
+
+fn is_video_path(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    [
+        ".mp4", ".mkv", ".webm", ".mov", ".avi", ".m4v", ".flv", ".ts",
+    ]
+    .iter()
+    .any(|ext| lower.ends_with(ext))
+}
+
+/// Expands files and folders into the media files to process, in name order.
+/// Hidden files and the outputs of earlier compress runs are skipped.
+pub fn collect_media_files(inputs: &[String], recursive: bool) -> Result<Vec<String>, String> {
+    fn walk(dir: &Path, recursive: bool, out: &mut Vec<String>) -> Result<(), String> {
+        let mut entries: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .collect();
+        entries.sort();
+        for path in entries {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if name.starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                if recursive {
+                    walk(&path, recursive, out)?;
+                }
+                continue;
+            }
+            let path_str = path.to_string_lossy().to_string();
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            if stem.ends_with("_compressed") {
+                continue;
+            }
+            if is_video_path(&path_str) || is_audio_path(&path_str) || is_image_path(&path_str) {
+                out.push(path_str);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        if path.is_dir() {
+            walk(path, recursive, &mut files)?;
+        } else if path.is_file() {
+            files.push(input.clone());
+        } else {
+            return Err(format!("File not found: {}", input));
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|f| seen.insert(f.clone()));
+    Ok(files)
+}
+
+/// A standalone queue task running `action` on a local file.
+pub fn local_job_task(
+    action: FollowUpAction,
+    input_path: String,
+    output_dir: Option<String>,
+) -> DownloadTask {
+    let path = Path::new(&input_path);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| input_path.clone());
+
+    let mut task = DownloadTask::new_pending(input_path.clone(), Default::default());
+    task.title = format!("{}: {}", action.label(), name);
+    task.path = path
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+    task.format = None;
+    task.status_detail = Some("Waiting to start...".to_string());
+    task.job = Some(FollowUpJob {
+        action,
+        input_path,
+        output_dir,
+    });
+    task
+}
+
+/// `<dir>/<stem><suffix>.<ext>`, keeping the input's extension when `ext` is None.
+pub(crate) fn sibling_path(input: &str, suffix: &str, ext: Option<&str>) -> String {
+    let path = Path::new(input);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output");
+    let ext = ext
+        .map(|e| e.to_string())
+        .or_else(|| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_string())
+        })
+        .unwrap_or_else(|| "mp4".to_string());
+    path.with_file_name(format!("{}{}.{}", stem, suffix, ext))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// `sibling_path`, moved into the job's output directory when it has one.
+fn job_output_path(job: &FollowUpJob, suffix: &str, ext: Option<&str>) -> String {
+    let sibling = sibling_path(&job.input_path, suffix, ext);
+    match &job.output_dir {
+        Some(dir) => Path::new(dir)
+            .join(Path::new(&sibling).file_name().unwrap_or_default())
+            .to_string_lossy()
+            .to_string(),
+        None => sibling,
+    }
+}
+
+/// True when `candidate` is a file `job` writes, so a watch folder doesn't
+/// queue its own results. Split parts are recognised by the
+/// `<stem> - NN - ` prefix `split_parts` gives them.
+pub fn is_job_output(job: &FollowUpJob, candidate: &str) -> bool {
+    match &job.action {
+        FollowUpAction::Compress { .. } => candidate == job_output_path(job, "_compressed", None),
+        FollowUpAction::ExtractAudio { format, .. } => {
+            candidate == job_output_path(job, "", Some(format.as_deref().unwrap_or("mp3")))
+        }
+        FollowUpAction::ExportAnimated { options } => {
+            let ext = options.format.extension();
+            candidate == job_output_path(job, "", Some(ext))
+                || candidate == job_output_path(job, "_animated", Some(ext))
+        }
+        FollowUpAction::SplitChapters => {
+            let input = Path::new(&job.input_path);
+            let dir = job.output_dir.as_deref().map(Path::new).or(input.parent());
+            let stem = input.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            let candidate = Path::new(candidate);
+            let name = candidate.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let Some(rest) = name.strip_prefix(stem).and_then(|r| r.strip_prefix(" - ")) else {
+                return false;
+            };
+            candidate.parent() == dir
+                && rest.len() > 5
+                && rest.as_bytes()[..2].iter().all(u8::is_ascii_digit)
+                && rest[2..].starts_with(" - ")
+        }
+        FollowUpAction::RunCommand { .. } => false,
+    }
+}
+
+/// Replaces `{file}` with a quoted reference to `$SCENECLIP_FILE`. The shell
+/// substitutes the value after parsing the command, so a crafted file name
+/// (e.g. a media title containing `$(...)`, `&` or `%VAR%`) can't inject commands.
+fn expand_command(command: &str) -> String {
+    if cfg!(windows) {
+        // cmd expands %VAR% once; the quotes keep & | < > ^ in the value literal
+        command.replace("{file}", "\"%SCENECLIP_FILE%\"")
+    } else {
+        command.replace("{file}", "\"$SCENECLIP_FILE\"")
+    }
+}
+
+async fn run_user_command(
+    command: &str,
+    file: &str,
+    on_event: UnboundedSender<FFmpegEvent>,
+) -> Result<(), String> {
+    // A quote would end cmd's quoting early; Windows forbids it in file names anyway
+    if cfg!(windows) && file.contains('"') {
+        return Err(format!(
+            "Refusing to pass a path containing '\"' to cmd: {}",
+            file
+        ));
+    }
+    let expanded = expand_command(command);
+
+    #[cfg(windows)]
+    let mut std_command = {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        let mut c = std::process::Command::new("cmd");
+        c.arg("/C").arg(&expanded);
+        c.creation_flags(CREATE_NO_WINDOW);
+        c
+    };
+    #[cfg(not(windows))]
+    let mut std_command = {
+        let mut c = std::process::Command::new("sh");
+        c.arg("-c").arg(&expanded);
+        c
+    };
+    std_command.env("SCENECLIP_FILE", file);
+
+    let mut command = tokio::process::Command::from(std_command);
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start command: {}", e))?;
+    if let Some(pid) = child.id() {
+        let _ = on_event.send(FFmpegEvent::ProcessStarted { pid });
+    }
+
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(forward_lines(stdout, "info", on_event.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(forward_lines(stderr, "warning", on_event.clone()));
+    }
+
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    for reader in readers {
+        let _ = reader.await;
+    }
+
+    if status.success() {
+        let _ = on_event.send(FFmpegEvent::Completed {
+            output_path: file.to_string(),
+        });
+        Ok(())
+    } else {
+        Err(format!("Command exited with code {:?}", status.code()))
+    }
+}
+
+fn forward_lines(
+    stream: impl tokio::io::AsyncRead + Unpin + Send + 'static,
+    level: &'static str,
+    on_event: UnboundedSender<FFmpegEvent>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stream).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let _ = on_event.send(FFmpegEvent::Log {
+                message: line,
+                level: level.to_string(),
+            });
+        }
+    })
+}
+
+/// Runs one follow-up and returns the path it produced.
+async fn execute(
+    app: &AppHandle,
+    job: &FollowUpJob,
+    on_event: UnboundedSender<FFmpegEvent>,
+) -> Result<String, String> {
+    let settings = crate::ytdlp::load_settings(app);
+    let input = job.input_path.clone();
+    if !Path::new(&input).exists() {
+        return Err(format!("Input file no longer exists: {}", input));
+    }
+    if let Some(dir) = &job.output_dir {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create output folder {}: {}", dir, e))?;
+    }
+
+    match &job.action {
+        FollowUpAction::Compress { options } => {
+            let output = job_output_path(job, "_compressed", None);
+            crate::commands::ffmpeg::compress_media_internal(
+                app,
+                input.clone(),
+                output.clone(),
+                options.clone(),
+                is_audio_path(&input),
+                is_image_path(&input),
+                &settings,
+                on_event,
+            )
+            .await?;
+            Ok(output)
+        }
+        FollowUpAction::SplitChapters => {
+            let chapters = crate::commands::ffmpeg::probe_chapters(app, &input).await?;
+            if chapters.is_empty() {
+                return Err("File has no chapters to split".to_string());
+            }
+            crate::commands::ffmpeg::split_media_chapters_internal(
+                app,
+                input.clone(),
+                chapters,
+                job.output_dir.as_deref(),
+                &settings,
+                on_event,
+            )
+            .await?;
+            // Chapters land next to the source file unless redirected
+            Ok(job.output_dir.clone().unwrap_or_else(|| {
+                Path::new(&input)
+                    .parent()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or(input)
+            }))
+        }
+        FollowUpAction::ExtractAudio { format, bitrate } => {
+            let ext = format.as_deref().unwrap_or("mp3");
+            let output = job_output_path(job, "", Some(ext));
+            if output == input {
+                return Err("Input is already in the requested audio format".to_string());
+            }
+            let options = CompressionOptions {
+                resolution: "original".to_string(),
+                encoder: "cpu".to_string(),
+                crf: 0,
+                preset: "balanced".to_string(),
+                audio_bitrate: Some(bitrate.clone().unwrap_or_else(|| "192k".to_string())),
+                speed_preset: "medium".to_string(),
+                target_size_mb: None,
+            };
+            // Audio-only compression: ffmpeg picks the codec from the extension
+            crate::commands::ffmpeg::compress_media_internal(
+                app,
+                input,
+                output.clone(),
+                options,
+                true,
+                false,
+                &settings,
+                on_event,
+            )
+            .await?;
+            Ok(output)
+        }
+        FollowUpAction::ExportAnimated { options } => {
+            let ext = options.format.extension();
+            let mut output = job_output_path(job, "", Some(ext));
+            if output == input {
+                output = job_output_path(job, "_animated", Some(ext));
+            }
+            crate::commands::ffmpeg::export_animated_internal(
+                app,
+                input,
+                output.clone(),
+                options.clone(),
+                &settings,
+                on_event,
+            )
+            .await?;
+            Ok(output)
+        }
+        FollowUpAction::RunCommand { command } => {
+            run_user_command(command, &input, on_event).await?;
+            Ok(input)
+        }
+    }
+}
+
+/// Queue-side runner for a follow-up task: mirrors the download monitor so the
+/// job gets the same status, progress and terminal output handling.
+pub async fn run_job(app: AppHandle, state: Arc<QueueState>, task_id: String, job: FollowUpJob) {
+    log::info!(
+        "[FollowUp] Running {} for task {} on {}",
+        job.action.label(),
+        task_id,
+        job.input_path
+    );
+    state.update_task(&task_id, |t| {
+        t.status = TaskStatus::Processing;
+        t.status_detail = Some(format!("{}...", job.action.label()));
+        t.progress = 0.0;
+    });
+    emit_queue_update(&app, &state);
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+    let state_monitor = state.clone();
+    let app_monitor = app.clone();
+    let monitor_id = task_id.clone();
+    let monitor_handle = tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match event {
+                FFmpegEvent::ProcessStarted { pid } => {
+                    state_monitor.update_task(&monitor_id, |t| t.pid = Some(pid));
+                }
+                FFmpegEvent::Progress {
+                    percent,
+                    speed,
+                    eta,
+                } => {
+                    state_monitor.update_task(&monitor_id, |t| {
+                        if matches!(t.status, TaskStatus::Paused | TaskStatus::Stopped) {
+                            return;
+                        }
+                        t.progress = percent;
+                        t.speed = Some(speed);
+                        t.eta = Some(eta);
+                    });
+                    crate::download_queue::emit_task_progress(&state_monitor, &monitor_id);
+                    emit_queue_update(&app_monitor, &state_monitor);
+                }
+                FFmpegEvent::Log { message, level } => {
+                    emit_task_output(&app_monitor, &state_monitor, &monitor_id, message, &level);
+                }
+                FFmpegEvent::Completed { .. } => {}
+                FFmpegEvent::Error { message } => {
+                    emit_task_output(
+                        &app_monitor,
+                        &state_monitor,
+                        &monitor_id,
+                        format!("[ERROR] {}", message),
+                        "error",
+                    );
+                }
+            }
+        }
+    });
+
+    let result = execute(&app, &job, tx).await;
+    let _ = monitor_handle.await;
+
+    state.update_task(&task_id, |t| {
+        if matches!(t.status, TaskStatus::Paused | TaskStatus::Stopped) {
+            return;
+        }
+        t.pid = None;
+        t.speed = Some("-".to_string());
+        t.eta = None;
+        match &result {
+            Ok(output) => {
+                t.status = TaskStatus::Completed;
+                t.progress = 100.0;
+                t.status_detail = Some("Done".to_string());
+                t.file_path = Some(output.clone());
+                t.completed_at = Some(
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_millis() as u64,
+                );
+                if let Ok(metadata) = std::fs::metadata(output) {
+                    if metadata.is_file() {
+                        t.file_size = Some(format!(
+                            "{:.2} MiB",
+                            metadata.len() as f64 / 1024.0 / 1024.0
+                        ));
+                    }
+                }
+            }
+            Err(e) => {
+                t.status = TaskStatus::Error;
+                t.status_detail = Some(e.clone());
+                t.error_message = Some(e.clone());
+            }
+        }
+    });
+
+    match &result {
+        Ok(output) => log::info!("[FollowUp] Task {} finished: {}", task_id, output),
+        Err(e) => log::error!("[FollowUp] Task {} failed: {}", task_id, e),
+    }
+
+    state
+        .abort_handles
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&task_id);
+    state.save();
+    emit_queue_update(&app, &state);
+    state.notify.notify_one();
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sibling_path_keeps_or_replaces_extension() {
+        assert_eq!(
+            sibling_path("/videos/clip.mkv", "_compressed", None),
+            "/videos/clip_compressed.mkv"
+        );
+        assert_eq!(
+            sibling_path("/videos/clip.mkv", "", Some("mp3")),
+            "/videos/clip.mp3"
+        );
+    }
+
+    #[test]
+    fn folder_expands_to_media_files() {
+        let dir = std::env::temp_dir().join(format!("sceneclip-jobs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for name in [
+            "b.mkv",
+            "a.mp3",
+            "a_compressed.mp3",
+            "notes.txt",
+            ".hidden.mp4",
+            "nested/c.webm",
+        ] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let input = vec![dir.to_string_lossy().to_string()];
+
+        let names = |files: Vec<String>| -> Vec<String> {
+            files
+                .iter()
+                .map(|f| f.strip_prefix(&input[0]).unwrap()[1..].replace('\\', "/"))
+                .collect()
+        };
+        let flat = collect_media_files(&input, false).unwrap();
+        assert_eq!(names(flat), vec!["a.mp3", "b.mkv"]);
+        let deep = collect_media_files(&input, true).unwrap();
+        assert_eq!(names(deep), vec!["a.mp3", "b.mkv", "nested/c.webm"]);
+        assert!(
+            collect_media_files(&[dir.join("gone.mp4").to_string_lossy().to_string()], false)
+                .is_err()
+        );
+
+        let task = local_job_task(
+            FollowUpAction::SplitChapters,
+            dir.join("b.mkv").to_string_lossy().to_string(),
+            None,
+        );
+        assert_eq!(task.title, "Split chapters: b.mkv");
+        assert_eq!(task.path, input[0]);
+        assert!(task.parent_id.is_none() && task.job.is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn job_outputs_are_recognised() {
+        let job = |action: FollowUpAction| FollowUpJob {
+            action,
+            input_path: "/in/talk.mp4".to_string(),
+            output_dir: None,
+        };
+        let audio = job(FollowUpAction::ExtractAudio {
+            format: None,
+            bitrate: None,
+        });
+        assert!(is_job_output(&audio, "/in/talk.mp3"));
+        assert!(!is_job_output(&audio, "/in/other.mp3"));
+        let split = job(FollowUpAction::SplitChapters);
+        assert!(is_job_output(&split, "/in/talk - 03 - Outro.mp4"));
+        assert!(!is_job_output(&split, "/in/talk - live.mp4"));
+        assert!(!is_job_output(&split, "/elsewhere/talk - 03 - Outro.mp4"));
+        assert!(!is_job_output(
+            &job(FollowUpAction::RunCommand {
+                command: "true".to_string()
+            }),
+            "/in/talk.mp4"
+        ));
+    }
+
+    #[test]
+    fn action_round_trips_with_type_tag() {
+        let json = r#"{"type":"extractAudio","format":"m4a","bitrate":null}"#;
+        let action: FollowUpAction = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            action,
+            FollowUpAction::ExtractAudio { ref format, .. } if format.as_deref() == Some("m4a")
+        ));
+        assert!(serde_json::to_string(&FollowUpAction::SplitChapters)
+            .unwrap()
+            .contains(r#""type":"splitChapters""#));
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn command_placeholder_is_never_interpreted_by_the_shell() {
+        assert_eq!(expand_command("ls -l {file}"), r#"ls -l "$SCENECLIP_FILE""#);
+
+        let file = r#"/tmp/it's "here" $(echo pwned) `id` & %PATH%.mp4"#;
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        run_user_command("printf '%s\\n' {file}", file, tx)
+            .await
+            .unwrap();
+        let mut printed = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let FFmpegEvent::Log { message, .. } = event {
+                printed.push(message);
+            }
+        }
+        assert_eq!(printed, vec![file.to_string()]);
+    }
+}
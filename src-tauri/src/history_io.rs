@@ -0,0 +1,278 @@
+use crate::download_queue::{DownloadTask, TaskStatus};
+use crate::history_store::HistoryQuery;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    M3u,
+}
+
+/// Status and date filters for an export. Dates are Unix milliseconds and are
+/// compared against the completion time (or the time the task was added).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportFilter {
+    pub statuses: Vec<TaskStatus>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+impl ExportFilter {
+    pub fn matches(&self, task: &DownloadTask) -> bool {
+        let at = finished_at(task);
+        (self.statuses.is_empty() || self.statuses.contains(&task.status))
+            && self.from.map_or(true, |from| at >= from)
+            && self.to.map_or(true, |to| at < to)
+    }
+
+    pub fn as_history_query(&self) -> HistoryQuery {
+        HistoryQuery {
+            statuses: self.statuses.clone(),
+            from: self.from,
+            to: self.to,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportFile {
+    version: u32,
+    exported_at: u64,
+    tasks: Vec<serde_json::Value>,
+}
+
+/// Completion time in ms, falling back to `added_at` (stored in seconds).
+pub fn finished_at(task: &DownloadTask) -> u64 {
+    task.completed_at.unwrap_or(task.added_at * 1000)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn status_name(status: &TaskStatus) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+pub fn to_json(tasks: &[DownloadTask]) -> Result<String, String> {
+    let file = ExportFile {
+        version: EXPORT_VERSION,
+        exported_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64,
+        tasks: tasks
+            .iter()
+            .map(|t| serde_json::to_value(t).map_err(|e| e.to_string()))
+            .collect::<Result<_, _>>()?,
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| e.to_string())
+}
+
+pub fn to_csv(tasks: &[DownloadTask]) -> String {
+    let mut out =
+        String::from("id,title,url,status,uploader,filePath,fileSize,format,addedAt,completedAt\n");
+    for t in tasks {
+        let row = [
+            t.id.clone(),
+            t.title.clone(),
+            t.url.clone(),
+            status_name(&t.status),
+            t.uploader.clone().unwrap_or_default(),
+            t.file_path.clone().unwrap_or_default(),
+            t.file_size.clone().unwrap_or_default(),
+            t.format.clone().unwrap_or_default(),
+            t.added_at.to_string(),
+            t.completed_at.map(|c| c.to_string()).unwrap_or_default(),
+        ];
+        let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Extended M3U of the downloaded files; tasks without an output file are skipped.
+pub fn to_m3u(tasks: &[DownloadTask]) -> String {
+    let mut out = String::from("#EXTM3U\n");
+    for t in tasks {
+        let Some(path) = t.file_path.as_ref().filter(|p| !p.is_empty()) else {
+            continue;
+        };
+        // Titles can't span lines in M3U
+        let title = t.title.replace(['\r', '\n'], " ");
+        out.push_str(&format!("#EXTINF:-1,{}\n{}\n", title, path));
+    }
+    out
+}
+
+pub fn render(format: ExportFormat, tasks: &[DownloadTask]) -> Result<String, String> {
+    match format {
+        ExportFormat::Json => to_json(tasks),
+        ExportFormat::Csv => Ok(to_csv(tasks)),
+        ExportFormat::M3u => Ok(to_m3u(tasks)),
+    }
+}
+
+/// Reads a JSON export (or a bare array of tasks). Entries that don't parse as
+/// tasks are counted rather than failing the whole import.
+pub fn parse_import(content: &str) -> Result<(Vec<DownloadTask>, usize), String> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Not a JSON export: {}", e))?;
+    let entries = match value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(mut obj) => match obj.remove("tasks") {
+            Some(serde_json::Value::Array(items)) => items,
+            _ => return Err("Export file has no \"tasks\" list".to_string()),
+        },
+        _ => return Err("Unsupported export format".to_string()),
+    };
+
+    let mut tasks = Vec::new();
+    let mut invalid = 0;
+    for entry in entries {
+        match serde_json::from_value::<DownloadTask>(entry) {
+            Ok(task) => tasks.push(task),
+            Err(_) => invalid += 1,
+        }
+    }
+    Ok((tasks, invalid))
+}
+
+/// Drops tasks whose URL or file path is already known (including earlier entries
+/// of the same import) and makes the rest safe to insert: fresh IDs on collision
+/// and no state left over from a process on another machine. URLs are compared
+/// in their `sanitize_url` form, so `known_urls` must hold canonical URLs.
+pub fn dedupe_import(
+    incoming: Vec<DownloadTask>,
+    known_urls: &mut HashSet<String>,
+    known_paths: &mut HashSet<String>,
+    existing_ids: &HashSet<String>,
+) -> (Vec<DownloadTask>, usize) {
+    let mut accepted = Vec::new();
+    let mut duplicates = 0;
+
+    for mut task in incoming {
+        let url = crate::ytdlp::sanitize_url(task.url.trim());
+        let path = task.file_path.clone().filter(|p| !p.is_empty());
+        if known_urls.contains(&url) || path.as_ref().is_some_and(|p| known_paths.contains(p)) {
+            duplicates += 1;
+            continue;
+        }
+        known_urls.insert(url);
+        if let Some(path) = path {
+            known_paths.insert(path);
+        }
+
+        if existing_ids.contains(&task.id) {
+            task.id = uuid::Uuid::new_v4().to_string();
+        }
+        task.pid = None;
+        task.applied_speed_limit = None;
+        if matches!(
+            task.status,
+            TaskStatus::Downloading
+                | TaskStatus::FetchingInfo
+                | TaskStatus::Processing
+                | TaskStatus::Queued
+                | TaskStatus::Paused
+        ) {
+            task.status = TaskStatus::Stopped;
+            task.status_detail = Some("Imported".to_string());
+        }
+        accepted.push(task);
+    }
+
+    (accepted, duplicates)
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(url: &str, file: Option<&str>) -> DownloadTask {
+        let mut t = DownloadTask::new_pending(url.to_string(), Default::default());
+        t.title = "A, \"quoted\" title".to_string();
+        t.status = TaskStatus::Completed;
+        t.file_path = file.map(String::from);
+        t
+    }
+
+    #[test]
+    fn csv_and_m3u_output() {
+        let tasks = vec![
+            task("https://a.example/1", Some("/dl/a.mp4")),
+            task("https://b.example/2", None),
+        ];
+
+        let csv = to_csv(&tasks);
+        assert!(csv.starts_with("id,title,url,"));
+        assert!(csv.contains(",\"A, \"\"quoted\"\" title\",https://a.example/1,completed,"));
+
+        let m3u = to_m3u(&tasks);
+        assert_eq!(m3u.lines().count(), 3);
+        assert!(m3u.ends_with("#EXTINF:-1,A, \"quoted\" title\n/dl/a.mp4\n"));
+    }
+
+    #[test]
+    fn json_round_trip_dedupes_by_url_and_path() {
+        let exported = to_json(&[
+            task("https://a.example/1", Some("/dl/a.mp4")),
+            task("https://b.example/2", Some("/dl/b.mp4")),
+            task("https://c.example/3", Some("/dl/a.mp4")),
+            task("https://b.example/2?utm_source=feed", Some("/dl/b2.mp4")),
+        ])
+        .unwrap();
+        let (tasks, invalid) = parse_import(&exported).unwrap();
+        assert_eq!((tasks.len(), invalid), (4, 0));
+
+        let mut urls: HashSet<String> = ["https://b.example/2".to_string()].into();
+        let mut paths = HashSet::new();
+        let (accepted, duplicates) = dedupe_import(tasks, &mut urls, &mut paths, &HashSet::new());
+        // b: URL already known (also with tracking parameters), c: same file as a
+        assert_eq!(duplicates, 3);
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].url, "https://a.example/1");
+    }
+
+    #[test]
+    fn filter_by_status_and_date() {
+        let mut t = task("https://a.example/1", None);
+        t.completed_at = Some(5_000);
+        let filter = ExportFilter {
+            statuses: vec![TaskStatus::Completed],
+            from: Some(1_000),
+            to: Some(5_000),
+        };
+        assert!(!filter.matches(&t));
+        t.completed_at = Some(4_999);
+        assert!(filter.matches(&t));
+    }
+}
@@ -0,0 +1,667 @@
+use crate::download_queue::{emit_queue_update, DownloadTask, QueueState, TaskStatus};
+use rusqlite::{params, params_from_iter, types::Value, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+/// Completed tasks kept in the live queue so the UI's recent list stays populated;
+/// anything older moves to the database.
+const RECENT_COMPLETED_IN_QUEUE: usize = 50;
+
+const SCHEMA_VERSION: i32 = 2;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS history (
+    id TEXT PRIMARY KEY,
+    url TEXT NOT NULL,
+    title TEXT NOT NULL,
+    uploader TEXT,
+    site TEXT,
+    status TEXT NOT NULL,
+    finished_at INTEGER NOT NULL,
+    task_json TEXT NOT NULL,
+    canonical_url TEXT,
+    video_id TEXT,
+    extractor TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_history_finished ON history(finished_at);
+CREATE INDEX IF NOT EXISTS idx_history_status ON history(status);
+CREATE INDEX IF NOT EXISTS idx_history_site ON history(site);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS history_fts USING fts5(
+    title, url, uploader, content='history', content_rowid='rowid'
+);
+CREATE TRIGGER IF NOT EXISTS history_ai AFTER INSERT ON history BEGIN
+    INSERT INTO history_fts(rowid, title, url, uploader)
+    VALUES (new.rowid, new.title, new.url, new.uploader);
+END;
+CREATE TRIGGER IF NOT EXISTS history_ad AFTER DELETE ON history BEGIN
+    INSERT INTO history_fts(history_fts, rowid, title, url, uploader)
+    VALUES ('delete', old.rowid, old.title, old.url, old.uploader);
+END;
+CREATE TRIGGER IF NOT EXISTS history_au AFTER UPDATE ON history BEGIN
+    INSERT INTO history_fts(history_fts, rowid, title, url, uploader)
+    VALUES ('delete', old.rowid, old.title, old.url, old.uploader);
+    INSERT INTO history_fts(rowid, title, url, uploader)
+    VALUES (new.rowid, new.title, new.url, new.uploader);
+END;
+";
+
+/// Indexes on columns added in v2; created after `migrate_v1` has added them.
+const INDEXES_V2: &str = "
+CREATE INDEX IF NOT EXISTS idx_history_canonical ON history(canonical_url);
+CREATE INDEX IF NOT EXISTS idx_history_video ON history(video_id);
+";
+
+/// Filters for `query`. Dates are Unix milliseconds, `page` is zero-based.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryQuery {
+    pub search: Option<String>,
+    pub statuses: Vec<TaskStatus>,
+    pub site: Option<String>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub page: u32,
+    pub page_size: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPage {
+    pub items: Vec<DownloadTask>,
+    pub total: u64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySite {
+    pub site: String,
+    pub count: u64,
+}
+
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+/// Host without `www.`/`m.`, used for the site filter.
+pub fn site_from_url(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let host = rest
+        .split(['/', '?', '#'])
+        .next()?
+        .rsplit('@')
+        .next()?
+        .split(':')
+        .next()?
+        .to_lowercase();
+    let host = host
+        .strip_prefix("www.")
+        .or_else(|| host.strip_prefix("m."))
+        .unwrap_or(&host)
+        .to_string();
+    (!host.is_empty()).then_some(host)
+}
+
+/// Turns free text into an FTS5 query: every word is quoted (so `-`, `:` etc.
+/// aren't parsed as operators) and prefix-matched.
+fn fts_query(search: &str) -> Option<String> {
+    let terms: Vec<String> = search
+        .split_whitespace()
+        .map(|w| format!("\"{}\"*", w.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+fn status_key(status: &TaskStatus) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+impl HistoryStore {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        Self::init(conn)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(|e| e.to_string())?;
+        let version: i32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+        if version == 1 {
+            Self::migrate_v1(&conn)?;
+        }
+        conn.execute_batch(INDEXES_V2).map_err(|e| e.to_string())?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// v2 added the duplicate-detection keys; backfill them from the stored tasks.
+    fn migrate_v1(conn: &Connection) -> Result<(), String> {
+        log::info!("[History] Migrating history database to v2");
+        conn.execute_batch(
+            "ALTER TABLE history ADD COLUMN canonical_url TEXT;
+             ALTER TABLE history ADD COLUMN video_id TEXT;
+             ALTER TABLE history ADD COLUMN extractor TEXT;",
+        )
+        .map_err(|e| e.to_string())?;
+
+        let rows: Vec<(String, String)> = {
+            let mut stmt = conn
+                .prepare("SELECT id, task_json FROM history")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| e.to_string())?
+                .flatten()
+                .collect();
+            rows
+        };
+        for (id, json) in rows {
+            let Ok(task) = serde_json::from_str::<DownloadTask>(&json) else {
+                continue;
+            };
+            conn.execute(
+                "UPDATE history SET canonical_url = ?2, video_id = ?3, extractor = ?4 WHERE id = ?1",
+                params![
+                    id,
+                    crate::ytdlp::sanitize_url(&task.url),
+                    task.video_id,
+                    task.extractor
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Inserts or replaces tasks in one transaction.
+    pub fn archive(&self, tasks: &[DownloadTask]) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        {
+            let mut stmt = tx
+                .prepare(
+                    "INSERT INTO history (id, url, title, uploader, site, status, finished_at,
+                        task_json, canonical_url, video_id, extractor)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                     ON CONFLICT(id) DO UPDATE SET
+                        url = excluded.url, title = excluded.title, uploader = excluded.uploader,
+                        site = excluded.site, status = excluded.status,
+                        finished_at = excluded.finished_at, task_json = excluded.task_json,
+                        canonical_url = excluded.canonical_url, video_id = excluded.video_id,
+                        extractor = excluded.extractor",
+                )
+                .map_err(|e| e.to_string())?;
+            for task in tasks {
+                let json = serde_json::to_string(task).map_err(|e| e.to_string())?;
+                // completed_at is in ms, added_at in s
+                let finished_at = task.completed_at.unwrap_or(task.added_at * 1000) as i64;
+                stmt.execute(params![
+                    task.id,
+                    task.url,
+                    task.title,
+                    task.uploader,
+                    site_from_url(&task.url),
+                    status_key(&task.status),
+                    finished_at,
+                    json,
+                    crate::ytdlp::sanitize_url(&task.url),
+                    task.video_id,
+                    task.extractor,
+                ])
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// WHERE clause and bound values for the filter part of a query (paging ignored).
+    fn filter_sql(query: &HistoryQuery) -> (String, Vec<Value>) {
+        let mut clauses: Vec<String> = Vec::new();
+        let mut args: Vec<Value> = Vec::new();
+        if let Some(fts) = query.search.as_deref().and_then(fts_query) {
+            clauses.push(
+                "rowid IN (SELECT rowid FROM history_fts WHERE history_fts MATCH ?)".to_string(),
+            );
+            args.push(Value::Text(fts));
+        }
+        if !query.statuses.is_empty() {
+            let marks = vec!["?"; query.statuses.len()].join(", ");
+            clauses.push(format!("status IN ({})", marks));
+            args.extend(query.statuses.iter().map(|s| Value::Text(status_key(s))));
+        }
+        if let Some(site) = query.site.as_ref().filter(|s| !s.is_empty()) {
+            clauses.push("site = ?".to_string());
+            args.push(Value::Text(site.to_lowercase()));
+        }
+        if let Some(from) = query.from {
+            clauses.push("finished_at >= ?".to_string());
+            args.push(Value::Integer(from as i64));
+        }
+        if let Some(to) = query.to {
+            clauses.push("finished_at < ?".to_string());
+            args.push(Value::Integer(to as i64));
+        }
+        let where_sql = if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clauses.join(" AND "))
+        };
+        (where_sql, args)
+    }
+
+    fn load_tasks(
+        conn: &Connection,
+        sql: &str,
+        args: &[Value],
+    ) -> Result<Vec<DownloadTask>, String> {
+        let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+        let tasks = stmt
+            .query_map(params_from_iter(args.iter()), |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?
+            .filter_map(|json| json.ok())
+            .filter_map(|json| serde_json::from_str::<DownloadTask>(&json).ok())
+            .collect();
+        Ok(tasks)
+    }
+
+    pub fn query(&self, query: &HistoryQuery) -> Result<HistoryPage, String> {
+        let page_size = query.page_size.unwrap_or(50).clamp(1, 500);
+        let (where_sql, args) = Self::filter_sql(query);
+
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let total: i64 = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM history {}", where_sql),
+                params_from_iter(args.iter()),
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+
+        let mut page_args = args;
+        page_args.push(Value::Integer(page_size as i64));
+        page_args.push(Value::Integer(query.page as i64 * page_size as i64));
+        let items = Self::load_tasks(
+            &conn,
+            &format!(
+                "SELECT task_json FROM history {} ORDER BY finished_at DESC LIMIT ? OFFSET ?",
+                where_sql
+            ),
+            &page_args,
+        )?;
+
+        Ok(HistoryPage {
+            items,
+            total: total.max(0) as u64,
+            page: query.page,
+            page_size,
+        })
+    }
+
+    /// Every record matching the filter, newest first (for export).
+    pub fn query_all(&self, query: &HistoryQuery) -> Result<Vec<DownloadTask>, String> {
+        let (where_sql, args) = Self::filter_sql(query);
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        Self::load_tasks(
+            &conn,
+            &format!(
+                "SELECT task_json FROM history {} ORDER BY finished_at DESC",
+                where_sql
+            ),
+            &args,
+        )
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<DownloadTask>, String> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let tasks = Self::load_tasks(
+            &conn,
+            "SELECT task_json FROM history WHERE id = ?",
+            &[Value::Text(id.to_string())],
+        )?;
+        Ok(tasks.into_iter().next())
+    }
+
+    /// Canonical URLs (see `ytdlp::sanitize_url`) and file paths already in the
+    /// history, for import de-duplication.
+    pub fn known_keys(&self) -> Result<(HashSet<String>, HashSet<String>), String> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stmt = conn
+            .prepare(
+                "SELECT url, canonical_url, json_extract(task_json, '$.filePath') FROM history",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut urls = HashSet::new();
+        let mut paths = HashSet::new();
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        for (url, canonical, path) in rows.flatten() {
+            urls.insert(
+                canonical
+                    .filter(|c| !c.is_empty())
+                    .unwrap_or_else(|| crate::ytdlp::sanitize_url(url.trim())),
+            );
+            if let Some(path) = path.filter(|p| !p.is_empty()) {
+                paths.insert(path);
+            }
+        }
+        Ok((urls, paths))
+    }
+
+    /// Most recent non-failed record with the same canonical URL or extractor video ID.
+    pub fn find_duplicate(
+        &self,
+        canonical_url: &str,
+        video_id: Option<&str>,
+        extractor: Option<&str>,
+    ) -> Result<Option<DownloadTask>, String> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let tasks = Self::load_tasks(
+            &conn,
+            "SELECT task_json FROM history
+             WHERE status = 'completed' AND (canonical_url = ?1
+                OR (?2 IS NOT NULL AND video_id = ?2
+                    AND (?3 IS NULL OR extractor IS NULL OR lower(extractor) = lower(?3))))
+             ORDER BY finished_at DESC LIMIT 1",
+            &[
+                Value::Text(canonical_url.to_string()),
+                video_id.map_or(Value::Null, |v| Value::Text(v.to_string())),
+                extractor.map_or(Value::Null, |e| Value::Text(e.to_string())),
+            ],
+        )?;
+        Ok(tasks.into_iter().next())
+    }
+
+    pub fn sites(&self) -> Result<Vec<HistorySite>, String> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stmt = conn
+            .prepare(
+                "SELECT site, COUNT(*) FROM history WHERE site IS NOT NULL
+                 GROUP BY site ORDER BY COUNT(*) DESC",
+            )
+            .map_err(|e| e.to_string())?;
+        let sites = stmt
+            .query_map([], |row| {
+                Ok(HistorySite {
+                    site: row.get(0)?,
+                    count: row.get::<_, i64>(1)?.max(0) as u64,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|s| s.ok())
+            .collect();
+        Ok(sites)
+    }
+
+    /// Deletes the given entries and returns the IDs that existed.
+    pub fn delete(&self, ids: &[String]) -> Result<Vec<String>, String> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut removed = Vec::new();
+        for id in ids {
+            let count = conn
+                .execute("DELETE FROM history WHERE id = ?1", params![id])
+                .map_err(|e| e.to_string())?;
+            if count > 0 {
+                removed.push(id.clone());
+            }
+        }
+        Ok(removed)
+    }
+
+    pub fn clear(&self) -> Result<usize, String> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.execute("DELETE FROM history", [])
+            .map_err(|e| e.to_string())
+    }
+
+    /// Applies the history retention settings (`0` disables either limit).
+    pub fn prune(&self, retention_days: u32, max_items: i32) -> Result<usize, String> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut removed = 0;
+        if retention_days > 0 {
+            let now_ms = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as i64;
+            let cutoff = now_ms - retention_days as i64 * 86_400_000;
+            removed += conn
+                .execute(
+                    "DELETE FROM history WHERE finished_at < ?1",
+                    params![cutoff],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        if max_items > 0 {
+            removed += conn
+                .execute(
+                    "DELETE FROM history WHERE id NOT IN
+                     (SELECT id FROM history ORDER BY finished_at DESC LIMIT ?1)",
+                    params![max_items],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(removed)
+    }
+}
+
+/// Moves completed tasks beyond the most recent `RECENT_COMPLETED_IN_QUEUE` out of
+/// the live queue into the history database. No-op when the store isn't available.
+pub fn archive_completed(app: &AppHandle, state: &QueueState, retention_days: u32, max_items: i32) {
+    let Some(store) = app.try_state::<Arc<HistoryStore>>() else {
+        return;
+    };
+
+    let candidates: Vec<DownloadTask> = {
+        let tasks = state.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let mut completed: Vec<&DownloadTask> = tasks
+            .values()
+            .filter(|t| t.status == TaskStatus::Completed)
+            .collect();
+        if completed.len() <= RECENT_COMPLETED_IN_QUEUE {
+            return;
+        }
+        completed.sort_by_key(|t| std::cmp::Reverse(t.completed_at.unwrap_or(0)));
+        completed
+            .into_iter()
+            .skip(RECENT_COMPLETED_IN_QUEUE)
+            .cloned()
+            .collect()
+    };
+
+    if let Err(e) = store.archive(&candidates) {
+        log::error!("[History] Failed to archive completed tasks: {}", e);
+        return;
+    }
+
+    let ids: Vec<String> = candidates.into_iter().map(|t| t.id).collect();
+    let moved = state.detach_tasks(&ids);
+    log::info!("[History] Archived {} completed task(s)", moved);
+
+    if let Err(e) = store.prune(retention_days, max_items) {
+        log::warn!("[History] Prune failed: {}", e);
+    }
+
+    state.save_now();
+    emit_queue_update(app, state);
+}
+
+/// Applies `f` to a task wherever it lives: the live queue first, then the
+/// history database. Returns false when neither knows the id.
+pub fn update_task_record<F>(
+    app: &AppHandle,
+    state: &QueueState,
+    id: &str,
+    f: F,
+) -> Result<bool, String>
+where
+    F: FnOnce(&mut DownloadTask),
+{
+    let in_queue = state
+        .tasks
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains_key(id);
+    if in_queue {
+        state.update_task(id, f);
+        emit_queue_update(app, state);
+        return Ok(true);
+    }
+
+    let Some(store) = app.try_state::<Arc<HistoryStore>>() else {
+        return Ok(false);
+    };
+    match store.get(id)? {
+        Some(mut task) => {
+            f(&mut task);
+            store.archive(&[task])?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completed(url: &str, title: &str, completed_at: u64) -> DownloadTask {
+        let mut task = DownloadTask::new_pending(url.to_string(), Default::default());
+        task.title = title.to_string();
+        task.status = TaskStatus::Completed;
+        task.completed_at = Some(completed_at);
+        task
+    }
+
+    #[test]
+    fn site_from_url_strips_prefixes() {
+        assert_eq!(
+            site_from_url("https://www.youtube.com/watch?v=x").as_deref(),
+            Some("youtube.com")
+        );
+        assert_eq!(
+            site_from_url("https://m.twitch.tv:443/videos/1").as_deref(),
+            Some("twitch.tv")
+        );
+        assert_eq!(site_from_url(""), None);
+    }
+
+    #[test]
+    fn search_filter_and_paginate() {
+        let store = HistoryStore::init(Connection::open_in_memory().unwrap()).unwrap();
+        let mut tasks = vec![
+            completed(
+                "https://www.youtube.com/watch?v=1",
+                "Rust conference talk",
+                3_000,
+            ),
+            completed("https://vimeo.com/2", "Cooking pasta", 2_000),
+            completed("https://youtube.com/watch?v=3", "Rustic cabin tour", 1_000),
+        ];
+        tasks[1].uploader = Some("Rust Kitchen".to_string());
+        store.archive(&tasks).unwrap();
+
+        let page = store
+            .query(&HistoryQuery {
+                search: Some("rust".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        // Prefix match over title and uploader, newest first
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items[0].title, "Rust conference talk");
+
+        let page = store
+            .query(&HistoryQuery {
+                search: Some("rust".to_string()),
+                site: Some("youtube.com".to_string()),
+                page: 1,
+                page_size: Some(1),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].title, "Rustic cabin tour");
+
+        assert_eq!(store.prune(0, 1).unwrap(), 2);
+    }
+
+    #[test]
+    fn known_keys_are_canonical_urls() {
+        let store = HistoryStore::init(Connection::open_in_memory().unwrap()).unwrap();
+        store
+            .archive(&[completed(
+                "https://www.youtube.com/watch?v=abc&si=tracking",
+                "Talk",
+                1_000,
+            )])
+            .unwrap();
+        let (urls, _) = store.known_keys().unwrap();
+        assert!(urls.contains("https://www.youtube.com/watch?v=abc"));
+    }
+
+    #[test]
+    fn find_duplicate_by_url_or_video_id() {
+        let store = HistoryStore::init(Connection::open_in_memory().unwrap()).unwrap();
+        let mut task = completed("https://www.youtube.com/watch?v=abc", "Talk", 1_000);
+        task.video_id = Some("abc".to_string());
+        task.extractor = Some("Youtube".to_string());
+        store.archive(&[task]).unwrap();
+
+        let found = |url: &str, id: Option<&str>, extractor: Option<&str>| {
+            store
+                .find_duplicate(url, id, extractor)
+                .unwrap()
+                .map(|t| t.title)
+        };
+        assert_eq!(
+            found("https://www.youtube.com/watch?v=abc", None, None).as_deref(),
+            Some("Talk")
+        );
+        assert_eq!(
+            found("https://youtu.be/abc", Some("abc"), Some("youtube")).as_deref(),
+            Some("Talk")
+        );
+        assert_eq!(
+            found("https://vimeo.com/abc", Some("abc"), Some("Vimeo")),
+            None
+        );
+
+        let mut stopped = completed("https://vimeo.com/7", "Stopped", 2_000);
+        stopped.status = TaskStatus::Stopped;
+        let id = stopped.id.clone();
+        store.archive(&[stopped]).unwrap();
+        assert_eq!(found("https://vimeo.com/7", None, None), None);
+
+        // Only IDs that were actually deleted are reported back
+        let ids = [id.clone(), "missing".to_string()];
+        assert_eq!(store.delete(&ids).unwrap(), vec![id]);
+    }
+}
@@ -6,11 +6,26 @@ use tauri::{
     Emitter, Manager,
 };
 
+mod bandwidth;
+mod batch_file;
 mod binary_resolver;
+mod clips;
+pub mod cli;
 mod commands;
 mod download_queue;
+mod duplicates;
+mod follow_up;
+mod history_io;
+mod history_store;
+mod loudness;
+mod playlist;
+mod previews;
 mod server;
 pub mod store_helpers;
+mod subscriptions;
+mod subtitles;
+mod tagging;
+mod watch_folders;
 mod ytdlp;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -77,6 +92,8 @@ pub fn run() {
             commands::keyring::set_credential,
             commands::keyring::get_credential,
             commands::keyring::delete_credential,
+            commands::keyring::get_api_token,
+            commands::keyring::regenerate_api_token,
             commands::download::download_with_channel,
             commands::download::get_download_args,
             commands::download::cancel_download,
@@ -84,7 +101,16 @@ pub fn run() {
             commands::filesystem::get_unique_filepath,
             commands::filesystem::save_temp_cookie_file,
             commands::ffmpeg::compress_media,
+            commands::ffmpeg::export_animated,
+            commands::ffmpeg::burn_subtitles,
             commands::ffmpeg::split_media_chapters,
+            commands::ffmpeg::split_audio,
+            commands::previews::generate_previews,
+            commands::loudness::analyze_loudness,
+            commands::loudness::normalize_loudness,
+            commands::subtitles::read_subtitles,
+            commands::subtitles::write_subtitles,
+            commands::subtitles::convert_subtitles,
             commands::settings::validate_path,
             commands::settings::validate_url,
             commands::settings::is_youtube_url,
@@ -93,10 +119,38 @@ pub fn run() {
             commands::queue::pause_queue,
             commands::queue::resume_queue,
             commands::queue::pause_task,
+            commands::queue::move_task,
+            commands::queue::move_task_to_top,
+            commands::queue::move_task_to_bottom,
+            commands::queue::set_task_priority,
+            commands::queue::set_task_follow_ups,
+            commands::queue::add_local_jobs,
+            commands::history::query_history,
+            commands::history::get_history_sites,
+            commands::history::delete_history_items,
+            commands::history::clear_history,
+            commands::history::export_history,
+            commands::history::import_history,
             commands::queue::resume_task,
+            commands::queue::retry_task,
             commands::queue::get_queue_state,
             commands::queue::verify_file_sizes,
             commands::queue::add_history_item,
+            commands::queue::expand_playlist,
+            commands::queue::add_playlist_to_queue,
+            commands::queue::get_group_progress,
+            commands::queue::pause_group,
+            commands::queue::resume_group,
+            commands::queue::remove_group,
+            commands::subscriptions::list_subscriptions,
+            commands::subscriptions::add_subscription,
+            commands::subscriptions::update_subscription,
+            commands::subscriptions::remove_subscription,
+            commands::subscriptions::sync_subscription_now,
+            commands::watch_folders::list_watch_folders,
+            commands::watch_folders::add_watch_folder,
+            commands::watch_folders::update_watch_folder,
+            commands::watch_folders::remove_watch_folder,
             commands::updater::check_updates,
             commands::updater::update_binary,
             commands::updater::cancel_update,
@@ -106,9 +160,14 @@ pub fn run() {
             commands::integrity::verify_binary_integrity,
             commands::notifications::notify_background,
             commands::metadata::parse_video_metadata,
+            commands::metadata::read_media_tags,
+            commands::metadata::write_media_tags,
+            commands::metadata::tag_history_items,
             commands::analysis::estimate_export_size,
             commands::analysis::estimate_download_size,
             commands::io::parse_batch_file,
+            commands::io::parse_batch_entries,
+            commands::io::import_batch_file,
         ])
         .setup(|app| {
             let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
@@ -203,6 +262,26 @@ pub fn run() {
             )));
             app.manage(queue_state.clone());
 
+            // Archived history (SQLite); without it completed tasks simply stay in the queue
+            if let Ok(data_dir) = app.path().app_data_dir() {
+                match crate::history_store::HistoryStore::open(&data_dir.join("history.db")) {
+                    Ok(store) => {
+                        app.manage(std::sync::Arc::new(store));
+                    }
+                    Err(e) => log::error!("[History] Failed to open history database: {}", e),
+                }
+            }
+
+            // Subscriptions (synced by the queue processor)
+            app.manage(std::sync::Arc::new(
+                crate::subscriptions::SubscriptionState::new(Some(app.handle().clone())),
+            ));
+
+            // Watch folders (scanned by the queue processor)
+            app.manage(std::sync::Arc::new(
+                crate::watch_folders::WatchFolderState::new(Some(app.handle().clone())),
+            ));
+
             // Spawn Background Queue Processor
             let handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
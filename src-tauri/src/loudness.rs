@@ -0,0 +1,363 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+
+/// EBU R128 targets for loudnorm. The defaults match the single-pass
+/// `audio_normalization` filter in `build_ytdlp_args`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LoudnessTarget {
+    /// Integrated loudness, LUFS
+    pub integrated: f64,
+    /// Maximum true peak, dBTP
+    pub true_peak: f64,
+    /// Loudness range, LU
+    pub lra: f64,
+}
+
+impl Default for LoudnessTarget {
+    fn default() -> Self {
+        Self {
+            integrated: -16.0,
+            true_peak: -1.5,
+            lra: 11.0,
+        }
+    }
+}
+
+/// One loudnorm measurement. Values are `None` when ffmpeg reports `-inf`
+/// (silent input).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoudnessMeasurement {
+    pub integrated: Option<f64>,
+    pub true_peak: Option<f64>,
+    pub lra: Option<f64>,
+    pub threshold: Option<f64>,
+}
+
+/// Stored on the task so levels can be audited later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoudnessReport {
+    pub input_path: String,
+    pub input: LoudnessMeasurement,
+    pub target: LoudnessTarget,
+    /// Set once a normalized copy was written.
+    pub normalized_path: Option<String>,
+    /// Levels of the normalized copy, as loudnorm measured them while writing.
+    pub output: Option<LoudnessMeasurement>,
+    pub measured_at: u64,
+}
+
+/// Raw `print_format=json` block; loudnorm prints every number as a string.
+#[derive(Debug, Deserialize)]
+struct LoudnormJson {
+    input_i: String,
+    input_tp: String,
+    input_lra: String,
+    input_thresh: String,
+    output_i: String,
+    output_tp: String,
+    output_lra: String,
+    output_thresh: String,
+    target_offset: String,
+}
+
+fn level(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// The (input, output) measurements and target offset from loudnorm's log.
+/// The JSON block is the last `{ ... }` in stderr.
+fn parse_loudnorm(stderr: &str) -> Result<(LoudnessMeasurement, LoudnessMeasurement, f64), String> {
+    let start = stderr
+        .rfind('{')
+        .ok_or("ffmpeg printed no loudness measurement")?;
+    let end = stderr[start..]
+        .find('}')
+        .map(|i| start + i + 1)
+        .ok_or("Truncated loudness measurement")?;
+    let json: LoudnormJson = serde_json::from_str(&stderr[start..end])
+        .map_err(|e| format!("Unreadable loudness measurement: {}", e))?;
+
+    let input = LoudnessMeasurement {
+        integrated: level(&json.input_i),
+        true_peak: level(&json.input_tp),
+        lra: level(&json.input_lra),
+        threshold: level(&json.input_thresh),
+    };
+    let output = LoudnessMeasurement {
+        integrated: level(&json.output_i),
+        true_peak: level(&json.output_tp),
+        lra: level(&json.output_lra),
+        threshold: level(&json.output_thresh),
+    };
+    Ok((input, output, level(&json.target_offset).unwrap_or(0.0)))
+}
+
+fn target_filter(target: &LoudnessTarget) -> String {
+    format!(
+        "loudnorm=I={}:TP={}:LRA={}",
+        target.integrated, target.true_peak, target.lra
+    )
+}
+
+/// First pass: decode the audio only and let loudnorm measure it.
+fn analysis_args(input: &str, target: &LoudnessTarget) -> Vec<String> {
+    vec![
+        "-hide_banner".into(),
+        "-nostats".into(),
+        "-i".into(),
+        input.into(),
+        "-map".into(),
+        "0:a:0".into(),
+        "-af".into(),
+        format!("{}:print_format=json", target_filter(target)),
+        "-f".into(),
+        "null".into(),
+        "-".into(),
+    ]
+}
+
+/// Encoder for the first audio stream matching the container, so the copy
+/// keeps its extension.
+fn audio_codec_args(output: &str) -> Vec<&'static str> {
+    let ext = Path::new(output)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "mp3" => vec!["-c:a:0", "libmp3lame", "-b:a:0", "320k"],
+        "opus" | "ogg" | "webm" => vec!["-c:a:0", "libopus", "-b:a:0", "192k"],
+        "flac" => vec!["-c:a:0", "flac"],
+        "wav" => vec!["-c:a:0", "pcm_s16le"],
+        _ => vec!["-c:a:0", "aac", "-b:a:0", "256k"],
+    }
+}
+
+/// Second pass: feed the measured values back so loudnorm can apply a
+/// linear gain instead of dynamic compression. Only the first audio stream
+/// was measured, so only it is filtered; video, subtitles and any other
+/// audio streams are copied. loudnorm resamples to 192 kHz internally, hence
+/// `-ar`.
+fn normalize_args(
+    input: &str,
+    output: &str,
+    target: &LoudnessTarget,
+    measured: &LoudnessMeasurement,
+    offset: f64,
+) -> Result<Vec<String>, String> {
+    let (Some(i), Some(tp), Some(lra), Some(thresh)) = (
+        measured.integrated,
+        measured.true_peak,
+        measured.lra,
+        measured.threshold,
+    ) else {
+        return Err("The audio is silent; there is nothing to normalize".to_string());
+    };
+    let filter = format!(
+        "{}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true:print_format=json",
+        target_filter(target),
+        i,
+        tp,
+        lra,
+        thresh,
+        offset
+    );
+
+    let mut args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-nostats".into(),
+        "-y".into(),
+        "-i".into(),
+        input.into(),
+    ];
+    for map in ["0:v?", "0:a", "0:s?"] {
+        args.push("-map".into());
+        args.push(map.into());
+    }
+    args.extend(["-c", "copy"].map(String::from));
+    args.extend(audio_codec_args(output).into_iter().map(String::from));
+    args.extend([
+        "-filter:a:0".into(),
+        filter,
+        "-ar:a:0".into(),
+        "48000".into(),
+    ]);
+    args.push(output.into());
+    Ok(args)
+}
+
+/// Runs ffmpeg and returns its stderr, where loudnorm prints its JSON.
+async fn run_ffmpeg(ffmpeg_path: &str, args: &[String]) -> Result<String, String> {
+    #[allow(unused_mut)]
+    let mut std_command = std::process::Command::new(ffmpeg_path);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        std_command.creation_flags(CREATE_NO_WINDOW);
+    }
+    std_command
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let mut command = tokio::process::Command::from(std_command);
+    command.kill_on_drop(true);
+    let output = command
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if output.status.success() {
+        return Ok(stderr);
+    }
+    let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+    Err(format!(
+        "Loudness pass failed: {}",
+        tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+    ))
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Measures integrated loudness, true peak and LRA of the first audio stream.
+pub async fn analyze(
+    ffmpeg_path: &str,
+    input: &str,
+    target: LoudnessTarget,
+) -> Result<LoudnessReport, String> {
+    if !Path::new(input).is_file() {
+        return Err(format!("File not found: {}", input));
+    }
+    let stderr = run_ffmpeg(ffmpeg_path, &analysis_args(input, &target)).await?;
+    let (measured, _, _) = parse_loudnorm(&stderr)?;
+    log::info!("[Loudness] {}: {:?}", input, measured);
+    Ok(LoudnessReport {
+        input_path: input.to_string(),
+        input: measured,
+        target,
+        normalized_path: None,
+        output: None,
+        measured_at: now_millis(),
+    })
+}
+
+/// Two-pass normalization: measures `input`, then writes `output` with the
+/// measured values applied. Writing over the input goes through a
+/// temporary sibling file.
+pub async fn normalize(
+    ffmpeg_path: &str,
+    input: &str,
+    output: &str,
+    target: LoudnessTarget,
+) -> Result<LoudnessReport, String> {
+    let first = run_ffmpeg(ffmpeg_path, &analysis_args(input, &target)).await?;
+    let (measured, _, offset) = parse_loudnorm(&first)?;
+
+    let in_place = Path::new(input) == Path::new(output);
+    let write_to = if in_place {
+        crate::clips::with_infix(output, "loudnorm")
+    } else {
+        output.to_string()
+    };
+    let args = normalize_args(input, &write_to, &target, &measured, offset)?;
+    let second = match run_ffmpeg(ffmpeg_path, &args).await {
+        Ok(stderr) => stderr,
+        Err(e) => {
+            let _ = std::fs::remove_file(&write_to);
+            return Err(e);
+        }
+    };
+    if in_place {
+        std::fs::rename(&write_to, output)
+            .map_err(|e| format!("Failed to replace {}: {}", output, e))?;
+    }
+    let (_, result, _) = parse_loudnorm(&second)?;
+
+    log::info!(
+        "[Loudness] Normalized {} -> {} ({:?} -> {:?} LUFS)",
+        input,
+        output,
+        measured.integrated,
+        result.integrated
+    );
+    Ok(LoudnessReport {
+        input_path: input.to_string(),
+        input: measured,
+        target,
+        normalized_path: Some(output.to_string()),
+        output: Some(result),
+        measured_at: now_millis(),
+    })
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = r#"Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'in.mp4':
+  Duration: 00:03:12.05, start: 0.000000, bitrate: 1203 kb/s
+[Parsed_loudnorm_0 @ 0x600000c7c000]
+{
+	"input_i" : "-23.41",
+	"input_tp" : "-4.20",
+	"input_lra" : "7.30",
+	"input_thresh" : "-33.72",
+	"output_i" : "-16.02",
+	"output_tp" : "-1.50",
+	"output_lra" : "6.10",
+	"output_thresh" : "-26.31",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.02"
+}
+"#;
+
+    #[test]
+    fn parses_loudnorm_json_block() {
+        let (input, output, offset) = parse_loudnorm(LOG).unwrap();
+        assert_eq!(input.integrated, Some(-23.41));
+        assert_eq!(input.true_peak, Some(-4.2));
+        assert_eq!(input.lra, Some(7.3));
+        assert_eq!(output.integrated, Some(-16.02));
+        assert_eq!(offset, 0.02);
+
+        let silent = LOG.replace("\"-23.41\"", "\"-inf\"");
+        let (input, _, _) = parse_loudnorm(&silent).unwrap();
+        assert_eq!(input.integrated, None);
+        assert!(parse_loudnorm("no json here").is_err());
+    }
+
+    #[test]
+    fn second_pass_applies_measured_values() {
+        let (input, _, offset) = parse_loudnorm(LOG).unwrap();
+        let target = LoudnessTarget::default();
+        let args = normalize_args("in.mp4", "out.mp4", &target, &input, offset)
+            .unwrap()
+            .join(" ");
+        assert!(args.contains("-map 0:v? -map 0:a -map 0:s? -c copy -c:a:0 aac -b:a:0 256k"));
+        assert!(args.contains(
+            "-filter:a:0 loudnorm=I=-16:TP=-1.5:LRA=11:measured_I=-23.41:measured_TP=-4.2:\
+             measured_LRA=7.3:measured_thresh=-33.72:offset=0.02:linear=true:print_format=json"
+        ));
+        assert!(args.ends_with("-ar:a:0 48000 out.mp4"));
+        assert!(analysis_args("in.mp3", &target)
+            .join(" ")
+            .ends_with("print_format=json -f null -"));
+
+        let silent = LoudnessMeasurement::default();
+        assert!(normalize_args("in.mp3", "out.mp3", &target, &silent, 0.0).is_err());
+    }
+}
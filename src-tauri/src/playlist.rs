@@ -0,0 +1,335 @@
+use crate::ytdlp::AppSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use tokio::process::Command;
+
+/// A single item of a playlist/channel as reported by `yt-dlp --flat-playlist`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistEntry {
+    /// 1-based position inside the playlist (matches yt-dlp's `playlist_index`).
+    pub index: u32,
+    pub id: Option<String>,
+    pub url: String,
+    pub title: Option<String>,
+    pub duration: Option<f64>,
+    pub uploader: Option<String>,
+    pub ie_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistInfo {
+    pub id: Option<String>,
+    pub title: String,
+    pub uploader: Option<String>,
+    pub webpage_url: String,
+    /// False when the URL resolved to a single video instead of a collection.
+    pub is_playlist: bool,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+/// Builds the arguments for a flat (metadata-only) playlist listing.
+/// Only network/auth settings are forwarded; format selection is irrelevant here.
+pub fn flat_playlist_args(url: &str, settings: &AppSettings) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "--flat-playlist".to_string(),
+        "-J".to_string(),
+        "--no-warnings".to_string(),
+        "--ignore-config".to_string(),
+        "--encoding".to_string(),
+        "utf-8".to_string(),
+    ];
+
+    if !settings.proxy.is_empty() && !settings.proxy.starts_with('-') {
+        args.push("--proxy".to_string());
+        args.push(settings.proxy.clone());
+    }
+
+    if settings.cookie_source == "browser" {
+        let target = settings.browser_type.as_deref().unwrap_or("chrome");
+        args.push("--cookies-from-browser".to_string());
+        args.push(target.to_string());
+    } else if settings.cookie_source == "txt" {
+        if let Some(path) = &settings.cookie_path {
+            args.push("--cookies".to_string());
+            args.push(path.clone());
+        }
+    }
+
+    args.push("--".to_string());
+    args.push(crate::ytdlp::sanitize_url(url));
+    args
+}
+
+/// Runs `yt-dlp --flat-playlist -J` and returns the expanded entry list.
+pub async fn fetch_playlist(
+    ytdlp_path: &str,
+    url: &str,
+    settings: &AppSettings,
+) -> Result<PlaylistInfo, String> {
+    log::info!("[Playlist] Expanding: {}", url);
+
+    #[allow(unused_mut)]
+    let mut std_cmd = std::process::Command::new(ytdlp_path);
+    std_cmd.args(flat_playlist_args(url, settings));
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        std_cmd.creation_flags(0x08000000);
+    }
+
+    let mut cmd = Command::from(std_cmd);
+    cmd.kill_on_drop(true);
+
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        log::warn!("[Playlist] Expansion failed for {}: {}", url, stderr);
+        return Err(stderr.trim().to_string());
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse playlist JSON: {}", e))?;
+
+    let info = parse_flat_playlist(&json, url);
+    log::info!(
+        "[Playlist] \"{}\" expanded into {} entries",
+        info.title,
+        info.entries.len()
+    );
+    Ok(info)
+}
+
+/// Converts the `-J` output of a flat listing into a `PlaylistInfo`.
+/// Nested collections (e.g. channel tabs returned inline) are flattened.
+pub fn parse_flat_playlist(json: &serde_json::Value, source_url: &str) -> PlaylistInfo {
+    let str_field = |v: &serde_json::Value, key: &str| -> Option<String> {
+        v.get(key).and_then(|x| x.as_str()).map(String::from)
+    };
+
+    let is_playlist = json.get("_type").and_then(|v| v.as_str()) == Some("playlist")
+        || json.get("entries").map_or(false, |e| e.is_array());
+
+    let title = str_field(json, "title").unwrap_or_else(|| "Untitled Playlist".to_string());
+    let webpage_url = str_field(json, "webpage_url").unwrap_or_else(|| source_url.to_string());
+
+    let mut entries = Vec::new();
+    if is_playlist {
+        collect_entries(json, &mut entries);
+    } else {
+        // Single video: expose it as a one-item list so callers can treat both uniformly
+        entries.push(PlaylistEntry {
+            index: 1,
+            id: str_field(json, "id"),
+            url: webpage_url.clone(),
+            title: str_field(json, "title"),
+            duration: json.get("duration").and_then(|v| v.as_f64()),
+            uploader: str_field(json, "uploader"),
+            ie_key: str_field(json, "extractor_key"),
+        });
+    }
+
+    PlaylistInfo {
+        id: str_field(json, "id"),
+        title,
+        uploader: str_field(json, "uploader").or_else(|| str_field(json, "channel")),
+        webpage_url,
+        is_playlist,
+        entries,
+    }
+}
+
+fn collect_entries(json: &serde_json::Value, out: &mut Vec<PlaylistEntry>) {
+    let Some(items) = json.get("entries").and_then(|v| v.as_array()) else {
+        return;
+    };
+
+    for item in items {
+        if item.is_null() {
+            // yt-dlp emits null for unavailable/private items
+            continue;
+        }
+        if item.get("entries").map_or(false, |e| e.is_array()) {
+            collect_entries(item, out);
+            continue;
+        }
+
+        let id = item.get("id").and_then(|v| v.as_str()).map(String::from);
+        let ie_key = item
+            .get("ie_key")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+        let Some(url) = resolve_entry_url(item, id.as_deref(), ie_key.as_deref()) else {
+            continue;
+        };
+
+        out.push(PlaylistEntry {
+            index: out.len() as u32 + 1,
+            id,
+            url,
+            title: item.get("title").and_then(|v| v.as_str()).map(String::from),
+            duration: item.get("duration").and_then(|v| v.as_f64()),
+            uploader: item
+                .get("uploader")
+                .or_else(|| item.get("channel"))
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            ie_key,
+        });
+    }
+}
+
+fn resolve_entry_url(
+    item: &serde_json::Value,
+    id: Option<&str>,
+    ie_key: Option<&str>,
+) -> Option<String> {
+    for key in ["url", "webpage_url", "original_url"] {
+        if let Some(u) = item.get(key).and_then(|v| v.as_str()) {
+            if u.starts_with("http://") || u.starts_with("https://") {
+                return Some(u.to_string());
+            }
+        }
+    }
+
+    // YouTube flat entries sometimes only carry the bare video ID
+    match (ie_key, id) {
+        (Some("Youtube"), Some(id)) => Some(format!("https://www.youtube.com/watch?v={}", id)),
+        _ => None,
+    }
+}
+
+/// Parses an item selection such as `"1-3,7,10-"` into sorted 1-based indices.
+///
+/// Supported forms: `N`, `A-B`, `A-` (open ended) and `-B` (from the start).
+/// `:` is accepted as an alias for `-` to mirror yt-dlp's `--playlist-items`.
+pub fn parse_playlist_items(spec: &str, count: usize) -> Result<Vec<usize>, String> {
+    let mut selected = BTreeSet::new();
+
+    for raw in spec.split(',') {
+        let part = raw.trim().replace(':', "-");
+        if part.is_empty() {
+            continue;
+        }
+
+        let parse_idx = |s: &str| -> Result<usize, String> {
+            s.trim()
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid playlist index '{}'", s.trim()))
+        };
+
+        let (start, end) = match part.split_once('-') {
+            Some((a, b)) => {
+                let start = if a.trim().is_empty() {
+                    1
+                } else {
+                    parse_idx(a)?
+                };
+                let end = if b.trim().is_empty() {
+                    count
+                } else {
+                    parse_idx(b)?
+                };
+                (start, end)
+            }
+            None => {
+                let n = parse_idx(&part)?;
+                (n, n)
+            }
+        };
+
+        if start == 0 {
+            return Err("Playlist indices start at 1".to_string());
+        }
+        if start > end {
+            return Err(format!("Invalid range '{}'", raw.trim()));
+        }
+
+        for i in start..=end.min(count) {
+            selected.insert(i);
+        }
+    }
+
+    Ok(selected.into_iter().collect())
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn playlist_items_single_and_ranges() {
+        assert_eq!(parse_playlist_items("1-3,7", 10).unwrap(), vec![1, 2, 3, 7]);
+        assert_eq!(parse_playlist_items("8-", 10).unwrap(), vec![8, 9, 10]);
+        assert_eq!(parse_playlist_items("-2", 10).unwrap(), vec![1, 2]);
+        assert_eq!(parse_playlist_items("2:4", 10).unwrap(), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn playlist_items_dedup_and_clamp() {
+        assert_eq!(parse_playlist_items("3,1-3, 3", 10).unwrap(), vec![1, 2, 3]);
+        assert_eq!(parse_playlist_items("4-20", 5).unwrap(), vec![4, 5]);
+        assert!(parse_playlist_items("", 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn playlist_items_rejects_invalid() {
+        assert!(parse_playlist_items("0", 5).is_err());
+        assert!(parse_playlist_items("5-2", 10).is_err());
+        assert!(parse_playlist_items("abc", 10).is_err());
+    }
+
+    #[test]
+    fn parse_flat_playlist_entries() {
+        let json = serde_json::json!({
+            "_type": "playlist",
+            "id": "PL123",
+            "title": "My Mix",
+            "entries": [
+                { "id": "aaa", "ie_key": "Youtube", "url": "https://www.youtube.com/watch?v=aaa", "title": "First" },
+                null,
+                { "id": "bbb", "ie_key": "Youtube", "title": "Second", "duration": 12.5 },
+                { "id": "ccc", "ie_key": "Unknown" }
+            ]
+        });
+
+        let info = parse_flat_playlist(&json, "https://www.youtube.com/playlist?list=PL123");
+        assert!(info.is_playlist);
+        assert_eq!(info.title, "My Mix");
+        assert_eq!(info.entries.len(), 2);
+        assert_eq!(info.entries[1].index, 2);
+        assert_eq!(info.entries[1].url, "https://www.youtube.com/watch?v=bbb");
+        assert_eq!(info.entries[1].duration, Some(12.5));
+    }
+
+    #[test]
+    fn parse_flat_playlist_nested_and_single() {
+        let nested = serde_json::json!({
+            "_type": "playlist",
+            "title": "Channel",
+            "entries": [
+                { "_type": "playlist", "entries": [ { "url": "https://example.com/v/1" } ] },
+                { "url": "https://example.com/v/2" }
+            ]
+        });
+        let info = parse_flat_playlist(&nested, "https://example.com/c");
+        assert_eq!(info.entries.len(), 2);
+        assert_eq!(info.entries[0].index, 1);
+
+        let single = serde_json::json!({ "id": "x", "title": "Solo", "webpage_url": "https://example.com/v/x" });
+        let info = parse_flat_playlist(&single, "https://example.com/v/x");
+        assert!(!info.is_playlist);
+        assert_eq!(info.entries.len(), 1);
+        assert_eq!(info.entries[0].url, "https://example.com/v/x");
+    }
+}
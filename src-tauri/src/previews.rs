@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tauri::{AppHandle, Manager};
+
+const DEFAULT_TILE_WIDTH: u32 = 320;
+/// Upper bound for requested thumbnails; each one is a separate ffmpeg run.
+const MAX_THUMBNAILS: u32 = 24;
+/// Upper bound for contact sheet columns and rows (tiles are numbered `%02d`).
+const MAX_SHEET_SIDE: u32 = 8;
+
+/// Preview images generated for a task, cached under `previews/<task id>`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskPreviews {
+    pub poster: Option<String>,
+    #[serde(default)]
+    pub thumbnails: Vec<String>,
+    pub contact_sheet: Option<String>,
+}
+
+impl TaskPreviews {
+    /// Takes over whatever `other` generated, keeping the rest.
+    pub fn merge(&mut self, other: TaskPreviews) {
+        if other.poster.is_some() {
+            self.poster = other.poster;
+        }
+        if !other.thumbnails.is_empty() {
+            self.thumbnails = other.thumbnails;
+        }
+        if other.contact_sheet.is_some() {
+            self.contact_sheet = other.contact_sheet;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum PreviewRequest {
+    /// One full-size frame; defaults to 10% into the file.
+    Poster { at: Option<f64> },
+    /// `count` evenly spaced frames.
+    Thumbnails { count: u32 },
+    /// A `columns` × `rows` grid, optionally stamped with each frame's time.
+    ContactSheet {
+        columns: u32,
+        rows: u32,
+        #[serde(default)]
+        timestamps: bool,
+    },
+}
+
+/// Task IDs become folder names, so anything but a plain name (separators,
+/// `..`, a drive or root) is refused.
+fn is_safe_key(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Cache folder for a task, or for a loose file keyed by its path.
+pub fn cache_dir(app: &AppHandle, task_id: Option<&str>, input: &str) -> Result<PathBuf, String> {
+    let base = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("previews");
+    let key = match task_id {
+        Some(id) if is_safe_key(id) => id.to_string(),
+        Some(id) => return Err(format!("Invalid task ID: {}", id)),
+        None => format!("file-{:x}", Sha256::digest(input.as_bytes()))[..21].to_string(),
+    };
+    Ok(base.join(key))
+}
+
+/// Drops the cached previews of deleted history entries.
+pub fn remove_cached(app: &AppHandle, ids: &[String]) {
+    let Ok(base) = app.path().app_data_dir().map(|d| d.join("previews")) else {
+        return;
+    };
+    for id in ids.iter().filter(|id| is_safe_key(id)) {
+        let _ = std::fs::remove_dir_all(base.join(id));
+    }
+}
+
+/// `count` timestamps in the middle of equal slices of `duration`, so the
+/// first and last frames are never black intro/outro frames.
+pub fn frame_times(duration: f64, count: u32) -> Vec<f64> {
+    let step = duration / count.max(1) as f64;
+    (0..count).map(|i| (i as f64 + 0.5) * step).collect()
+}
+
+/// `M:SS`, or `H:MM:SS` past the hour.
+pub fn format_timestamp(secs: f64) -> String {
+    let total = secs.max(0.0) as u64;
+    let (h, m, s) = (total / 3600, total / 60 % 60, total % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+/// Grabs the frame at `at` (fast input seek), scaled to `width` and
+/// optionally stamped with its timestamp.
+fn frame_args(input: &str, at: f64, width: Option<u32>, stamp: bool, output: &str) -> Vec<String> {
+    let mut filters = Vec::new();
+    if let Some(w) = width {
+        filters.push(format!("scale={}:-2", w));
+    }
+    if stamp {
+        // Colons separate filter options, so the ones in the time are escaped
+        filters.push(format!(
+            "drawtext=text='{}':x=6:y=h-th-6:fontsize=16:fontcolor=white:box=1:boxcolor=black@0.6:boxborderw=4",
+            format_timestamp(at).replace(':', "\\:")
+        ));
+    }
+
+    let mut args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-y".into(),
+        "-ss".into(),
+        format!("{:.3}", at),
+        "-i".into(),
+        input.into(),
+        "-frames:v".into(),
+        "1".into(),
+    ];
+    if !filters.is_empty() {
+        args.push("-vf".into());
+        args.push(filters.join(","));
+    }
+    args.extend(["-q:v".into(), "3".into(), output.into()]);
+    args
+}
+
+/// Tiles numbered frames (`pattern` uses `%02d`) into one image.
+fn tile_args(pattern: &str, columns: u32, rows: u32, output: &str) -> Vec<String> {
+    vec![
+        "-hide_banner".into(),
+        "-y".into(),
+        "-i".into(),
+        pattern.into(),
+        "-vf".into(),
+        format!("tile={}x{}:padding=4:margin=4", columns, rows),
+        "-frames:v".into(),
+        "1".into(),
+        "-q:v".into(),
+        "3".into(),
+        output.into(),
+    ]
+}
+
+async fn run_ffmpeg_quiet(ffmpeg_path: &str, args: &[String]) -> Result<(), String> {
+    #[allow(unused_mut)]
+    let mut std_command = std::process::Command::new(ffmpeg_path);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        std_command.creation_flags(CREATE_NO_WINDOW);
+    }
+    std_command
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let mut command = tokio::process::Command::from(std_command);
+    command.kill_on_drop(true);
+    let output = command
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let tail: Vec<&str> = stderr.lines().rev().take(3).collect();
+    Err(format!(
+        "Preview generation failed: {}",
+        tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+    ))
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// Produces the requested previews of `input` in `dir`. Files already there
+/// from an earlier identical request are reused.
+pub async fn generate(
+    app: &AppHandle,
+    input: &str,
+    dir: &Path,
+    request: &PreviewRequest,
+    width: Option<u32>,
+) -> Result<TaskPreviews, String> {
+    if !Path::new(input).is_file() {
+        return Err(format!("File not found: {}", input));
+    }
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let settings = crate::ytdlp::load_settings(app);
+    let ffmpeg_path = crate::ytdlp::resolve_ffmpeg_path(app, &settings.binary_path_ffmpeg);
+    let (duration, _, _) = crate::commands::analysis::probe_file_info(app, Some(input)).await;
+    let duration = duration.filter(|d| *d > 0.0);
+
+    let mut previews = TaskPreviews::default();
+    match request {
+        PreviewRequest::Poster { at } => {
+            let at = at.or(duration.map(|d| d * 0.1)).unwrap_or(0.0).max(0.0);
+            let output = dir.join(format!("poster-{}.jpg", (at * 1000.0) as u64));
+            if !output.exists() {
+                let args = frame_args(input, at, width, false, &path_string(&output));
+                run_ffmpeg_quiet(&ffmpeg_path, &args).await?;
+            }
+            previews.poster = Some(path_string(&output));
+        }
+        PreviewRequest::Thumbnails { count } => {
+            let duration = duration.ok_or("Could not determine the duration")?;
+            let count = (*count).clamp(1, MAX_THUMBNAILS);
+            let width = width.unwrap_or(DEFAULT_TILE_WIDTH);
+            for (i, at) in frame_times(duration, count).into_iter().enumerate() {
+                let output = dir.join(format!("thumb-{}-{}-{:02}.jpg", count, width, i));
+                if !output.exists() {
+                    let args = frame_args(input, at, Some(width), false, &path_string(&output));
+                    run_ffmpeg_quiet(&ffmpeg_path, &args).await?;
+                }
+                previews.thumbnails.push(path_string(&output));
+            }
+        }
+        PreviewRequest::ContactSheet {
+            columns,
+            rows,
+            timestamps,
+        } => {
+            let duration = duration.ok_or("Could not determine the duration")?;
+            let (columns, rows) = (
+                (*columns).clamp(1, MAX_SHEET_SIDE),
+                (*rows).clamp(1, MAX_SHEET_SIDE),
+            );
+            let width = width.unwrap_or(DEFAULT_TILE_WIDTH);
+            let name = format!(
+                "sheet-{}x{}-{}{}",
+                columns,
+                rows,
+                width,
+                if *timestamps { "-ts" } else { "" }
+            );
+            let output = dir.join(format!("{}.jpg", name));
+            if !output.exists() {
+                let times = frame_times(duration, columns * rows);
+                let mut tiles = Vec::new();
+                let result = async {
+                    for (i, at) in times.into_iter().enumerate() {
+                        let tile = dir.join(format!("{}-{:02}.jpg", name, i));
+                        let args =
+                            frame_args(input, at, Some(width), *timestamps, &path_string(&tile));
+                        tiles.push(tile);
+                        run_ffmpeg_quiet(&ffmpeg_path, &args).await?;
+                    }
+                    let pattern = path_string(&dir.join(format!("{}-%02d.jpg", name)));
+                    let args = tile_args(&pattern, columns, rows, &path_string(&output));
+                    run_ffmpeg_quiet(&ffmpeg_path, &args).await
+                }
+                .await;
+                for tile in tiles {
+                    let _ = std::fs::remove_file(tile);
+                }
+                result?;
+            }
+            previews.contact_sheet = Some(path_string(&output));
+        }
+    }
+
+    log::info!("[Previews] Generated {:?} for {}", request, input);
+    Ok(previews)
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_centred_in_equal_slices() {
+        assert_eq!(frame_times(60.0, 3), vec![10.0, 30.0, 50.0]);
+        assert_eq!(format_timestamp(75.9), "1:15");
+        assert_eq!(format_timestamp(3725.0), "1:02:05");
+
+        let args = frame_args("in.mp4", 75.0, Some(320), true, "out.jpg").join(" ");
+        assert!(args.starts_with("-hide_banner -y -ss 75.000 -i in.mp4 -frames:v 1"));
+        assert!(args.contains("-vf scale=320:-2,drawtext=text='1\\:15':"));
+        let args = frame_args("in.mp4", 0.0, None, false, "out.jpg").join(" ");
+        assert!(!args.contains("-vf"));
+        assert!(tile_args("t-%02d.jpg", 4, 3, "s.jpg")
+            .contains(&"tile=4x3:padding=4:margin=4".to_string()));
+    }
+
+    #[test]
+    fn only_plain_task_ids_name_cache_folders() {
+        assert!(is_safe_key("3f2b9c1e-8a7d-4e21-9c3b-0d1f2e3a4b5c"));
+        for id in ["", "..", "../x", "a/b", "a\\b", "/etc", "C:", "."] {
+            assert!(!is_safe_key(id), "{}", id);
+        }
+    }
+
+    #[test]
+    fn merge_keeps_earlier_previews() {
+        let mut previews = TaskPreviews {
+            poster: Some("poster.jpg".to_string()),
+            thumbnails: vec!["a.jpg".to_string()],
+            contact_sheet: None,
+        };
+        previews.merge(TaskPreviews {
+            contact_sheet: Some("sheet.jpg".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(previews.poster.as_deref(), Some("poster.jpg"));
+        assert_eq!(previews.thumbnails, vec!["a.jpg".to_string()]);
+        assert_eq!(previews.contact_sheet.as_deref(), Some("sheet.jpg"));
+
+        let request: PreviewRequest =
+            serde_json::from_str(r#"{"kind":"contactSheet","columns":4,"rows":4}"#).unwrap();
+        assert!(matches!(
+            request,
+            PreviewRequest::ContactSheet {
+                timestamps: false,
+                ..
+            }
+        ));
+    }
+}
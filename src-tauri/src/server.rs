@@ -1,17 +1,23 @@
 use axum::{
-    extract::State,
-    http::{Method, StatusCode},
-    response::{IntoResponse, Response},
+    extract::{Path, Query, Request, State},
+    http::{header, Method, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     routing::{get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tauri::{AppHandle, Emitter, Manager};
 use tower_http::cors::{Any, CorsLayer};
 
-use crate::download_queue::{DownloadTask, QueueState, TaskStatus};
+use crate::download_queue::{DownloadTask, QueueEvent, QueueState, TaskPriority, TaskStatus};
+use crate::duplicates::{enqueue_with_policy, DuplicatePolicy, EnqueueStatus};
+use crate::follow_up::FollowUpAction;
 use crate::ytdlp::YtDlpOptions;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -21,6 +27,46 @@ struct DownloadRequest {
     user_agent: Option<String>,
     start_time: Option<f64>,
     end_time: Option<f64>,
+    #[serde(default, rename = "duplicatePolicy")]
+    duplicate_policy: DuplicatePolicy,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiAddTaskRequest {
+    url: String,
+    #[serde(default)]
+    options: YtDlpOptions,
+    #[serde(default)]
+    priority: TaskPriority,
+    #[serde(default, rename = "followUps")]
+    follow_ups: Vec<FollowUpAction>,
+    #[serde(default, rename = "duplicatePolicy")]
+    duplicate_policy: DuplicatePolicy,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ApiMovePosition {
+    Top,
+    Bottom,
+}
+
+/// Either `{"position": "top" | "bottom"}` or `{"index": n}`.
+#[derive(Debug, Deserialize)]
+struct ApiMoveTaskRequest {
+    index: Option<usize>,
+    position: Option<ApiMovePosition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiPriorityRequest {
+    priority: TaskPriority,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiEventsQuery {
+    task_id: Option<String>,
 }
 
 #[derive(Clone)]
@@ -28,6 +74,18 @@ struct AppState {
     app_handle: AppHandle,
 }
 
+/// Bearer token for the `/api` routes, cached from the keyring at startup.
+pub struct ApiToken(pub RwLock<String>);
+
+/// Swaps the in-memory token (used after regeneration so old clients are rejected).
+pub fn set_api_token(app: &AppHandle, token: String) {
+    if let Some(state) = app.try_state::<ApiToken>() {
+        *state.0.write().unwrap_or_else(|e| e.into_inner()) = token;
+    } else {
+        app.manage(ApiToken(RwLock::new(token)));
+    }
+}
+
 pub fn init(app_handle: AppHandle) {
     tauri::async_runtime::spawn(async move {
         start_server(app_handle).await;
@@ -35,10 +93,23 @@ pub fn init(app_handle: AppHandle) {
 }
 
 async fn start_server(app_handle: AppHandle) {
+    // Keyring access is blocking (D-Bus / Keychain), keep it off the async runtime
+    let token = tokio::task::spawn_blocking(crate::commands::keyring::get_or_create_api_token)
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()))
+        .unwrap_or_else(|e| {
+            log::warn!(
+                "[API] Keyring unavailable ({}). Using a session-only API token.",
+                e
+            );
+            uuid::Uuid::new_v4().simple().to_string()
+        });
+    set_api_token(&app_handle, token);
+
     // CORS whitelist: only browser extensions and localhost can access
     // Security: prevents arbitrary websites from adding downloads
     let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST])
+        .allow_methods([Method::GET, Method::POST, Method::DELETE])
         .allow_origin([
             "chrome-extension://*".parse().unwrap(),
             "moz-extension://*".parse().unwrap(),
@@ -48,9 +119,27 @@ async fn start_server(app_handle: AppHandle) {
 
     let state = AppState { app_handle };
 
+    // Authenticated REST API for scripts; /health and /download stay open for the extension
+    let api = Router::new()
+        .route("/tasks", get(api_list_tasks).post(api_add_task))
+        .route("/tasks/:id", get(api_get_task).delete(api_remove_task))
+        .route("/tasks/:id/pause", post(api_pause_task))
+        .route("/tasks/:id/resume", post(api_resume_task))
+        .route("/tasks/:id/move", post(api_move_task))
+        .route("/tasks/:id/priority", post(api_set_task_priority))
+        .route("/queue", get(api_queue_status))
+        .route("/queue/pause", post(api_pause_queue))
+        .route("/queue/resume", post(api_resume_queue))
+        .route("/events", get(api_events))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_api_token,
+        ));
+
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/download", post(handle_download))
+        .nest("/api", api)
         .layer(cors)
         .with_state(state);
 
@@ -136,48 +225,411 @@ async fn handle_download(
         None
     };
 
-    let id = uuid::Uuid::new_v4().to_string();
-    let task = DownloadTask {
-        id: id.clone(),
-        url: payload.url.clone(),
-        title: "From Extension...".to_string(),
-        status: TaskStatus::Pending,
-        progress: 0.0,
-        speed: Some("-".to_string()),
-        eta: Some("-".to_string()),
-        path: String::new(), // Will be resolved by queue processor using settings
-        error_message: None,
-        added_at: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs(),
-        pid: None,
-        status_detail: Some("Queued via Browser Extension".to_string()),
-        eta_raw: None,
-        speed_raw: None,
-        total_size: None,
-        range: range_display,
-        format: options.format.clone(),
-        file_path: None,
-        scheduled_time: None,
-        retry_count: Some(0),
-        ytdlp_command: None,
-        file_size: None,
-        completed_at: None,
-        options,
-    };
-
-    queue_state.add_task(task, &state.app_handle);
-    log::info!("Extension task added to queue successfully.");
-
-    // Notify UI that a new task was added (for visual feedback only)
-    let _ = state.app_handle.emit("extension-download-added", &id);
-
-    // Bring window to front
-    if let Some(window) = state.app_handle.get_webview_window("main") {
-        let _ = window.show();
-        let _ = window.set_focus();
-    }
+    let mut task = DownloadTask::new_pending(payload.url.clone(), options);
+    task.title = "From Extension...".to_string();
+    task.status_detail = Some("Queued via Browser Extension".to_string());
+    task.range = range_display;
+    let id = task.id.clone();
+
+    // The extension gives up after 2s and falls back to the deep link, which
+    // would add the task a second time, so answer before the duplicate check
+    // (which may probe the URL with yt-dlp). Its outcome reaches the UI as events.
+    let app_handle = state.app_handle.clone();
+    let queue = queue_state.inner().clone();
+    let url = payload.url;
+    let policy = payload.duplicate_policy;
+    tauri::async_runtime::spawn(async move {
+        let result = enqueue_with_policy(&app_handle, &queue, task, policy).await;
+        if result.status == EnqueueStatus::Skipped {
+            log::info!("Extension download skipped as duplicate: {}", url);
+            let _ = app_handle.emit("extension-download-duplicate", &result);
+            return;
+        }
+        log::info!("Extension task added to queue successfully.");
+
+        // Notify UI that a new task was added (for visual feedback only)
+        let _ = app_handle.emit("extension-download-added", &id);
+
+        // Bring window to front
+        if let Some(window) = app_handle.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    });
 
     (StatusCode::OK, "Download queued").into_response()
 }
+
+// === LOCAL REST API ===
+
+fn api_error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(serde_json::json!({ "error": message.into() }))).into_response()
+}
+
+/// Constant-time comparison so the token can't be guessed byte by byte via timing.
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+async fn require_api_token(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let expected = match state.app_handle.try_state::<ApiToken>() {
+        Some(t) => t.0.read().unwrap_or_else(|e| e.into_inner()).clone(),
+        None => return api_error(StatusCode::SERVICE_UNAVAILABLE, "API not initialized"),
+    };
+
+    // EventSource can't set headers, so `?token=` is accepted as a fallback
+    let provided = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim().to_string())
+        .or_else(|| {
+            req.uri().query().and_then(|q| {
+                q.split('&')
+                    .find_map(|pair| pair.strip_prefix("token="))
+                    .map(String::from)
+            })
+        });
+
+    match provided {
+        Some(token) if !expected.is_empty() && tokens_match(&token, &expected) => {
+            next.run(req).await
+        }
+        _ => {
+            log::warn!(
+                "[API] Rejected unauthenticated request to {}",
+                req.uri().path()
+            );
+            api_error(StatusCode::UNAUTHORIZED, "Missing or invalid API token")
+        }
+    }
+}
+
+fn queue_state(state: &AppState) -> Result<Arc<QueueState>, Response> {
+    state
+        .app_handle
+        .try_state::<Arc<QueueState>>()
+        .map(|qs| qs.inner().clone())
+        .ok_or_else(|| api_error(StatusCode::SERVICE_UNAVAILABLE, "Queue not initialized"))
+}
+
+fn ordered_tasks(queue: &QueueState) -> Vec<DownloadTask> {
+    let tasks = queue.tasks.lock().unwrap_or_else(|e| e.into_inner());
+    let order = queue.queue_order.lock().unwrap_or_else(|e| e.into_inner());
+    order
+        .iter()
+        .filter_map(|id| tasks.get(id).cloned())
+        .collect()
+}
+
+async fn api_list_tasks(State(state): State<AppState>) -> Response {
+    match queue_state(&state) {
+        Ok(queue) => Json(ordered_tasks(&queue)).into_response(),
+        Err(resp) => resp,
+    }
+}
+
+async fn api_get_task(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let queue = match queue_state(&state) {
+        Ok(q) => q,
+        Err(resp) => return resp,
+    };
+    let task = queue
+        .tasks
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&id)
+        .cloned();
+    match task {
+        Some(t) => Json(t).into_response(),
+        None => api_error(StatusCode::NOT_FOUND, "Task not found"),
+    }
+}
+
+async fn api_add_task(
+    State(state): State<AppState>,
+    Json(payload): Json<ApiAddTaskRequest>,
+) -> Response {
+    let queue = match queue_state(&state) {
+        Ok(q) => q,
+        Err(resp) => return resp,
+    };
+
+    if let Some(sites) = state
+        .app_handle
+        .try_state::<Arc<crate::ytdlp::SupportedSites>>()
+    {
+        if !sites.matches(&payload.url) {
+            return api_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "URL not supported. This site is not in the yt-dlp supported sites list.",
+            );
+        }
+    }
+
+    // Arbitrary shell commands stay a desktop-only feature
+    if payload
+        .follow_ups
+        .iter()
+        .any(|a| matches!(a, FollowUpAction::RunCommand { .. }))
+    {
+        return api_error(
+            StatusCode::FORBIDDEN,
+            "runCommand follow-ups can only be added from the app",
+        );
+    }
+
+    // yt-dlp writes to the archive file, so API clients must not choose its path;
+    // subscriptions set it from inside the app
+    let options = YtDlpOptions {
+        download_archive: None,
+        ..payload.options
+    };
+    let mut task = DownloadTask::new_pending(payload.url, options);
+    task.status_detail = Some("Queued via API".to_string());
+    task.priority = payload.priority;
+    task.follow_ups = payload.follow_ups;
+    let id = task.id.clone();
+    let url = task.url.clone();
+
+    let result =
+        enqueue_with_policy(&state.app_handle, &queue, task, payload.duplicate_policy).await;
+    if result.status == EnqueueStatus::Skipped {
+        log::info!("[API] Duplicate skipped: {}", url);
+        return (StatusCode::CONFLICT, Json(result)).into_response();
+    }
+
+    log::info!("[API] Task added to queue: {} (ID: {})", url, id);
+    (
+        StatusCode::CREATED,
+        Json(serde_json::json!({ "id": id, "result": result })),
+    )
+        .into_response()
+}
+
+async fn api_remove_task(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let queue = match queue_state(&state) {
+        Ok(q) => q,
+        Err(resp) => return resp,
+    };
+    match crate::commands::queue::remove_task_with_cleanup(&queue, &state.app_handle, &id).await {
+        Some(_) => {
+            log::info!("[API] Task removed: {}", id);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        None => api_error(StatusCode::NOT_FOUND, "Task not found"),
+    }
+}
+
+async fn api_pause_task(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let queue = match queue_state(&state) {
+        Ok(q) => q,
+        Err(resp) => return resp,
+    };
+    match queue.pause_task(&id, &state.app_handle) {
+        Ok(()) => {
+            log::info!("[API] Task paused: {}", id);
+            crate::commands::queue::schedule_hard_pause(queue, state.app_handle.clone(), id);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => api_error(StatusCode::CONFLICT, e),
+    }
+}
+
+async fn api_resume_task(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let queue = match queue_state(&state) {
+        Ok(q) => q,
+        Err(resp) => return resp,
+    };
+    match queue.resume_task(&id, &state.app_handle) {
+        Ok(()) => {
+            log::info!("[API] Task resumed: {}", id);
+            queue.notify.notify_one();
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => api_error(StatusCode::NOT_FOUND, e),
+    }
+}
+
+async fn api_move_task(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<ApiMoveTaskRequest>,
+) -> Response {
+    let queue = match queue_state(&state) {
+        Ok(q) => q,
+        Err(resp) => return resp,
+    };
+    let index = match (payload.position, payload.index) {
+        (Some(ApiMovePosition::Top), _) => 0,
+        (Some(ApiMovePosition::Bottom), _) => usize::MAX,
+        (None, Some(index)) => index,
+        (None, None) => {
+            return api_error(
+                StatusCode::BAD_REQUEST,
+                "Expected \"index\" or \"position\"",
+            )
+        }
+    };
+    match queue.move_task(&id, index, &state.app_handle) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => api_error(StatusCode::NOT_FOUND, e),
+    }
+}
+
+async fn api_set_task_priority(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<ApiPriorityRequest>,
+) -> Response {
+    let queue = match queue_state(&state) {
+        Ok(q) => q,
+        Err(resp) => return resp,
+    };
+    match queue.set_task_priority(&id, payload.priority, &state.app_handle) {
+        Ok(()) => {
+            log::info!("[API] Task {} priority set to {:?}", id, payload.priority);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => api_error(StatusCode::NOT_FOUND, e),
+    }
+}
+
+async fn api_queue_status(State(state): State<AppState>) -> Response {
+    let queue = match queue_state(&state) {
+        Ok(q) => q,
+        Err(resp) => return resp,
+    };
+    let tasks = ordered_tasks(&queue);
+    let active = tasks
+        .iter()
+        .filter(|t| {
+            matches!(
+                t.status,
+                TaskStatus::Downloading
+                    | TaskStatus::FetchingInfo
+                    | TaskStatus::Processing
+                    | TaskStatus::Queued
+            )
+        })
+        .count();
+    let pending = tasks
+        .iter()
+        .filter(|t| t.status == TaskStatus::Pending)
+        .count();
+
+    Json(serde_json::json!({
+        "paused": queue.paused.load(std::sync::atomic::Ordering::SeqCst),
+        "total": tasks.len(),
+        "active": active,
+        "pending": pending,
+    }))
+    .into_response()
+}
+
+async fn api_pause_queue(State(state): State<AppState>) -> Response {
+    match queue_state(&state) {
+        Ok(queue) => {
+            log::info!("[API] Queue paused");
+            let suspended = queue.pause_all(&state.app_handle);
+            Json(serde_json::json!({ "paused": true, "affected": suspended })).into_response()
+        }
+        Err(resp) => resp,
+    }
+}
+
+async fn api_resume_queue(State(state): State<AppState>) -> Response {
+    match queue_state(&state) {
+        Ok(queue) => {
+            log::info!("[API] Queue resumed");
+            let resumed = queue.resume_all(&state.app_handle);
+            Json(serde_json::json!({ "paused": false, "affected": resumed })).into_response()
+        }
+        Err(resp) => resp,
+    }
+}
+
+/// Keeps only the parts of an event relevant to `task_id` (None = everything).
+fn filter_event(event: QueueEvent, task_id: Option<&str>) -> Option<QueueEvent> {
+    let Some(wanted) = task_id else {
+        return Some(event);
+    };
+    match event {
+        QueueEvent::QueueUpdate { tasks } => Some(QueueEvent::QueueUpdate {
+            tasks: tasks.into_iter().filter(|t| t.id == wanted).collect(),
+        }),
+        QueueEvent::TaskProgress { ref task_id, .. }
+        | QueueEvent::TaskOutput { ref task_id, .. }
+            if task_id != wanted =>
+        {
+            None
+        }
+        other => Some(other),
+    }
+}
+
+fn to_sse_event(event: &QueueEvent) -> Event {
+    Event::default()
+        .event(event.name())
+        .json_data(event)
+        .unwrap_or_else(|_| Event::default().comment("serialization error"))
+}
+
+/// Server-Sent Events stream of `queue_update`, `task_progress` and `task_output`,
+/// with the JSON payloads described on `QueueEvent`. Starts with a `queue_update`
+/// snapshot so clients don't need a separate fetch.
+async fn api_events(
+    State(state): State<AppState>,
+    Query(query): Query<ApiEventsQuery>,
+) -> Response {
+    let queue = match queue_state(&state) {
+        Ok(q) => q,
+        Err(resp) => return resp,
+    };
+
+    let rx = queue.events.subscribe();
+    let snapshot = filter_event(
+        QueueEvent::QueueUpdate {
+            tasks: ordered_tasks(&queue),
+        },
+        query.task_id.as_deref(),
+    );
+
+    log::info!(
+        "[API] Event stream opened (filter: {})",
+        query.task_id.as_deref().unwrap_or("all")
+    );
+
+    let stream = futures::stream::unfold(
+        (rx, snapshot, query.task_id),
+        |(mut rx, mut pending, task_id)| async move {
+            if let Some(event) = pending.take() {
+                let sse = to_sse_event(&event);
+                return Some((Ok::<_, std::convert::Infallible>(sse), (rx, None, task_id)));
+            }
+            loop {
+                match rx.recv().await {
+                    Ok(event) => {
+                        if let Some(event) = filter_event(event, task_id.as_deref()) {
+                            let sse = to_sse_event(&event);
+                            return Some((Ok(sse), (rx, None, task_id)));
+                        }
+                    }
+                    // Slow client: drop the missed events, the next queue_update resyncs it
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        },
+    );
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
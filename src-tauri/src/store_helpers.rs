@@ -86,3 +86,18 @@ pub fn set_settings_value(app: &AppHandle, key: &str, value: Value) -> Result<()
     }
     Err("Failed to set nested settings value".to_string())
 }
+
+/// Reads the Zustand `state` object straight from a `settings.json` file.
+/// Used by the headless CLI, which runs without the store plugin.
+pub fn read_state_from_file(path: &std::path::Path) -> Option<Value> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let root: Value = serde_json::from_str(&content).ok()?;
+    let store_val = root.get("app-storage-v5-clean")?;
+
+    if let Some(json_str) = store_val.as_str() {
+        let parsed = serde_json::from_str::<Value>(json_str).ok()?;
+        parsed.get("state").cloned()
+    } else {
+        store_val.get("state").cloned()
+    }
+}
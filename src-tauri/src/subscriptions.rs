@@ -0,0 +1,488 @@
+use crate::download_queue::{
+    with_suffix, write_atomic, DownloadTask, QueueState, TaskGroup, TaskStatus,
+};
+use crate::playlist::PlaylistEntry;
+use crate::ytdlp::YtDlpOptions;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+const DEFAULT_INTERVAL_HOURS: u32 = 24;
+
+fn read_subscriptions(path: &Path) -> Result<Option<Vec<Subscription>>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+/// A channel/playlist that is periodically re-checked for new uploads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub options: YtDlpOptions,
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub created_at: u64,
+    pub last_sync_at: Option<u64>,
+    pub last_error: Option<String>,
+    pub last_new_count: Option<usize>,
+}
+
+fn default_interval_hours() -> u32 {
+    DEFAULT_INTERVAL_HOURS
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Subscription {
+    pub fn is_due(&self, now: u64) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        match self.last_sync_at {
+            Some(last) => now >= last + self.interval_hours.max(1) as u64 * 3600,
+            None => true,
+        }
+    }
+}
+
+pub struct SubscriptionState {
+    pub subscriptions: Arc<Mutex<Vec<Subscription>>>,
+    /// IDs currently being synced, so the processor never runs two syncs of the same feed
+    pub syncing: Arc<Mutex<HashSet<String>>>,
+    pub persistence_path: Option<PathBuf>,
+    pub archive_dir: Option<PathBuf>,
+}
+
+impl SubscriptionState {
+    pub fn new(app_handle: Option<AppHandle>) -> Self {
+        let data_dir = app_handle
+            .as_ref()
+            .and_then(|app| app.path().app_data_dir().ok());
+
+        let state = Self {
+            subscriptions: Arc::new(Mutex::new(Vec::new())),
+            syncing: Arc::new(Mutex::new(HashSet::new())),
+            persistence_path: data_dir.as_ref().map(|dir| dir.join("subscriptions.json")),
+            archive_dir: data_dir.map(|dir| dir.join("archives")),
+        };
+        state.load();
+        state
+    }
+
+    fn load(&self) {
+        let Some(path) = &self.persistence_path else {
+            return;
+        };
+        let data = match read_subscriptions(path) {
+            Ok(Some(data)) => data,
+            primary => {
+                if let Err(e) = &primary {
+                    log::error!("[Subscriptions] {} is unreadable: {}", path.display(), e);
+                    // Move it aside, otherwise the next save would rotate it over the good backup
+                    let stamp = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs();
+                    let _ = fs::rename(path, with_suffix(path, &format!(".corrupt-{}", stamp)));
+                }
+                let backup = with_suffix(path, ".bak");
+                match read_subscriptions(&backup) {
+                    Ok(Some(data)) => {
+                        log::warn!("[Subscriptions] Restored from {}", backup.display());
+                        data
+                    }
+                    Ok(None) => return,
+                    Err(e) => {
+                        log::error!("[Subscriptions] Backup is unreadable too: {}", e);
+                        return;
+                    }
+                }
+            }
+        };
+        log::info!("[Subscriptions] Loaded {} subscriptions", data.len());
+        *self.subscriptions.lock().unwrap_or_else(|e| e.into_inner()) = data;
+    }
+
+    pub fn save_now(&self) {
+        if let Some(path) = &self.persistence_path {
+            let subs = self.subscriptions.lock().unwrap_or_else(|e| e.into_inner());
+            if let Ok(json) = serde_json::to_vec_pretty(&*subs) {
+                if let Err(e) = write_atomic(path, &json) {
+                    log::error!("[Subscriptions] Failed to save: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Location of the yt-dlp `--download-archive` file for a subscription.
+    pub fn archive_path(&self, id: &str) -> Option<PathBuf> {
+        self.archive_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.txt", id)))
+    }
+
+    pub fn list(&self) -> Vec<Subscription> {
+        self.subscriptions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Subscription> {
+        self.subscriptions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|s| s.id == id)
+            .cloned()
+    }
+
+    pub fn add(&self, sub: Subscription) {
+        self.subscriptions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(sub);
+        self.save_now();
+    }
+
+    pub fn update<F>(&self, id: &str, f: F) -> Result<Subscription, String>
+    where
+        F: FnOnce(&mut Subscription),
+    {
+        let updated = {
+            let mut subs = self.subscriptions.lock().unwrap_or_else(|e| e.into_inner());
+            let sub = subs
+                .iter_mut()
+                .find(|s| s.id == id)
+                .ok_or_else(|| "Subscription not found".to_string())?;
+            f(sub);
+            sub.clone()
+        };
+        self.save_now();
+        Ok(updated)
+    }
+
+    pub fn remove(&self, id: &str, delete_archive: bool) -> Option<Subscription> {
+        let removed = {
+            let mut subs = self.subscriptions.lock().unwrap_or_else(|e| e.into_inner());
+            let pos = subs.iter().position(|s| s.id == id)?;
+            subs.remove(pos)
+        };
+        if delete_archive {
+            if let Some(path) = self.archive_path(id) {
+                let _ = fs::remove_file(path);
+            }
+        }
+        self.save_now();
+        Some(removed)
+    }
+
+    fn try_begin_sync(&self, id: &str) -> bool {
+        self.syncing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.to_string())
+    }
+
+    fn end_sync(&self, id: &str) {
+        self.syncing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+    }
+}
+
+/// Reads a yt-dlp download archive (`<extractor> <id>` per line) into a set.
+pub fn read_archive(path: &Path) -> HashSet<String> {
+    fs::read_to_string(path)
+        .map(|content| {
+            content
+                .lines()
+                .map(|l| l.trim())
+                .filter(|l| !l.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The key yt-dlp writes to the archive for an entry, if it can be derived.
+pub fn archive_key(entry: &PlaylistEntry) -> Option<String> {
+    match (&entry.ie_key, &entry.id) {
+        // yt-dlp lowercases the extractor key but keeps the (case-sensitive) video ID
+        (Some(ie), Some(id)) => Some(format!("{} {}", ie.to_lowercase(), id)),
+        _ => None,
+    }
+}
+
+/// Keeps only entries that are neither archived nor already waiting in the queue.
+pub fn filter_new_entries(
+    entries: Vec<PlaylistEntry>,
+    archive: &HashSet<String>,
+    queued_urls: &HashSet<String>,
+) -> Vec<PlaylistEntry> {
+    entries
+        .into_iter()
+        .filter(|e| !archive_key(e).map_or(false, |k| archive.contains(&k)))
+        .filter(|e| !queued_urls.contains(&e.url))
+        .collect()
+}
+
+/// Expands the subscription URL and enqueues every entry not seen before.
+/// Returns the number of newly queued tasks.
+pub async fn sync_subscription(
+    app: &AppHandle,
+    queue: &QueueState,
+    subs: &SubscriptionState,
+    id: &str,
+) -> Result<usize, String> {
+    let sub = subs
+        .get(id)
+        .ok_or_else(|| "Subscription not found".to_string())?;
+    let archive_path = subs
+        .archive_path(id)
+        .ok_or_else(|| "App data directory unavailable".to_string())?;
+
+    if let Some(parent) = archive_path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    log::info!("[Subscriptions] Syncing \"{}\" ({})", sub.title, sub.url);
+
+    let settings = crate::ytdlp::load_settings(app);
+    let ytdlp_path = crate::ytdlp::resolve_ytdlp_path(app, &settings.binary_path_yt_dlp);
+    let info = crate::playlist::fetch_playlist(&ytdlp_path, &sub.url, &settings).await?;
+
+    let archive = read_archive(&archive_path);
+    let queued_urls: HashSet<String> = {
+        let tasks = queue.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks
+            .values()
+            .filter(|t| t.group_id.as_deref() == Some(id))
+            .filter(|t| !matches!(t.status, TaskStatus::Error | TaskStatus::Stopped))
+            .map(|t| t.url.clone())
+            .collect()
+    };
+
+    let new_entries = filter_new_entries(info.entries, &archive, &queued_urls);
+    let count = new_entries.len();
+
+    if count > 0 {
+        let mut options = sub.options.clone();
+        options.download_archive = Some(archive_path.to_string_lossy().to_string());
+
+        let children: Vec<DownloadTask> = new_entries
+            .into_iter()
+            .map(|entry| {
+                let mut task = DownloadTask::new_pending(entry.url, options.clone());
+                if let Some(t) = entry.title {
+                    task.title = t;
+                }
+                task.playlist_index = Some(entry.index);
+                task.video_id = entry.id;
+                task.extractor = entry.ie_key;
+                task
+            })
+            .collect();
+
+        let group = TaskGroup {
+            id: sub.id.clone(),
+            title: sub.title.clone(),
+            url: sub.url.clone(),
+            added_at: sub.created_at,
+        };
+        queue.add_group(group, children, app);
+    }
+
+    // Keep the stored title in sync with the channel name once known
+    let _ = subs.update(id, |s| {
+        if s.title == s.url && !info.title.is_empty() {
+            s.title = info.title.clone();
+        }
+    });
+
+    log::info!(
+        "[Subscriptions] \"{}\" synced: {} new entries",
+        sub.title,
+        count
+    );
+    Ok(count)
+}
+
+/// Runs a sync and records its outcome on the subscription.
+pub async fn run_sync(
+    app: AppHandle,
+    queue: Arc<QueueState>,
+    subs: Arc<SubscriptionState>,
+    id: String,
+) -> Result<usize, String> {
+    if !subs.try_begin_sync(&id) {
+        return Err("Subscription is already syncing".to_string());
+    }
+
+    let result = sync_subscription(&app, &queue, &subs, &id).await;
+    subs.end_sync(&id);
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let _ = subs.update(&id, |s| {
+        s.last_sync_at = Some(now);
+        match &result {
+            Ok(count) => {
+                s.last_error = None;
+                s.last_new_count = Some(*count);
+            }
+            Err(e) => {
+                log::warn!("[Subscriptions] Sync failed for {}: {}", s.url, e);
+                s.last_error = Some(e.clone());
+            }
+        }
+    });
+    let _ = app.emit("subscriptions_update", subs.list());
+
+    result
+}
+
+/// Called from the queue processor tick: spawns a sync for every due subscription.
+pub fn spawn_due_syncs(app: &AppHandle, queue: &Arc<QueueState>) {
+    let Some(subs) = app.try_state::<Arc<SubscriptionState>>() else {
+        return;
+    };
+    let subs = subs.inner().clone();
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let due: Vec<String> = {
+        let syncing = subs.syncing.lock().unwrap_or_else(|e| e.into_inner());
+        subs.list()
+            .into_iter()
+            .filter(|s| s.is_due(now) && !syncing.contains(&s.id))
+            .map(|s| s.id)
+            .collect()
+    };
+
+    for id in due {
+        let app = app.clone();
+        let queue = queue.clone();
+        let subs = subs.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = run_sync(app, queue, subs, id).await;
+        });
+    }
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(index: u32, id: &str) -> PlaylistEntry {
+        PlaylistEntry {
+            index,
+            id: Some(id.to_string()),
+            url: format!("https://www.youtube.com/watch?v={}", id),
+            title: None,
+            duration: None,
+            uploader: None,
+            ie_key: Some("Youtube".to_string()),
+        }
+    }
+
+    #[test]
+    fn filters_archived_and_queued_entries() {
+        let archive: HashSet<String> = ["youtube aaa".to_string()].into_iter().collect();
+        let queued: HashSet<String> = ["https://www.youtube.com/watch?v=bbb".to_string()]
+            .into_iter()
+            .collect();
+
+        let fresh = filter_new_entries(
+            vec![entry(1, "aaa"), entry(2, "bbb"), entry(3, "ccc")],
+            &archive,
+            &queued,
+        );
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].id.as_deref(), Some("ccc"));
+    }
+
+    #[test]
+    fn subscription_due_respects_interval() {
+        let mut sub = Subscription {
+            id: "s".to_string(),
+            url: "https://example.com/c".to_string(),
+            title: "c".to_string(),
+            options: YtDlpOptions::default(),
+            interval_hours: 6,
+            enabled: true,
+            created_at: 0,
+            last_sync_at: None,
+            last_error: None,
+            last_new_count: None,
+        };
+        assert!(sub.is_due(1_000));
+
+        sub.last_sync_at = Some(1_000);
+        assert!(!sub.is_due(1_000 + 5 * 3600));
+        assert!(sub.is_due(1_000 + 6 * 3600));
+
+        sub.enabled = false;
+        assert!(!sub.is_due(1_000 + 48 * 3600));
+    }
+
+    #[test]
+    fn truncated_file_falls_back_to_backup() {
+        let dir = std::env::temp_dir().join(format!("sceneclip-subs-{}", uuid::Uuid::new_v4()));
+        let state = SubscriptionState {
+            subscriptions: Arc::new(Mutex::new(Vec::new())),
+            syncing: Arc::new(Mutex::new(HashSet::new())),
+            persistence_path: Some(dir.join("subscriptions.json")),
+            archive_dir: None,
+        };
+        let sub = |id: &str| Subscription {
+            id: id.to_string(),
+            url: format!("https://example.com/{}", id),
+            title: id.to_string(),
+            options: YtDlpOptions::default(),
+            interval_hours: 24,
+            enabled: true,
+            created_at: 0,
+            last_sync_at: None,
+            last_error: None,
+            last_new_count: None,
+        };
+        state.add(sub("a"));
+        state.add(sub("b"));
+
+        // What a crash in the middle of a plain `fs::write` leaves behind
+        let path = state.persistence_path.clone().unwrap();
+        fs::write(&path, "[{\"id\": \"a\", \"url\"").unwrap();
+        state.load();
+        let ids: Vec<String> = state.list().into_iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec!["a".to_string()]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,565 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// `[Script Info]`/`[V4+ Styles]` used when writing ASS from SRT or VTT.
+const DEFAULT_ASS_HEADER: &str = "[Script Info]
+ScriptType: v4.00+
+PlayResX: 384
+PlayResY: 288
+ScaledBorderAndShadow: yes
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,16,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,0,0,0,0,100,100,0,0,1,1,0,2,10,10,10,1
+";
+
+const ASS_EVENT_FORMAT: &str =
+    "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+    Ass,
+}
+
+impl SubtitleFormat {
+    /// Format from the file extension (`.ssa` is read as ASS).
+    pub fn from_path(path: &str) -> Option<Self> {
+        let ext = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())?
+            .to_lowercase();
+        match ext.as_str() {
+            "srt" => Some(SubtitleFormat::Srt),
+            "vtt" => Some(SubtitleFormat::Vtt),
+            "ass" | "ssa" => Some(SubtitleFormat::Ass),
+            _ => None,
+        }
+    }
+}
+
+/// One caption. `text` uses `\n` for line breaks in every format; ASS
+/// override tags (`{\i1}`) and SRT/VTT markup (`<i>`) are kept as written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cue {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    /// ASS style name; `Default` when written without one.
+    #[serde(default)]
+    pub style: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitleTrack {
+    pub format: SubtitleFormat,
+    pub cues: Vec<Cue>,
+    /// Everything before `[Events]` of an ASS file, kept so a round trip
+    /// keeps the styles.
+    #[serde(default)]
+    pub header: Option<String>,
+}
+
+/// `HH:MM:SS.mmm`, `MM:SS,mmm`, ASS `H:MM:SS.cc` or plain seconds.
+pub fn parse_timestamp(value: &str) -> Option<f64> {
+    let value = value.trim().replace(',', ".");
+    let parts: Vec<&str> = value.split(':').collect();
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+    let mut secs = 0.0;
+    for part in &parts {
+        let n: f64 = part.trim().parse().ok()?;
+        if n < 0.0 {
+            return None;
+        }
+        secs = secs * 60.0 + n;
+    }
+    Some(secs)
+}
+
+/// Whole milliseconds split into (h, m, s, ms).
+fn split_millis(secs: f64) -> (u64, u64, u64, u64) {
+    let total = (secs.max(0.0) * 1000.0).round() as u64;
+    (
+        total / 3_600_000,
+        total / 60_000 % 60,
+        total / 1000 % 60,
+        total % 1000,
+    )
+}
+
+fn srt_time(secs: f64) -> String {
+    let (h, m, s, ms) = split_millis(secs);
+    format!("{:02}:{:02}:{:02},{:03}", h, m, s, ms)
+}
+
+fn vtt_time(secs: f64) -> String {
+    let (h, m, s, ms) = split_millis(secs);
+    format!("{:02}:{:02}:{:02}.{:03}", h, m, s, ms)
+}
+
+fn ass_time(secs: f64) -> String {
+    let (h, m, s, ms) = split_millis(secs);
+    format!("{}:{:02}:{:02}.{:02}", h, m, s, ms / 10)
+}
+
+/// Drops YouTube's karaoke timing tags (`<00:00:01.500>`, `<c>`) from VTT text.
+fn strip_vtt_timing_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('<') {
+        out.push_str(&rest[..open]);
+        match rest[open..].find('>') {
+            Some(close) => {
+                let tag = &rest[open + 1..open + close];
+                let is_timing = tag.starts_with(|c: char| c.is_ascii_digit())
+                    || tag == "c"
+                    || tag.starts_with("c.")
+                    || tag == "/c";
+                if !is_timing {
+                    out.push_str(&rest[open..=open + close]);
+                }
+                rest = &rest[open + close + 1..];
+            }
+            None => {
+                out.push_str(&rest[open..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// SRT and VTT share the block layout: optional identifier, a `-->` timing
+/// line, then text until a blank line. Blocks without timing (the `WEBVTT`
+/// header, `NOTE`, `STYLE`) are skipped.
+fn parse_blocks(content: &str, vtt: bool) -> Vec<Cue> {
+    let mut cues = Vec::new();
+    for block in content.split("\n\n") {
+        let lines: Vec<&str> = block.lines().collect();
+        let Some(timing_idx) = lines.iter().position(|l| l.contains("-->")) else {
+            continue;
+        };
+        let (start, end) = match lines[timing_idx].split_once("-->") {
+            Some((s, e)) => (s, e.split_whitespace().next().unwrap_or("")),
+            None => continue,
+        };
+        let (Some(start), Some(end)) = (parse_timestamp(start), parse_timestamp(end)) else {
+            continue;
+        };
+        let text = lines[timing_idx + 1..].join("\n");
+        let text = if vtt {
+            strip_vtt_timing_tags(&text)
+        } else {
+            text
+        };
+        cues.push(Cue {
+            start,
+            end,
+            text,
+            style: None,
+        });
+    }
+    cues
+}
+
+fn parse_ass(content: &str) -> Result<(String, Vec<Cue>), String> {
+    let events_at = content
+        .find("[Events]")
+        .ok_or("Not an ASS file: [Events] section missing")?;
+    let header = content[..events_at].trim_end().to_string();
+
+    let mut fields: Vec<String> = Vec::new();
+    let mut cues = Vec::new();
+    for line in content[events_at..].lines().skip(1) {
+        let line = line.trim_start();
+        if line.starts_with('[') {
+            break;
+        }
+        if let Some(format) = line.strip_prefix("Format:") {
+            fields = format.split(',').map(|f| f.trim().to_lowercase()).collect();
+        } else if let Some(dialogue) = line.strip_prefix("Dialogue:") {
+            if fields.is_empty() {
+                fields = ASS_EVENT_FORMAT["Format:".len()..]
+                    .split(',')
+                    .map(|f| f.trim().to_lowercase())
+                    .collect();
+            }
+            // Text is last and may itself contain commas
+            let values: Vec<&str> = dialogue.trim_start().splitn(fields.len(), ',').collect();
+            let get = |name: &str| {
+                fields
+                    .iter()
+                    .position(|f| f == name)
+                    .and_then(|i| values.get(i).copied())
+            };
+            let (Some(start), Some(end)) = (
+                get("start").and_then(parse_timestamp),
+                get("end").and_then(parse_timestamp),
+            ) else {
+                continue;
+            };
+            cues.push(Cue {
+                start,
+                end,
+                text: get("text")
+                    .unwrap_or("")
+                    .replace("\\N", "\n")
+                    .replace("\\n", "\n"),
+                style: get("style").map(|s| s.trim().to_string()),
+            });
+        }
+    }
+    Ok((header, cues))
+}
+
+/// `{...}` override blocks removed, for SRT/VTT output.
+fn strip_ass_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut depth = 0;
+    for c in text.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            _ if depth == 0 => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Basic `<i>`/`<b>`/`<u>` markup turned into ASS overrides; other tags dropped.
+fn markup_to_ass(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('<') {
+        out.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            out.push_str(&rest[open..]);
+            rest = "";
+            break;
+        };
+        let tag = rest[open + 1..open + close].to_lowercase();
+        match tag.as_str() {
+            "i" | "b" | "u" => out.push_str(&format!("{{\\{}1}}", tag)),
+            "/i" | "/b" | "/u" => out.push_str(&format!("{{\\{}0}}", &tag[1..])),
+            _ => {}
+        }
+        rest = &rest[open + close + 1..];
+    }
+    out.push_str(rest);
+    out.replace('\n', "\\N")
+}
+
+/// Look of burned-in captions, applied over the file's own styles.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitleStyle {
+    pub font_name: Option<String>,
+    pub font_size: Option<u32>,
+    /// `#RRGGBB`
+    pub color: Option<String>,
+    pub outline_color: Option<String>,
+    pub outline: Option<f64>,
+    #[serde(default)]
+    pub bold: bool,
+    /// Captions at the top instead of the bottom.
+    #[serde(default)]
+    pub top: bool,
+    pub margin_v: Option<u32>,
+}
+
+/// `#RRGGBB` as an ASS `&HBBGGRR&` colour.
+fn ass_colour(hex: &str) -> Option<String> {
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(format!(
+        "&H{}{}{}&",
+        &hex[4..6].to_uppercase(),
+        &hex[2..4].to_uppercase(),
+        &hex[0..2].to_uppercase()
+    ))
+}
+
+impl SubtitleStyle {
+    /// The `force_style` value for ffmpeg's `subtitles` filter.
+    pub fn force_style(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(font) = &self.font_name {
+            // Quotes and separators would end the filter option early
+            let font: String = font
+                .chars()
+                .filter(|c| !matches!(c, '\'' | ',' | ':' | '\\' | '=' | '[' | ']' | ';'))
+                .collect();
+            if !font.trim().is_empty() {
+                parts.push(format!("Fontname={}", font.trim()));
+            }
+        }
+        if let Some(size) = self.font_size {
+            parts.push(format!("Fontsize={}", size));
+        }
+        if let Some(colour) = self.color.as_deref().and_then(ass_colour) {
+            parts.push(format!("PrimaryColour={}", colour));
+        }
+        if let Some(colour) = self.outline_color.as_deref().and_then(ass_colour) {
+            parts.push(format!("OutlineColour={}", colour));
+        }
+        if let Some(outline) = self.outline {
+            parts.push(format!("Outline={}", outline.max(0.0)));
+        }
+        if self.bold {
+            parts.push("Bold=-1".to_string());
+        }
+        if self.top {
+            parts.push("Alignment=8".to_string());
+        }
+        if let Some(margin) = self.margin_v {
+            parts.push(format!("MarginV={}", margin));
+        }
+        parts.join(",")
+    }
+}
+
+/// A path escaped for use as a filter option inside `-vf` (both the option
+/// and the filtergraph level, see ffmpeg-filters "Notes on filtergraph
+/// escaping"). Backslashes become slashes, which Windows also accepts.
+pub fn escape_filter_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len() + 8);
+    for c in path.chars() {
+        match c {
+            '\\' => out.push('/'),
+            ':' => out.push_str("\\\\:"),
+            '\'' => out.push_str("\\\\\\'"),
+            ',' | '[' | ']' | ';' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// The `-vf` filter burning `subtitle_path` into the picture.
+pub fn burn_filter(subtitle_path: &str, style: Option<&SubtitleStyle>) -> String {
+    let mut filter = format!("subtitles=filename={}", escape_filter_path(subtitle_path));
+    if let Some(force) = style.map(|s| s.force_style()).filter(|f| !f.is_empty()) {
+        filter.push_str(&format!(":force_style='{}'", force));
+    }
+    filter
+}
+
+impl SubtitleTrack {
+    pub fn parse(content: &str, format: SubtitleFormat) -> Result<Self, String> {
+        let content = content
+            .trim_start_matches('\u{feff}')
+            .replace("\r\n", "\n")
+            .replace('\r', "\n");
+        let (header, cues) = match format {
+            SubtitleFormat::Srt => (None, parse_blocks(&content, false)),
+            SubtitleFormat::Vtt => (None, parse_blocks(&content, true)),
+            SubtitleFormat::Ass => {
+                let (header, cues) = parse_ass(&content)?;
+                (Some(header), cues)
+            }
+        };
+        let mut cues: Vec<Cue> = cues.into_iter().filter(|c| c.end > c.start).collect();
+        cues.sort_by(|a, b| a.start.total_cmp(&b.start));
+        Ok(Self {
+            format,
+            cues,
+            header,
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let format = SubtitleFormat::from_path(path)
+            .ok_or_else(|| format!("Unsupported subtitle format: {}", path))?;
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::parse(&String::from_utf8_lossy(&bytes), format)
+    }
+
+    /// Writes the track as `format`, converting markup on the way.
+    pub fn render(&self, format: SubtitleFormat) -> String {
+        let mut out = String::new();
+        match format {
+            SubtitleFormat::Srt | SubtitleFormat::Vtt => {
+                if format == SubtitleFormat::Vtt {
+                    out.push_str("WEBVTT\n\n");
+                }
+                for (i, cue) in self.cues.iter().enumerate() {
+                    let text = if self.format == SubtitleFormat::Ass {
+                        strip_ass_tags(&cue.text)
+                    } else {
+                        cue.text.clone()
+                    };
+                    if format == SubtitleFormat::Srt {
+                        out.push_str(&format!(
+                            "{}\n{} --> {}\n{}\n\n",
+                            i + 1,
+                            srt_time(cue.start),
+                            srt_time(cue.end),
+                            text
+                        ));
+                    } else {
+                        out.push_str(&format!(
+                            "{} --> {}\n{}\n\n",
+                            vtt_time(cue.start),
+                            vtt_time(cue.end),
+                            text
+                        ));
+                    }
+                }
+            }
+            SubtitleFormat::Ass => {
+                let header = match (&self.header, self.format) {
+                    (Some(h), SubtitleFormat::Ass) => h.as_str(),
+                    _ => DEFAULT_ASS_HEADER.trim_end(),
+                };
+                out.push_str(header);
+                out.push_str("\n\n[Events]\n");
+                out.push_str(ASS_EVENT_FORMAT);
+                out.push('\n');
+                for cue in &self.cues {
+                    let text = if self.format == SubtitleFormat::Ass {
+                        cue.text.replace('\n', "\\N")
+                    } else {
+                        markup_to_ass(&cue.text)
+                    };
+                    out.push_str(&format!(
+                        "Dialogue: 0,{},{},{},,0,0,0,,{}\n",
+                        ass_time(cue.start),
+                        ass_time(cue.end),
+                        cue.style.as_deref().unwrap_or("Default"),
+                        text
+                    ));
+                }
+            }
+        }
+        out
+    }
+
+    /// Writes to `path` in the format its extension names.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let format = SubtitleFormat::from_path(path)
+            .ok_or_else(|| format!("Unsupported subtitle format: {}", path))?;
+        if let Some(parent) = Path::new(path).parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        std::fs::write(path, self.render(format))
+            .map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+
+    /// Moves every cue by `secs`. Cues pushed entirely before zero are dropped.
+    pub fn shift(&mut self, secs: f64) {
+        for cue in &mut self.cues {
+            cue.start = (cue.start + secs).max(0.0);
+            cue.end += secs;
+        }
+        self.cues.retain(|c| c.end > c.start);
+    }
+
+    /// Keeps what is shown between `start` and `end` and rebases it to zero,
+    /// so the captions line up with a clip cut from the same range. Cues that
+    /// straddle a cut are shortened to the window.
+    pub fn clip(&mut self, start: f64, end: Option<f64>) {
+        let start = start.max(0.0);
+        let end = end.filter(|e| *e > start).unwrap_or(f64::INFINITY);
+        self.cues.retain(|c| c.end > start && c.start < end);
+        for cue in &mut self.cues {
+            cue.start = cue.start.max(start) - start;
+            cue.end = cue.end.min(end) - start;
+        }
+        self.cues.retain(|c| c.end > c.start);
+    }
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRT: &str = "\u{feff}1\r\n00:00:01,000 --> 00:00:03,500\r\n<i>Hello</i>\r\nthere\r\n\r\n2\r\n00:00:10,000 --> 00:00:12,000\r\nBye\r\n";
+
+    #[test]
+    fn parses_and_converts_between_formats() {
+        let track = SubtitleTrack::parse(SRT, SubtitleFormat::Srt).unwrap();
+        assert_eq!(track.cues.len(), 2);
+        assert_eq!(track.cues[0].text, "<i>Hello</i>\nthere");
+        assert_eq!(track.cues[0].end, 3.5);
+
+        let vtt = track.render(SubtitleFormat::Vtt);
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:01.000 --> 00:00:03.500\n"));
+        let ass = track.render(SubtitleFormat::Ass);
+        assert!(ass.contains("[V4+ Styles]"));
+        assert!(ass.contains(
+            "Dialogue: 0,0:00:01.00,0:00:03.50,Default,,0,0,0,,{\\i1}Hello{\\i0}\\Nthere\n"
+        ));
+
+        // ASS keeps its header and commas in the text survive the round trip
+        let back = SubtitleTrack::parse(&ass, SubtitleFormat::Ass).unwrap();
+        assert_eq!(back.cues[0].text, "{\\i1}Hello{\\i0}\nthere");
+        assert!(back
+            .render(SubtitleFormat::Srt)
+            .contains("\nHello\nthere\n"));
+
+        let vtt = "WEBVTT\nKind: captions\n\nNOTE hi\n\n00:01.000 --> 00:02.000 align:start\nA <00:00:01.500><c>word</c>, <b>bold</b>\n";
+        let track = SubtitleTrack::parse(vtt, SubtitleFormat::Vtt).unwrap();
+        assert_eq!(track.cues.len(), 1);
+        assert_eq!(track.cues[0].text, "A word, <b>bold</b>");
+    }
+
+    #[test]
+    fn clip_rebases_cues_to_the_window() {
+        let mut track = SubtitleTrack::parse(SRT, SubtitleFormat::Srt).unwrap();
+        track.clip(2.0, Some(11.0));
+        assert_eq!(track.cues.len(), 2);
+        assert_eq!((track.cues[0].start, track.cues[0].end), (0.0, 1.5));
+        assert_eq!((track.cues[1].start, track.cues[1].end), (8.0, 9.0));
+
+        let mut track = SubtitleTrack::parse(SRT, SubtitleFormat::Srt).unwrap();
+        track.clip(4.0, Some(9.0));
+        assert!(track.cues.is_empty());
+
+        let mut track = SubtitleTrack::parse(SRT, SubtitleFormat::Srt).unwrap();
+        track.shift(-2.0);
+        assert_eq!((track.cues[0].start, track.cues[0].end), (0.0, 1.5));
+        track.shift(-5.0);
+        assert_eq!(track.cues.len(), 1);
+    }
+
+    #[test]
+    fn timestamps_in_every_notation() {
+        assert_eq!(parse_timestamp("01:02:03,250"), Some(3723.25));
+        assert_eq!(parse_timestamp("0:00:05.50"), Some(5.5));
+        assert_eq!(parse_timestamp("1:30"), Some(90.0));
+        assert_eq!(parse_timestamp("42"), Some(42.0));
+        assert_eq!(parse_timestamp("abc"), None);
+        assert_eq!(ass_time(3723.256), "1:02:03.25");
+        assert_eq!(srt_time(61.5), "00:01:01,500");
+
+        let style = SubtitleStyle {
+            font_name: Some("Noto, Sans".to_string()),
+            font_size: Some(24),
+            color: Some("#ffcc00".to_string()),
+            top: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            burn_filter("C:\\subs\\it's [1].srt", Some(&style)),
+            "subtitles=filename=C\\\\:/subs/it\\\\\\'s \\[1\\].srt:force_style='Fontname=Noto Sans,Fontsize=24,PrimaryColour=&H00CCFF&,Alignment=8'"
+        );
+        assert_eq!(burn_filter("a.srt", None), "subtitles=filename=a.srt");
+    }
+}
@@ -0,0 +1,692 @@
+use crate::commands::metadata::{CoverArt, MediaTagInfo, MediaTags};
+use crate::download_queue::DownloadTask;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use tauri::AppHandle;
+
+lazy_static::lazy_static! {
+    static ref TOKEN_RE: regex::Regex = regex::Regex::new(r"\{([A-Za-z0-9_]+)\}").unwrap();
+}
+
+/// Containers the editor can write, keyed by extension. Each stores tags and
+/// cover art differently.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Container {
+    Mp3,
+    Mp4,
+    Flac,
+    /// Opus and Vorbis: tags live on the audio stream, cover art is a
+    /// `METADATA_BLOCK_PICTURE` comment.
+    Ogg,
+    /// Cover art is an attachment rather than a video stream.
+    Matroska,
+    WebM,
+}
+
+impl Container {
+    fn from_path(path: &str) -> Result<Self, String> {
+        let ext = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match ext.as_str() {
+            "mp3" => Ok(Self::Mp3),
+            "m4a" | "mp4" | "m4v" | "mov" => Ok(Self::Mp4),
+            "flac" => Ok(Self::Flac),
+            "opus" | "ogg" | "oga" => Ok(Self::Ogg),
+            "mkv" | "mka" => Ok(Self::Matroska),
+            "webm" => Ok(Self::WebM),
+            _ => Err(format!("Tag editing is not supported for .{} files", ext)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Probe {
+    #[serde(default)]
+    format: ProbeFormat,
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ProbeFormat {
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeStream {
+    index: usize,
+    #[serde(default)]
+    codec_type: String,
+    #[serde(default)]
+    disposition: HashMap<String, i64>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+impl ProbeStream {
+    fn is_cover(&self) -> bool {
+        let image_attachment = self.codec_type == "attachment"
+            && self
+                .tags
+                .get("mimetype")
+                .is_some_and(|m| m.starts_with("image/"));
+        image_attachment || self.disposition.get("attached_pic") == Some(&1)
+    }
+}
+
+/// ffmpeg's generic key for each field, followed by the spellings containers
+/// report back (Vorbis comments, Matroska tags). Same order as `values`.
+const KEYS: [(&str, &[&str]); 8] = [
+    ("title", &["title"]),
+    ("artist", &["artist", "performer"]),
+    ("album", &["album"]),
+    ("album_artist", &["album_artist", "albumartist"]),
+    ("track", &["track", "tracknumber"]),
+    ("date", &["date", "year"]),
+    ("genre", &["genre"]),
+    ("comment", &["comment", "description"]),
+];
+
+fn values(tags: &MediaTags) -> [&Option<String>; 8] {
+    [
+        &tags.title,
+        &tags.artist,
+        &tags.album,
+        &tags.album_artist,
+        &tags.track,
+        &tags.date,
+        &tags.genre,
+        &tags.comment,
+    ]
+}
+
+fn values_mut(tags: &mut MediaTags) -> [&mut Option<String>; 8] {
+    [
+        &mut tags.title,
+        &mut tags.artist,
+        &mut tags.album,
+        &mut tags.album_artist,
+        &mut tags.track,
+        &mut tags.date,
+        &mut tags.genre,
+        &mut tags.comment,
+    ]
+}
+
+/// Container tags merged with those of the first audio stream, where Ogg
+/// keeps them. Keys are matched case-insensitively.
+fn parse_probe(path: &str, probe: &Probe) -> MediaTagInfo {
+    let mut found: HashMap<String, String> = HashMap::new();
+    let audio = probe.streams.iter().find(|s| s.codec_type == "audio");
+    for tags in std::iter::once(&probe.format.tags).chain(audio.map(|s| &s.tags)) {
+        for (key, value) in tags {
+            if !value.trim().is_empty() {
+                found
+                    .entry(key.to_lowercase())
+                    .or_insert_with(|| value.trim().to_string());
+            }
+        }
+    }
+
+    let mut tags = MediaTags::default();
+    for (slot, (_, aliases)) in values_mut(&mut tags).into_iter().zip(KEYS) {
+        *slot = aliases.iter().find_map(|k| found.get(*k).cloned());
+    }
+    MediaTagInfo {
+        path: path.to_string(),
+        tags,
+        has_cover: probe.streams.iter().any(ProbeStream::is_cover),
+    }
+}
+
+/// A value from yt-dlp's info JSON as tag text. `{year}` and `{iso_date}`
+/// are derived from `upload_date` (`YYYYMMDD`) when the JSON lacks them.
+fn template_value(info: &serde_json::Value, name: &str) -> String {
+    match info.get(name) {
+        Some(serde_json::Value::String(s)) => return s.clone(),
+        Some(serde_json::Value::Number(n)) => return n.to_string(),
+        Some(serde_json::Value::Bool(b)) => return b.to_string(),
+        _ => {}
+    }
+    let upload_date = info
+        .get("upload_date")
+        .and_then(|v| v.as_str())
+        .filter(|d| d.len() == 8 && d.bytes().all(|b| b.is_ascii_digit()));
+    match (name, upload_date) {
+        ("year", Some(d)) => d[..4].to_string(),
+        ("iso_date", Some(d)) => format!("{}-{}-{}", &d[..4], &d[4..6], &d[6..]),
+        _ => String::new(),
+    }
+}
+
+/// Replaces every `{field}` with the matching info JSON value; unknown
+/// fields become empty.
+pub fn render_template(template: &str, info: &serde_json::Value) -> String {
+    TOKEN_RE
+        .replace_all(template, |caps: &regex::Captures| {
+            template_value(info, &caps[1])
+        })
+        .trim()
+        .to_string()
+}
+
+/// Renders every set field of `template` against `info`.
+pub fn render_tags(template: &MediaTags, info: &serde_json::Value) -> MediaTags {
+    let mut rendered = template.clone();
+    for slot in values_mut(&mut rendered) {
+        if let Some(value) = slot.as_mut() {
+            *value = render_template(value, info);
+        }
+    }
+    rendered
+}
+
+/// The info JSON yt-dlp wrote next to `media` (`<stem>.info.json`), or the
+/// fields the task recorded when there is none.
+pub fn info_json(media: &str, task: Option<&DownloadTask>) -> serde_json::Value {
+    let stem = media.rsplit_once('.').map_or(media, |(stem, _)| stem);
+    let sidecar = format!("{}.info.json", stem);
+    if let Ok(content) = std::fs::read_to_string(&sidecar) {
+        if let Ok(info @ serde_json::Value::Object(_)) = serde_json::from_str(&content) {
+            return info;
+        }
+    }
+    let Some(task) = task else {
+        return serde_json::json!({});
+    };
+    serde_json::json!({
+        "title": task.title,
+        "uploader": task.uploader,
+        "id": task.video_id,
+        "extractor": task.extractor,
+        "webpage_url": task.url,
+        "playlist_index": task.playlist_index,
+    })
+}
+
+/// A FLAC picture block (front cover, no dimensions), base64 encoded as the
+/// `METADATA_BLOCK_PICTURE` Vorbis comment expects.
+fn block_picture(image: &[u8], mime: &str) -> String {
+    let mut block = Vec::with_capacity(image.len() + 64);
+    block.extend(3u32.to_be_bytes());
+    block.extend((mime.len() as u32).to_be_bytes());
+    block.extend(mime.as_bytes());
+    // Description, width, height, depth and palette size
+    for _ in 0..5 {
+        block.extend(0u32.to_be_bytes());
+    }
+    block.extend((image.len() as u32).to_be_bytes());
+    block.extend(image);
+    base64(&block)
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Escapes a value for an `;FFMETADATA1` file.
+fn escape_ffmetadata(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn image_mime(path: &str) -> Option<&'static str> {
+    let ext = Path::new(path).extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "png" => Some("image/png"),
+        _ => None,
+    }
+}
+
+/// How the cover is handled once any replacement image is on disk.
+enum CoverPlan<'a> {
+    Keep,
+    Remove,
+    /// A JPEG/PNG file for the muxers that take an image input.
+    Image {
+        path: &'a str,
+        mime: &'static str,
+    },
+    /// An `;FFMETADATA1` file carrying the audio stream's tags and the
+    /// picture comment; too large for a command line argument.
+    OggPicture {
+        metadata_path: &'a str,
+    },
+}
+
+/// The copy-and-retag command: every stream is copied, the edited fields
+/// are set (an empty value removes the tag) and the cover is kept, removed
+/// or replaced.
+fn write_args(
+    input: &str,
+    output: &str,
+    container: Container,
+    probe: &Probe,
+    edits: &MediaTags,
+    cover: &CoverPlan,
+) -> Result<Vec<String>, String> {
+    let mut args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-nostats".into(),
+        "-y".into(),
+        "-i".into(),
+        input.into(),
+    ];
+    let covers: Vec<usize> = probe
+        .streams
+        .iter()
+        .filter(|s| s.is_cover())
+        .map(|s| s.index)
+        .collect();
+    let mut maps: Vec<String> = Vec::new();
+    let mut cover_args: Vec<String> = Vec::new();
+
+    if container == Container::Ogg {
+        // The Ogg muxer can't take the picture stream the demuxer exposes
+        maps.push("0:a".into());
+    } else {
+        maps.push("0".into());
+        if !matches!(cover, CoverPlan::Keep) {
+            maps.extend(covers.iter().map(|i| format!("-0:{}", i)));
+        }
+    }
+
+    match cover {
+        CoverPlan::Keep | CoverPlan::Remove => {}
+        CoverPlan::Image { path, mime } => match container {
+            Container::Mp3 | Container::Mp4 | Container::Flac => {
+                args.extend(["-i".into(), path.to_string()]);
+                maps.push("1:v:0".into());
+                let n = probe
+                    .streams
+                    .iter()
+                    .filter(|s| s.codec_type == "video" && !s.is_cover())
+                    .count();
+                cover_args.extend([format!("-disposition:v:{}", n), "attached_pic".into()]);
+                if container == Container::Mp3 {
+                    cover_args.extend([
+                        format!("-metadata:s:v:{}", n),
+                        "title=Album cover".into(),
+                        format!("-metadata:s:v:{}", n),
+                        "comment=Cover (front)".into(),
+                    ]);
+                }
+            }
+            Container::Matroska => {
+                let n = probe
+                    .streams
+                    .iter()
+                    .filter(|s| s.codec_type == "attachment" && !s.is_cover())
+                    .count();
+                let name = if *mime == "image/png" {
+                    "cover.png"
+                } else {
+                    "cover.jpg"
+                };
+                cover_args.extend([
+                    "-attach".into(),
+                    path.to_string(),
+                    format!("-metadata:s:t:{}", n),
+                    format!("mimetype={}", mime),
+                    format!("-metadata:s:t:{}", n),
+                    format!("filename={}", name),
+                ]);
+            }
+            Container::WebM => return Err("WebM files can't carry cover art".to_string()),
+            Container::Ogg => return Err("Ogg cover art goes through a metadata file".into()),
+        },
+        CoverPlan::OggPicture { metadata_path } => {
+            args.extend([
+                "-f".into(),
+                "ffmetadata".into(),
+                "-i".into(),
+                metadata_path.to_string(),
+            ]);
+            cover_args.extend(["-map_metadata:s:a:0".into(), "1:g".into()]);
+        }
+    }
+
+    for map in maps {
+        args.extend(["-map".into(), map]);
+    }
+    args.extend(["-c".into(), "copy".into()]);
+    args.extend(cover_args);
+
+    let scope = if container == Container::Ogg {
+        "-metadata:s:a:0"
+    } else {
+        "-metadata"
+    };
+    for ((key, _), value) in KEYS.iter().zip(values(edits)) {
+        if let Some(value) = value {
+            args.extend([scope.into(), format!("{}={}", key, value.trim())]);
+        }
+    }
+    if container == Container::Mp3 {
+        // ID3v2.3 is what most players and tag editors read
+        args.extend(["-id3v2_version".into(), "3".into()]);
+    }
+    args.push(output.into());
+    Ok(args)
+}
+
+async fn run_quiet(binary: &str, args: &[String]) -> Result<Vec<u8>, String> {
+    #[allow(unused_mut)]
+    let mut std_command = std::process::Command::new(binary);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        std_command.creation_flags(CREATE_NO_WINDOW);
+    }
+    std_command
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut command = tokio::process::Command::from(std_command);
+    command.kill_on_drop(true);
+    let output = command
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", binary, e))?;
+    if output.status.success() {
+        return Ok(output.stdout);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let tail: Vec<&str> = stderr.lines().rev().take(3).collect();
+    Err(format!(
+        "Tagging failed: {}",
+        tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+    ))
+}
+
+async fn probe(app: &AppHandle, path: &str) -> Result<Probe, String> {
+    let ffprobe_path = crate::ytdlp::resolve_binary_path(app, "ffprobe");
+    let args: Vec<String> = [
+        "-v",
+        "error",
+        "-show_format",
+        "-show_streams",
+        "-of",
+        "json",
+        path,
+    ]
+    .map(String::from)
+    .to_vec();
+    let stdout = run_quiet(&ffprobe_path, &args).await?;
+    serde_json::from_slice(&stdout).map_err(|e| format!("Unparseable ffprobe output: {}", e))
+}
+
+/// Reads the tags and whether a cover is embedded.
+pub async fn read(app: &AppHandle, path: &str) -> Result<MediaTagInfo, String> {
+    if !Path::new(path).is_file() {
+        return Err(format!("File not found: {}", path));
+    }
+    Container::from_path(path)?;
+    Ok(parse_probe(path, &probe(app, path).await?))
+}
+
+/// Re-encodes the first picture of `input` (a cover stream or any image) to
+/// JPEG at `output`.
+async fn extract_jpeg(ffmpeg_path: &str, input: &str, output: &str) -> Result<(), String> {
+    let args: Vec<String> = [
+        "-hide_banner",
+        "-y",
+        "-i",
+        input,
+        "-map",
+        "0:v:0",
+        "-frames:v",
+        "1",
+        "-q:v",
+        "2",
+        output,
+    ]
+    .map(String::from)
+    .to_vec();
+    run_quiet(ffmpeg_path, &args).await.map(|_| ())
+}
+
+/// Applies `edits` and the cover change to `path` in place, through a
+/// temporary sibling file, and returns the tags read back.
+pub async fn write(
+    app: &AppHandle,
+    path: &str,
+    edits: &MediaTags,
+    cover: &CoverArt,
+) -> Result<MediaTagInfo, String> {
+    if !Path::new(path).is_file() {
+        return Err(format!("File not found: {}", path));
+    }
+    let container = Container::from_path(path)?;
+    let probe = probe(app, path).await?;
+    let had_cover = probe.streams.iter().any(ProbeStream::is_cover);
+    let settings = crate::ytdlp::load_settings(app);
+    let ffmpeg_path = crate::ytdlp::resolve_ffmpeg_path(app, &settings.binary_path_ffmpeg);
+
+    let scratch = std::env::temp_dir().join(format!("sceneclip-tags-{}", uuid::Uuid::new_v4()));
+    let mut temp_files: Vec<String> = Vec::new();
+    let output = crate::clips::with_infix(path, "tagging");
+    let result = async {
+        // Ogg rewrites drop the picture, so a kept cover is carried over too
+        let source = match cover {
+            CoverArt::Image { path: image } => Some(image.as_str()),
+            CoverArt::Keep if container == Container::Ogg && had_cover => Some(path),
+            _ => None,
+        };
+        let image = match source {
+            Some(source) if source == path || image_mime(source).is_none() => {
+                let jpeg = format!("{}.jpg", scratch.to_string_lossy());
+                temp_files.push(jpeg.clone());
+                extract_jpeg(&ffmpeg_path, source, &jpeg).await?;
+                Some((jpeg, "image/jpeg"))
+            }
+            Some(source) => Some((
+                source.to_string(),
+                image_mime(source).unwrap_or("image/jpeg"),
+            )),
+            None => None,
+        };
+
+        let metadata_path = format!("{}.txt", scratch.to_string_lossy());
+        let plan = match (&image, cover) {
+            (Some((image, mime)), _) if container == Container::Ogg => {
+                let bytes = std::fs::read(image).map_err(|e| e.to_string())?;
+                let mut metadata = String::from(";FFMETADATA1\n");
+                if let Some(audio) = probe.streams.iter().find(|s| s.codec_type == "audio") {
+                    for (key, value) in &audio.tags {
+                        metadata.push_str(&format!(
+                            "{}={}\n",
+                            escape_ffmetadata(key),
+                            escape_ffmetadata(value)
+                        ));
+                    }
+                }
+                metadata.push_str(&format!(
+                    "METADATA_BLOCK_PICTURE={}\n",
+                    escape_ffmetadata(&block_picture(&bytes, mime))
+                ));
+                std::fs::write(&metadata_path, metadata).map_err(|e| e.to_string())?;
+                temp_files.push(metadata_path.clone());
+                CoverPlan::OggPicture {
+                    metadata_path: &metadata_path,
+                }
+            }
+            (Some((image, mime)), _) => CoverPlan::Image { path: image, mime },
+            (None, CoverArt::Remove) => CoverPlan::Remove,
+            (None, _) => CoverPlan::Keep,
+        };
+
+        let args = write_args(path, &output, container, &probe, edits, &plan)?;
+        run_quiet(&ffmpeg_path, &args).await?;
+        std::fs::rename(&output, path).map_err(|e| format!("Failed to replace {}: {}", path, e))
+    }
+    .await;
+
+    for file in temp_files {
+        let _ = std::fs::remove_file(file);
+    }
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&output);
+        return Err(e);
+    }
+    log::info!("[Tagging] Updated tags of {} (cover: {:?})", path, cover);
+    read(app, path).await
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe_json(json: &str) -> Probe {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn reads_tags_across_containers() {
+        let opus = probe_json(
+            r#"{"format": {"tags": {"encoder": "Lavf60"}},
+                "streams": [{"index": 0, "codec_type": "audio",
+                             "tags": {"TITLE": "Song", "ARTIST": "Band", "TRACKNUMBER": "3",
+                                      "DESCRIPTION": "Live"}}]}"#,
+        );
+        let info = parse_probe("a.opus", &opus);
+        assert_eq!(info.tags.title.as_deref(), Some("Song"));
+        assert_eq!(info.tags.artist.as_deref(), Some("Band"));
+        assert_eq!(info.tags.track.as_deref(), Some("3"));
+        assert_eq!(info.tags.comment.as_deref(), Some("Live"));
+        assert!(!info.has_cover);
+
+        let mkv = probe_json(
+            r#"{"format": {"tags": {"title": "Film", "DATE": "2024"}},
+                "streams": [{"index": 0, "codec_type": "video"},
+                            {"index": 1, "codec_type": "attachment",
+                             "tags": {"mimetype": "image/jpeg", "filename": "cover.jpg"}}]}"#,
+        );
+        let info = parse_probe("a.mkv", &mkv);
+        assert_eq!(info.tags.date.as_deref(), Some("2024"));
+        assert!(info.has_cover);
+        assert!(Container::from_path("a.wav").is_err());
+    }
+
+    #[test]
+    fn write_args_per_container() {
+        let mp4 = probe_json(
+            r#"{"streams": [{"index": 0, "codec_type": "video"},
+                            {"index": 1, "codec_type": "audio"},
+                            {"index": 2, "codec_type": "video", "disposition": {"attached_pic": 1}}]}"#,
+        );
+        let edits = MediaTags {
+            title: Some("New".to_string()),
+            comment: Some(String::new()),
+            ..Default::default()
+        };
+        let cover = CoverPlan::Image {
+            path: "c.png",
+            mime: "image/png",
+        };
+        let args = write_args(
+            "a.mp4",
+            "a.tagging.mp4",
+            Container::Mp4,
+            &mp4,
+            &edits,
+            &cover,
+        )
+        .unwrap()
+        .join(" ");
+        assert!(args.contains("-i a.mp4 -i c.png -map 0 -map -0:2 -map 1:v:0 -c copy"));
+        assert!(args.contains("-disposition:v:1 attached_pic"));
+        assert!(args.ends_with("-metadata title=New -metadata comment= a.tagging.mp4"));
+
+        let args = write_args(
+            "a.mp4",
+            "o.mp4",
+            Container::Mp4,
+            &mp4,
+            &edits,
+            &CoverPlan::Keep,
+        )
+        .unwrap()
+        .join(" ");
+        assert!(args.contains("-map 0 -c copy -metadata"));
+
+        let opus = probe_json(r#"{"streams": [{"index": 0, "codec_type": "audio"}]}"#);
+        let plan = CoverPlan::OggPicture {
+            metadata_path: "m.txt",
+        };
+        let args = write_args("a.opus", "o.opus", Container::Ogg, &opus, &edits, &plan)
+            .unwrap()
+            .join(" ");
+        assert!(args.contains("-f ffmetadata -i m.txt -map 0:a -c copy -map_metadata:s:a:0 1:g"));
+        assert!(args.contains("-metadata:s:a:0 title=New"));
+        assert!(write_args("a.webm", "o.webm", Container::WebM, &opus, &edits, &cover).is_err());
+
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(escape_ffmetadata("a=b;c"), "a\\=b\\;c");
+    }
+
+    #[test]
+    fn templates_read_info_json_fields() {
+        let info = serde_json::json!({
+            "title": "Episode 4",
+            "uploader": "Channel",
+            "playlist_index": 4,
+            "upload_date": "20240131",
+        });
+        let template = MediaTags {
+            title: Some("{title}".to_string()),
+            artist: Some("{uploader}".to_string()),
+            track: Some("{playlist_index}".to_string()),
+            date: Some("{iso_date}".to_string()),
+            album: Some("{playlist} ".to_string()),
+            comment: None,
+            ..Default::default()
+        };
+        let tags = render_tags(&template, &info);
+        assert_eq!(tags.title.as_deref(), Some("Episode 4"));
+        assert_eq!(tags.artist.as_deref(), Some("Channel"));
+        assert_eq!(tags.track.as_deref(), Some("4"));
+        assert_eq!(tags.date.as_deref(), Some("2024-01-31"));
+        assert_eq!(tags.album.as_deref(), Some(""));
+        assert_eq!(tags.comment, None);
+        assert_eq!(
+            render_template("{year} - {title}", &info),
+            "2024 - Episode 4"
+        );
+    }
+}
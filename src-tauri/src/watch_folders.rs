@@ -0,0 +1,396 @@
+use crate::download_queue::{emit_queue_update, DownloadTask, QueueState};
+use crate::follow_up::{
+    collect_media_files, is_job_output, local_job_task, FollowUpAction, FollowUpJob,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Minimum time between two scans of the watched folders.
+const SCAN_INTERVAL_SECS: u64 = 5;
+/// A file counts as finished once its size and mtime stay unchanged this long.
+const SETTLE_SECS: u64 = 10;
+
+/// A directory whose new media files are processed automatically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchFolder {
+    pub id: String,
+    pub path: String,
+    pub action: FollowUpAction,
+    /// Where results go; next to the source file when unset.
+    pub output_dir: Option<String>,
+    #[serde(default)]
+    pub recursive: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub created_at: u64,
+    pub last_error: Option<String>,
+    pub last_queued_count: Option<usize>,
+    /// Files already queued, or present before the folder was watched.
+    #[serde(default)]
+    pub processed: HashSet<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Last seen size/mtime of a file that hasn't been queued yet.
+#[derive(Debug, Clone)]
+struct Observation {
+    size: u64,
+    modified: Option<SystemTime>,
+    unchanged_since: u64,
+}
+
+pub struct WatchFolderState {
+    pub folders: Arc<Mutex<Vec<WatchFolder>>>,
+    observations: Mutex<HashMap<String, Observation>>,
+    last_scan_at: AtomicU64,
+    pub persistence_path: Option<PathBuf>,
+}
+
+impl WatchFolderState {
+    pub fn new(app_handle: Option<AppHandle>) -> Self {
+        let persistence_path = app_handle
+            .as_ref()
+            .and_then(|app| app.path().app_data_dir().ok())
+            .map(|dir| dir.join("watch_folders.json"));
+
+        let state = Self {
+            folders: Arc::new(Mutex::new(Vec::new())),
+            observations: Mutex::new(HashMap::new()),
+            last_scan_at: AtomicU64::new(0),
+            persistence_path,
+        };
+        state.load();
+        state
+    }
+
+    fn load(&self) {
+        if let Some(path) = &self.persistence_path {
+            if let Ok(content) = fs::read_to_string(path) {
+                match serde_json::from_str::<Vec<WatchFolder>>(&content) {
+                    Ok(data) => {
+                        log::info!("[WatchFolders] Loaded {} watch folders", data.len());
+                        *self.folders.lock().unwrap_or_else(|e| e.into_inner()) = data;
+                    }
+                    Err(e) => log::error!("[WatchFolders] Failed to parse {:?}: {}", path, e),
+                }
+            }
+        }
+    }
+
+    pub fn save_now(&self) {
+        if let Some(path) = &self.persistence_path {
+            let folders = self.folders.lock().unwrap_or_else(|e| e.into_inner());
+            if let Ok(json) = serde_json::to_string_pretty(&*folders) {
+                if let Some(parent) = path.parent() {
+                    let _ = fs::create_dir_all(parent);
+                }
+                if let Err(e) = fs::write(path, json) {
+                    log::error!("[WatchFolders] Failed to save: {}", e);
+                }
+            }
+        }
+    }
+
+    pub fn list(&self) -> Vec<WatchFolder> {
+        self.folders
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn add(&self, folder: WatchFolder) {
+        self.folders
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(folder);
+        self.save_now();
+    }
+
+    pub fn update<F>(&self, id: &str, f: F) -> Result<WatchFolder, String>
+    where
+        F: FnOnce(&mut WatchFolder),
+    {
+        let updated = {
+            let mut folders = self.folders.lock().unwrap_or_else(|e| e.into_inner());
+            let folder = folders
+                .iter_mut()
+                .find(|w| w.id == id)
+                .ok_or_else(|| "Watch folder not found".to_string())?;
+            f(folder);
+            folder.clone()
+        };
+        self.save_now();
+        Ok(updated)
+    }
+
+    pub fn remove(&self, id: &str) -> Option<WatchFolder> {
+        let removed = {
+            let mut folders = self.folders.lock().unwrap_or_else(|e| e.into_inner());
+            let pos = folders.iter().position(|w| w.id == id)?;
+            folders.remove(pos)
+        };
+        self.save_now();
+        Some(removed)
+    }
+
+    /// True once `path` has kept the same size and mtime for `SETTLE_SECS`.
+    /// Anything else (first sighting, still growing) restarts the clock.
+    fn settled(&self, path: &str, size: u64, modified: Option<SystemTime>, now: u64) -> bool {
+        let mut observations = self.observations.lock().unwrap_or_else(|e| e.into_inner());
+        match observations.get(path) {
+            Some(o) if o.size == size && o.modified == modified => {
+                size > 0 && now >= o.unchanged_since + SETTLE_SECS
+            }
+            _ => {
+                observations.insert(
+                    path.to_string(),
+                    Observation {
+                        size,
+                        modified,
+                        unchanged_since: now,
+                    },
+                );
+                false
+            }
+        }
+    }
+
+    /// Scans every enabled folder and returns the jobs for files that have
+    /// finished appearing, marking them processed.
+    fn scan(&self, now: u64) -> Vec<DownloadTask> {
+        let mut jobs = Vec::new();
+        let mut waiting = HashSet::new();
+
+        for folder in self.list().into_iter().filter(|w| w.enabled) {
+            let files =
+                match collect_media_files(std::slice::from_ref(&folder.path), folder.recursive) {
+                    Ok(files) => files,
+                    Err(e) => {
+                        if folder.last_error.as_ref() != Some(&e) {
+                            log::warn!("[WatchFolders] Scan of {} failed: {}", folder.path, e);
+                            let _ = self.update(&folder.id, |w| w.last_error = Some(e));
+                        }
+                        continue;
+                    }
+                };
+            // Results written inside a recursive watch folder must not be picked up again
+            let files: Vec<String> = files
+                .into_iter()
+                .filter(|f| match folder.output_dir.as_deref() {
+                    Some(dir) => !Path::new(f).starts_with(dir),
+                    None => true,
+                })
+                .collect();
+            // Nor may results written next to their sources
+            let done: Vec<FollowUpJob> = folder
+                .processed
+                .iter()
+                .map(|input| FollowUpJob {
+                    action: folder.action.clone(),
+                    input_path: input.clone(),
+                    output_dir: folder.output_dir.clone(),
+                })
+                .collect();
+            let files: Vec<String> = files
+                .into_iter()
+                .filter(|f| {
+                    !done
+                        .iter()
+                        .any(|j| j.input_path != *f && is_job_output(j, f))
+                })
+                .collect();
+
+            let ready: Vec<String> = files
+                .iter()
+                .filter(|f| !folder.processed.contains(*f))
+                .filter(|f| match fs::metadata(f) {
+                    Ok(meta) => self.settled(f, meta.len(), meta.modified().ok(), now),
+                    Err(_) => false,
+                })
+                .cloned()
+                .collect();
+
+            waiting.extend(
+                files
+                    .iter()
+                    .filter(|f| !folder.processed.contains(*f) && !ready.contains(f))
+                    .cloned(),
+            );
+            let listed: HashSet<String> = files.into_iter().collect();
+            let changed = !ready.is_empty()
+                || folder.last_error.is_some()
+                || folder.processed.iter().any(|p| !listed.contains(p));
+            if changed {
+                let _ = self.update(&folder.id, |w| {
+                    // Forget deleted files so a re-added file with the same name runs again
+                    w.processed.retain(|p| listed.contains(p));
+                    w.processed.extend(ready.iter().cloned());
+                    w.last_error = None;
+                    if !ready.is_empty() {
+                        w.last_queued_count = Some(ready.len());
+                    }
+                });
+            }
+
+            for file in ready {
+                log::info!(
+                    "[WatchFolders] New file in {}: {} -> {}",
+                    folder.path,
+                    file,
+                    folder.action.label()
+                );
+                jobs.push(local_job_task(
+                    folder.action.clone(),
+                    file,
+                    folder.output_dir.clone(),
+                ));
+            }
+        }
+
+        // Only files still waiting to settle need to be remembered
+        self.observations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|path, _| waiting.contains(path));
+        jobs
+    }
+}
+
+/// Called from the queue processor tick: queues a local job for every new,
+/// settled file in the watch folders. Finished jobs reach history the same way
+/// as any completed task.
+pub fn scan_due(app: &AppHandle, queue: &Arc<QueueState>) {
+    let Some(watch) = app.try_state::<Arc<WatchFolderState>>() else {
+        return;
+    };
+
+    let now = SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    if now < watch.last_scan_at.load(Ordering::SeqCst) + SCAN_INTERVAL_SECS {
+        return;
+    }
+    watch.last_scan_at.store(now, Ordering::SeqCst);
+
+    let jobs = watch.scan(now);
+    if jobs.is_empty() {
+        return;
+    }
+    queue.enqueue_all(jobs);
+    emit_queue_update(app, queue);
+    let _ = app.emit("watch_folders_update", watch.list());
+}
+
+// ============================================================================
+// Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> WatchFolderState {
+        WatchFolderState {
+            folders: Arc::new(Mutex::new(Vec::new())),
+            observations: Mutex::new(HashMap::new()),
+            last_scan_at: AtomicU64::new(0),
+            persistence_path: None,
+        }
+    }
+
+    #[test]
+    fn file_settles_after_size_stops_changing() {
+        let watch = state();
+        assert!(!watch.settled("a.mp4", 100, None, 0));
+        assert!(!watch.settled("a.mp4", 100, None, SETTLE_SECS - 1));
+        // Still growing: the clock restarts
+        assert!(!watch.settled("a.mp4", 200, None, SETTLE_SECS));
+        assert!(!watch.settled("a.mp4", 200, None, 2 * SETTLE_SECS - 1));
+        assert!(watch.settled("a.mp4", 200, None, 2 * SETTLE_SECS));
+        // Empty placeholders never count as finished
+        assert!(!watch.settled("b.mp4", 0, None, 0));
+        assert!(!watch.settled("b.mp4", 0, None, SETTLE_SECS));
+    }
+
+    #[test]
+    fn scan_queues_new_files_once() {
+        let dir = std::env::temp_dir().join(format!("sceneclip-watch-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("old.mp4"), b"old").unwrap();
+        let old = dir.join("old.mp4").to_string_lossy().to_string();
+
+        let watch = state();
+        watch.add(WatchFolder {
+            id: "w".to_string(),
+            path: dir.to_string_lossy().to_string(),
+            action: FollowUpAction::SplitChapters,
+            output_dir: Some(dir.join("out").to_string_lossy().to_string()),
+            recursive: true,
+            enabled: true,
+            created_at: 0,
+            last_error: None,
+            last_queued_count: None,
+            processed: [old].into_iter().collect(),
+        });
+
+        fs::write(dir.join("new.mkv"), b"new").unwrap();
+        assert!(watch.scan(0).is_empty());
+        let jobs = watch.scan(SETTLE_SECS);
+        assert_eq!(jobs.len(), 1);
+        let job = jobs[0].job.as_ref().unwrap();
+        assert!(job.input_path.ends_with("new.mkv"));
+        assert!(job.output_dir.as_deref().unwrap().ends_with("out"));
+
+        // Results in the output folder and already queued files are ignored
+        fs::create_dir_all(dir.join("out")).unwrap();
+        fs::write(dir.join("out").join("new - 01 - Intro.mkv"), b"part").unwrap();
+        assert!(watch.scan(2 * SETTLE_SECS).is_empty());
+        assert!(watch.scan(3 * SETTLE_SECS).is_empty());
+        assert_eq!(watch.list()[0].processed.len(), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn results_next_to_sources_are_not_queued_again() {
+        let dir = std::env::temp_dir().join(format!("sceneclip-watch-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let watch = state();
+        watch.add(WatchFolder {
+            id: "w".to_string(),
+            path: dir.to_string_lossy().to_string(),
+            action: FollowUpAction::SplitChapters,
+            output_dir: None,
+            recursive: false,
+            enabled: true,
+            created_at: 0,
+            last_error: None,
+            last_queued_count: None,
+            processed: HashSet::new(),
+        });
+
+        fs::write(dir.join("talk.mkv"), b"talk").unwrap();
+        assert!(watch.scan(0).is_empty());
+        assert_eq!(watch.scan(SETTLE_SECS).len(), 1);
+
+        fs::write(dir.join("talk - 01 - Intro.mkv"), b"part").unwrap();
+        fs::write(dir.join("talk - notes.mkv"), b"other").unwrap();
+        assert!(watch.scan(2 * SETTLE_SECS).is_empty());
+        let jobs = watch.scan(3 * SETTLE_SECS);
+        assert_eq!(jobs.len(), 1);
+        let job = jobs[0].job.as_ref().unwrap();
+        assert!(job.input_path.ends_with("talk - notes.mkv"));
+        assert!(watch.scan(4 * SETTLE_SECS).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
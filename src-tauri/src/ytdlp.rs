@@ -4,9 +4,11 @@ use std::collections::HashSet;
 use std::fs;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use url::Url;
 
+use crate::binary_resolver::PathContext;
+
 lazy_static! {
     /// Generic tokens to exclude when extracting site keywords from yt-dlp extractors.
     /// These are common content-type or navigation words that would cause false positive
@@ -84,6 +86,47 @@ pub struct YtDlpOptions {
     pub disable_js_runtime: Option<bool>,
     // Schedule
     pub scheduled_time: Option<u64>,
+    // Subscriptions: yt-dlp records finished IDs here so re-syncs skip them
+    pub download_archive: Option<String>,
+    // Per-task --limit-rate, overrides the bandwidth schedule
+    pub speed_limit: Option<String>,
+    // Multiple named ranges of one URL: one file per clip, or a single file
+    // when `concat_clips` is set. Takes precedence over range_start/range_end.
+    pub clips: Option<Vec<ClipRange>>,
+    pub concat_clips: Option<bool>,
+    // Download a padded range and cut it locally with ffmpeg instead of
+    // trusting where --download-sections lands
+    pub precise_cut: Option<PreciseCut>,
+    // Set by the clip runner for each run; never persisted
+    #[serde(skip)]
+    pub active_clip: Option<ActiveClip>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipRange {
+    pub name: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PreciseCut {
+    /// Stream copy: fast, but the start snaps to the nearest keyframe
+    Copy,
+    /// Re-encode: frame-exact
+    Reencode,
+}
+
+/// The clip a multi-clip run is currently downloading (1-based `index`).
+/// `joined` parts are temporary files that get concatenated afterwards.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveClip {
+    pub index: u32,
+    pub count: u32,
+    pub name: String,
+    pub joined: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -129,7 +172,10 @@ pub struct AppSettings {
     pub hardware_decoding: bool,
     // Network
     pub concurrent_downloads: u32,
+    // Local jobs (compress, extract audio, ...) have their own limit
+    pub concurrent_transcodes: u32,
     pub speed_limit: String,
+    pub bandwidth_schedule: Vec<crate::bandwidth::BandwidthWindow>,
     pub use_aria2c: bool,
     pub proxy: String,
     pub user_agent: String,
@@ -184,7 +230,9 @@ impl Default for AppSettings {
             container: "mp4".to_string(),
             hardware_decoding: false,
             concurrent_downloads: 2,
+            concurrent_transcodes: 1,
             speed_limit: String::new(),
+            bandwidth_schedule: Vec::new(),
             use_aria2c: false,
             proxy: String::new(),
             user_agent: String::new(),
@@ -430,15 +478,15 @@ pub fn load_gpu_type(app: &AppHandle) -> String {
 
 // --- Logic ---
 
-pub fn resolve_binary_path(app: &AppHandle, binary_name: &str) -> String {
+pub fn resolve_binary_path(app: &impl PathContext, binary_name: &str) -> String {
     crate::binary_resolver::resolve_to_string(app, binary_name)
 }
 
-pub fn resolve_ytdlp_path(app: &AppHandle, configured_path: &str) -> String {
+pub fn resolve_ytdlp_path(app: &impl PathContext, configured_path: &str) -> String {
     crate::binary_resolver::resolve_configured(app, "yt-dlp", configured_path)
 }
 
-pub fn resolve_ffmpeg_path(app: &AppHandle, configured_path: &str) -> String {
+pub fn resolve_ffmpeg_path(app: &impl PathContext, configured_path: &str) -> String {
     crate::binary_resolver::resolve_configured(app, "ffmpeg", configured_path)
 }
 
@@ -494,6 +542,17 @@ pub fn sanitize_filename(
         }
     }
 
+    // {clip} / {clip_index} for multi-clip runs. Templates without either
+    // token get a " - {clip}" suffix so separate clips don't overwrite each other.
+    let (clip_name, clip_index) = match &options.active_clip {
+        Some(c) if !c.joined => (c.name.replace(['/', '\\'], "_"), format!("{:02}", c.index)),
+        _ => (String::new(), String::new()),
+    };
+    let has_clip_token = final_name.to_lowercase().contains("{clip");
+    if !clip_name.is_empty() && !has_clip_token {
+        final_name.push_str(" - {clip}");
+    }
+
     // Case-Insensitive Replacement for standard tokens
     let standard_vars = vec![
         ("title", title_value),
@@ -507,6 +566,8 @@ pub fn sanitize_filename(
                 .and_then(|v| v.as_str())
                 .unwrap_or("Unknown"),
         ),
+        ("clip_index", &clip_index),
+        ("clip", &clip_name),
     ];
 
     for (k, v) in standard_vars {
@@ -523,6 +584,11 @@ pub fn sanitize_filename(
         final_name = final_name.replace(&format!("{{{}}}", capitalized), v);
     }
 
+    // Drop the separator left behind by an empty clip token
+    if has_clip_token && clip_name.is_empty() {
+        final_name = final_name.trim_end_matches([' ', '-', '_']).to_string();
+    }
+
     // Legacy cleanup
     final_name = final_name
         .replace("{resolution}", &resolution)
@@ -599,6 +665,10 @@ pub fn sanitize_filename(
     {
         final_name = format!("{}{}", sanitize_segment(title_value), expected_ext);
     }
+
+    if let Some(clip) = options.active_clip.as_ref().filter(|c| c.joined) {
+        final_name = crate::clips::part_file_name(&final_name, clip.index);
+    }
     final_name
 }
 
@@ -608,7 +678,7 @@ pub async fn build_ytdlp_args(
     settings: &AppSettings,
     final_filename: &str,
     gpu_type: &str,
-    app_handle: &AppHandle,
+    app_handle: &impl PathContext,
 ) -> Vec<String> {
     log::info!(
         "Building yt-dlp arguments for: {} (File: {})",
@@ -688,9 +758,9 @@ pub async fn build_ytdlp_args(
             || settings.use_chrome_cookie_unlock;
 
         if needs_plugins {
-            if let Ok(plugins_path) = app_handle
-                .path()
-                .resolve("resources/plugins", tauri::path::BaseDirectory::Resource)
+            if let Some(plugins_path) = app_handle
+                .resource_dir()
+                .map(|dir| dir.join("resources").join("plugins"))
             {
                 args.push("--plugin-dirs".to_string());
                 args.push(plugins_path.to_string_lossy().to_string());
@@ -1119,6 +1189,11 @@ pub async fn build_ytdlp_args(
         args.push("--live-from-start".to_string());
     }
 
+    if let Some(archive) = &options.download_archive {
+        args.push("--download-archive".to_string());
+        args.push(archive.clone());
+    }
+
     if options.split_chapters.unwrap_or(false) && !options.audio_normalization.unwrap_or(false) {
         args.push("--split-chapters".to_string());
 
@@ -1205,11 +1280,10 @@ pub async fn build_ytdlp_args(
         }
     }
 
-    if !settings.speed_limit.is_empty() && !settings.speed_limit.starts_with("-") {
-        let mut limit = settings.speed_limit.trim().to_string();
-        if limit.chars().all(|c| c.is_numeric()) {
-            limit.push('K');
-        }
+    // Per-task override > time-of-day schedule > static speed_limit
+    if let crate::bandwidth::BandwidthLimit::Rate(limit) =
+        crate::bandwidth::effective_limit(settings, options)
+    {
         args.push("--limit-rate".to_string());
         args.push(limit);
     }
@@ -1521,6 +1595,34 @@ mod tests {
         assert!(!result.contains("Original Title"));
     }
 
+    #[test]
+    fn sanitize_filename_clip_tokens() {
+        let meta = serde_json::json!({ "title": "Talk", "ext": "mp4" });
+        let clip = |joined| YtDlpOptions {
+            active_clip: Some(ActiveClip {
+                index: 2,
+                count: 3,
+                name: "Q/A".to_string(),
+                joined,
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sanitize_filename("{title} [{clip_index}] {clip}", &meta, &clip(false)),
+            "Talk [02] Q_A.mp4"
+        );
+        assert_eq!(
+            sanitize_filename("{title}", &meta, &clip(false)),
+            "Talk - Q_A.mp4"
+        );
+        // Joined parts drop the clip tokens and get a part suffix instead
+        assert_eq!(
+            sanitize_filename("{title} - {clip}", &meta, &clip(true)),
+            "Talk.clip02.mp4"
+        );
+    }
+
     #[test]
     fn sanitize_filename_extension_handling() {
         let meta = serde_json::json!({
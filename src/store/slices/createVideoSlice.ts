@@ -1,6 +1,6 @@
 
 import { StateCreator } from 'zustand'
-import { AppState, DownloadTask, VideoSlice } from './types'
+import { AppState, DownloadTask, EnqueueResult, VideoSlice } from './types'
 import { invoke } from '@tauri-apps/api/core'
 import { listen, UnlistenFn } from '@tauri-apps/api/event'
 import { buildCompressedOutputPath } from '../../lib/ffmpegService'
@@ -11,8 +11,17 @@ import { stat } from '@tauri-apps/plugin-fs'
 
 // Store unlisten function to prevent listener accumulation
 let queueUpdateUnlisten: UnlistenFn | null = null
+let extensionDuplicateUnlisten: UnlistenFn | null = null
 let trailingTimeout: NodeJS.Timeout | null = null
 
+const notifySkipped = (result: EnqueueResult) => {
+    if (result.status !== 'skipped' || !result.duplicate) return
+    const where = result.duplicate.source === 'queue' ? "already in the queue" : "already downloaded"
+    notify.info("Skipped duplicate download", {
+        description: `"${result.duplicate.title}" is ${where}.`
+    });
+}
+
 export const createVideoSlice: StateCreator<AppState, [], [], VideoSlice> = (set, get) => {
 
     return {
@@ -49,6 +58,10 @@ export const createVideoSlice: StateCreator<AppState, [], [], VideoSlice> = (set
                 queueUpdateUnlisten()
                 queueUpdateUnlisten = null
             }
+            if (extensionDuplicateUnlisten) {
+                extensionDuplicateUnlisten()
+                extensionDuplicateUnlisten = null
+            }
             if (trailingTimeout) {
                 clearTimeout(trailingTimeout)
                 trailingTimeout = null
@@ -69,7 +82,6 @@ export const createVideoSlice: StateCreator<AppState, [], [], VideoSlice> = (set
 
                 if (now - lastUpdate > THROTTLE_MS) {
                     // Leading edge: Update immediately if outside window
-                    console.log("[VideoSlice] queue_update RECEIVED:", event.payload?.length || 0, "tasks")
                     set({
                         tasks: event.payload,
                         tasksById: normalizeTasks(event.payload)
@@ -79,7 +91,6 @@ export const createVideoSlice: StateCreator<AppState, [], [], VideoSlice> = (set
                     // Trailing edge: Schedule update for end of window
                     const remaining = THROTTLE_MS - (now - lastUpdate)
                     trailingTimeout = setTimeout(() => {
-                        console.log("[VideoSlice] queue_update TRAILING:", event.payload?.length || 0, "tasks")
                         set({
                             tasks: event.payload,
                             tasksById: normalizeTasks(event.payload)
@@ -89,16 +100,20 @@ export const createVideoSlice: StateCreator<AppState, [], [], VideoSlice> = (set
                     }, remaining)
                 }
             });
+
+            // The extension is answered before its duplicate check finishes
+            extensionDuplicateUnlisten = await listen<EnqueueResult>('extension-download-duplicate', (event) => {
+                notifySkipped(event.payload)
+            });
         },
 
 
         addTask: async (url, options) => {
-            console.log("[VideoSlice] addTask called:", { url, options });
-            // No duplicate check needed here, backend handles logic or we allow it.
+            // Duplicates are detected by the backend, which skips them by default
             try {
                 // Pass options directly. Backend expects camelCase keys matching YtDlpOptions
-                await invoke('add_to_queue', { url, options });
-                console.log("[VideoSlice] addTask success (invoke returned)");
+                const result = await invoke<EnqueueResult>('add_to_queue', { url, options });
+                notifySkipped(result);
                 // No need to manually update state, the event 'queue_update' will fire from backend
             } catch (e) {
                 console.error("[VideoSlice] addTask failed:", e);
@@ -201,11 +216,13 @@ export const createVideoSlice: StateCreator<AppState, [], [], VideoSlice> = (set
             const interrupted = get().tasks.filter(t =>
                 t.status === 'stopped' && (t.statusDetail === 'Interrupted by Restart' || t.statusDetail?.includes('Interrupted'))
             );
-            interrupted.forEach(t => {
-                invoke('add_to_queue', {
-                    url: t.url,
-                    options: t.options || t._options || {}
-                });
+            interrupted.forEach(async t => {
+                // Requeue in place so the partial download is continued, not restarted
+                try {
+                    await invoke('retry_task', { id: t.id });
+                } catch (e) {
+                    console.error("Failed to recover task:", e);
+                }
             });
             return interrupted.length;
         },
@@ -45,6 +45,27 @@ export interface DownloadTask {
   options?: DownloadOptions // Mapping from Backend 'options'
 }
 
+export type EnqueueStatus = 'added' | 'skipped' | 'redownloaded' | 'renamed'
+
+// Existing task an add request collided with (backend `DuplicateMatch`)
+export interface DuplicateMatch {
+  taskId: string
+  title: string
+  url: string
+  status: DownloadStatus
+  filePath?: string
+  source: 'queue' | 'history'
+  matchedOn: 'url' | 'videoId'
+}
+
+// Returned by `add_to_queue`; `taskId` is missing when the add was skipped
+export interface EnqueueResult {
+  status: EnqueueStatus
+  taskId?: string
+  duplicate?: DuplicateMatch
+  filename?: string
+}
+
 
 
 export interface UISlice {